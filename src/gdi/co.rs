@@ -229,6 +229,8 @@ const_ordinary! { HS: i32;
 }
 
 const_bitflag! { LR: u32;
+	/// [`HCURSOR::CreateIconFromResourceEx`](crate::prelude::gdi_Hcursor::CreateIconFromResourceEx),
+	/// [`HICON::CreateIconFromResourceEx`](crate::prelude::gdi_Hicon::CreateIconFromResourceEx),
 	/// [`HINSTANCE::LoadImageBitmap`](crate::prelude::gdi_Hinstance::LoadImageBitmap),
 	/// [`HINSTANCE::LoadImageCursor`](crate::prelude::gdi_Hinstance::LoadImageCursor)
 	/// and
@@ -111,5 +111,6 @@ extern_sys! { "msimg32";
 }
 
 extern_sys! { "user32";
+	CreateIconFromResourceEx(PCVOID, u32, BOOL, u32, i32, i32, u32) -> HANDLE
 	LoadImageW(HANDLE, PCSTR, u32, i32, i32, u32) -> HANDLE // returns GdiObjectGuard, so needs gdi feature
 }
@@ -0,0 +1,47 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::co;
+use crate::decl::*;
+use crate::gdi::ffi;
+use crate::guard::*;
+use crate::kernel::privs::*;
+use crate::prelude::*;
+
+impl gdi_Hcursor for HCURSOR {}
+
+/// This trait is enabled with the `gdi` feature, and provides methods for
+/// [`HCURSOR`](crate::HCURSOR).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait gdi_Hcursor: user_Hcursor {
+	/// [`CreateIconFromResourceEx`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconfromresourceex)
+	/// function for a cursor.
+	///
+	/// The resource bits are the hotspot coordinates, as two `u16` values,
+	/// followed by a DIB or a PNG image, as stored in an `RT_CURSOR` resource.
+	#[must_use]
+	fn CreateIconFromResourceEx(
+		res_bits: &[u8],
+		sz: SIZE,
+		flags: co::LR,
+	) -> SysResult<DestroyCursorGuard>
+	{
+		unsafe {
+			ptr_to_sysresult_handle(
+				ffi::CreateIconFromResourceEx(
+					res_bits.as_ptr() as _,
+					res_bits.len() as _,
+					0,
+					0x0003_0000,
+					sz.cx,
+					sz.cy,
+					flags.raw(),
+				),
+			).map(|h| DestroyCursorGuard::new(h))
+		}
+	}
+}
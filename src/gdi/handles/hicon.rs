@@ -0,0 +1,60 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::co;
+use crate::decl::*;
+use crate::gdi::ffi;
+use crate::guard::*;
+use crate::kernel::privs::*;
+use crate::prelude::*;
+
+impl gdi_Hicon for HICON {}
+
+/// This trait is enabled with the `gdi` feature, and provides methods for
+/// [`HICON`](crate::HICON).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait gdi_Hicon: user_Hicon {
+	/// [`CreateIconFromResourceEx`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconfromresourceex)
+	/// function for an icon.
+	///
+	/// The resource bits are a DIB or a PNG image, as stored in an `.ico` file
+	/// or in an `RT_ICON` resource.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let png_bytes: Vec<u8>; // loaded somewhere
+	/// # let png_bytes = Vec::<u8>::default();
+	///
+	/// let hicon = w::HICON::CreateIconFromResourceEx(
+	///     &png_bytes, w::SIZE::new(32, 32), co::LR::DEFAULTCOLOR)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	fn CreateIconFromResourceEx(
+		res_bits: &[u8],
+		sz: SIZE,
+		flags: co::LR,
+	) -> SysResult<DestroyIconGuard>
+	{
+		unsafe {
+			ptr_to_sysresult_handle(
+				ffi::CreateIconFromResourceEx(
+					res_bits.as_ptr() as _,
+					res_bits.len() as _,
+					1,
+					0x0003_0000,
+					sz.cx,
+					sz.cy,
+					flags.raw(),
+				),
+			).map(|h| DestroyIconGuard::new(h))
+		}
+	}
+}
@@ -2,7 +2,9 @@ mod gdi_traits;
 mod hbitmap;
 mod hbrush;
 mod hdc;
+mod hcursor;
 mod hfont;
+mod hicon;
 mod hinstance;
 mod hpalette;
 mod hpen;
@@ -19,7 +21,9 @@ pub mod traits {
 	pub use super::hbitmap::gdi_Hbitmap;
 	pub use super::hbrush::gdi_Hbrush;
 	pub use super::hdc::gdi_Hdc;
+	pub use super::hcursor::gdi_Hcursor;
 	pub use super::hfont::gdi_Hfont;
+	pub use super::hicon::gdi_Hicon;
 	pub use super::hinstance::gdi_Hinstance;
 	pub use super::hpalette::gdi_Hpalette;
 	pub use super::hpen::gdi_Hpen;
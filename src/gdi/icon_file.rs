@@ -0,0 +1,320 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::kernel::privs::bytes::*;
+use crate::prelude::*;
+
+/// Signature which starts every PNG stream.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Contents of an `.ico` or `.cur` file, which is an
+/// [`ICONDIR`](https://learn.microsoft.com/en-us/previous-versions/ms997538(v=msdn.10))
+/// header followed by the images, each one either a DIB or a PNG stream.
+///
+/// The bytes are parsed with [`IconFile::parse`](crate::IconFile::parse), and
+/// written with [`IconFile::serialize`](crate::IconFile::serialize).
+///
+/// # Examples
+///
+/// Loading the icon which best fits the small icon size of the current DPI:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let f = w::FileMapped::open("C:\\Temp\\app.ico", w::FileAccess::ExistingReadOnly)?;
+/// let ico = w::IconFile::parse(f.as_slice())?;
+///
+/// let dpi = w::HWND::DESKTOP.GetDC()?.GetDeviceCaps(co::GDC::LOGPIXELSY) as u32;
+/// let hicon = ico.to_hicon(16 * dpi / 96)?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IconFile {
+	/// Whether this is a `.cur` file, otherwise an `.ico` file.
+	pub is_cursor: bool,
+	/// The images, in the order they appear in the file.
+	pub images: Vec<IconImage>,
+}
+
+/// An image of an [`IconFile`](crate::IconFile).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IconImage {
+	/// Width in pixels.
+	pub width: u32,
+	/// Height in pixels.
+	pub height: u32,
+	/// Number of bits per pixel.
+	pub bit_count: u16,
+	/// Hotspot coordinates, used only by cursors.
+	pub hotspot: (u16, u16),
+	/// The image data: either a PNG stream or a DIB, which is a
+	/// [`BITMAPINFOHEADER`](crate::BITMAPINFOHEADER) followed by the color
+	/// table, the XOR bitmap and the AND mask, with the height doubled.
+	pub data: Vec<u8>,
+}
+
+impl IconImage {
+	/// Creates a new image from a PNG stream or a DIB, reading its dimensions
+	/// and bit count from the data itself.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is neither a PNG stream nor a DIB.
+	pub fn from_data(data: Vec<u8>, hotspot: (u16, u16)) -> SysResult<Self> {
+		let (width, height, bit_count) = if data.starts_with(&PNG_SIGNATURE) {
+			if slice_at(&data, 12, 4)? != b"IHDR" {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			let channels = match slice_at(&data, 25, 1)?[0] { // PNG color type
+				0 | 3 => 1, // grayscale or palette
+				2 => 3, // RGB
+				4 => 2, // grayscale and alpha
+				6 => 4, // RGBA
+				_ => return Err(co::ERROR::INVALID_DATA),
+			};
+			(
+				read_u32_be(&data, 16)?,
+				read_u32_be(&data, 20)?,
+				slice_at(&data, 24, 1)?[0] as u16 * channels,
+			)
+		} else {
+			match read_u32(&data, 0)? {
+				12 => ( // BITMAPCOREHEADER
+					read_u16(&data, 4)? as u32,
+					read_u16(&data, 6)? as u32 / 2,
+					read_u16(&data, 10)?,
+				),
+				n if n >= 40 => ( // BITMAPINFOHEADER or later
+					read_u32(&data, 4)? as i32 as u32,
+					(read_u32(&data, 8)? as i32).unsigned_abs() / 2,
+					read_u16(&data, 14)?,
+				),
+				_ => return Err(co::ERROR::INVALID_DATA),
+			}
+		};
+
+		if width == 0 || height == 0 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		Ok(Self { width, height, bit_count, hotspot, data })
+	}
+
+	/// Creates a new image by reading the bitmaps of an icon with
+	/// [`HICON::GetIconInfo`](crate::prelude::user_Hicon::GetIconInfo). The
+	/// image is stored as a 32-bit DIB.
+	///
+	/// If the handle was created as a cursor, its hotspot is kept.
+	pub fn from_hicon(hicon: &HICON) -> SysResult<Self> {
+		let ii = hicon.GetIconInfo()?;
+		let hotspot = if ii.fIcon() {
+			(0, 0)
+		} else {
+			(ii.xHotspot as _, ii.yHotspot as _)
+		};
+		let hbm_mask = unsafe { DeleteObjectGuard::new(ii.hbmMask) };
+		let hbm_color = unsafe { DeleteObjectGuard::new(ii.hbmColor) };
+
+		let mut bmp = BITMAP::default();
+		hbm_mask.GetObject(&mut bmp)?;
+		let is_mono = hbm_color.as_opt().is_none();
+		let (cx, cy) = if is_mono {
+			(bmp.bmWidth, bmp.bmHeight / 2) // AND mask on top, XOR bitmap below
+		} else {
+			(bmp.bmWidth, bmp.bmHeight)
+		};
+
+		let hdc = HWND::DESKTOP.GetDC()?;
+		let (xor_bits, mask_bits) = if is_mono {
+			let mut bits = dib_bits_32(&hdc, &hbm_mask, cx, cy * 2)?;
+			let mask_bits = bits.split_off((cx * cy * 4) as usize); // bottom-up, so XOR comes first
+			(bits, mask_bits)
+		} else {
+			(
+				dib_bits_32(&hdc, &hbm_color, cx, cy)?,
+				dib_bits_32(&hdc, &hbm_mask, cx, cy)?,
+			)
+		};
+
+		let and_stride = ((cx + 31) / 32 * 4) as usize;
+		let mut and_bits = vec![0u8; and_stride * cy as usize];
+		mask_bits.chunks_exact(4)
+			.enumerate()
+			.filter(|(_, px)| px[..3].iter().any(|c| *c != 0))
+			.for_each(|(idx, _)| {
+				let (y, x) = (idx / cx as usize, idx % cx as usize);
+				and_bits[y * and_stride + x / 8] |= 0x80 >> (x % 8);
+			});
+
+		let mut bih = BITMAPINFOHEADER::default();
+		bih.biWidth = cx;
+		bih.biHeight = cy * 2;
+		bih.biPlanes = 1;
+		bih.biBitCount = 32;
+		bih.biCompression = co::BI::RGB;
+		bih.biSizeImage = (xor_bits.len() + and_bits.len()) as _;
+
+		let mut data = bih.serialize().to_vec();
+		data.extend_from_slice(&xor_bits);
+		data.extend_from_slice(&and_bits);
+		Self::from_data(data, hotspot)
+	}
+
+	/// Tells whether the image data is a PNG stream, otherwise it's a DIB.
+	#[must_use]
+	pub fn is_png(&self) -> bool {
+		self.data.starts_with(&PNG_SIGNATURE)
+	}
+
+	/// Creates an icon from the image with
+	/// [`HICON::CreateIconFromResourceEx`](crate::prelude::gdi_Hicon::CreateIconFromResourceEx),
+	/// stretched to the given size.
+	#[must_use]
+	pub fn to_hicon(&self, sz: SIZE) -> SysResult<DestroyIconGuard> {
+		HICON::CreateIconFromResourceEx(&self.data, sz, co::LR::DEFAULTCOLOR)
+	}
+
+	/// Creates a cursor from the image with
+	/// [`HCURSOR::CreateIconFromResourceEx`](crate::prelude::gdi_Hcursor::CreateIconFromResourceEx),
+	/// stretched to the given size.
+	#[must_use]
+	pub fn to_hcursor(&self, sz: SIZE) -> SysResult<DestroyCursorGuard> {
+		let mut res_bits = Vec::<u8>::with_capacity(4 + self.data.len());
+		res_bits.extend_from_slice(&self.hotspot.0.to_le_bytes());
+		res_bits.extend_from_slice(&self.hotspot.1.to_le_bytes());
+		res_bits.extend_from_slice(&self.data);
+		HCURSOR::CreateIconFromResourceEx(&res_bits, sz, co::LR::DEFAULTCOLOR)
+	}
+}
+
+impl IconFile {
+	/// Parses the bytes of an `.ico` or `.cur` file.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is malformed.
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		if read_u16(data, 0)? != 0 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let is_cursor = match read_u16(data, 2)? {
+			1 => false,
+			2 => true,
+			_ => return Err(co::ERROR::INVALID_DATA),
+		};
+		let count = read_u16(data, 4)? as usize;
+
+		let images = (0..count)
+			.map(|idx| {
+				let entry = slice_at(data, 6 + idx * 16, 16)?; // ICONDIRENTRY
+				let img_sz = read_u32(entry, 8)? as usize;
+				let img_off = read_u32(entry, 12)? as usize;
+				let hotspot = if is_cursor {
+					(read_u16(entry, 4)?, read_u16(entry, 6)?)
+				} else {
+					(0, 0)
+				};
+				IconImage::from_data(slice_at(data, img_off, img_sz)?.to_vec(), hotspot)
+			})
+			.collect::<SysResult<Vec<_>>>()?;
+
+		Ok(Self { is_cursor, images })
+	}
+
+	/// Serializes the images into the bytes of an `.ico` or `.cur` file, which
+	/// can be parsed back with [`IconFile::parse`](crate::IconFile::parse).
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::default();
+		buf.extend_from_slice(&0u16.to_le_bytes());
+		buf.extend_from_slice(&(if self.is_cursor { 2u16 } else { 1u16 }).to_le_bytes());
+		buf.extend_from_slice(&(self.images.len() as u16).to_le_bytes());
+
+		let mut img_off = 6 + self.images.len() * 16;
+		for img in self.images.iter() {
+			buf.push(if img.width >= 256 { 0 } else { img.width as _ }); // zero means 256
+			buf.push(if img.height >= 256 { 0 } else { img.height as _ });
+			buf.push(match img.bit_count {
+				1..=7 => 1 << img.bit_count, // palette size
+				_ => 0,
+			});
+			buf.push(0); // reserved
+			let (field1, field2) = if self.is_cursor {
+				img.hotspot
+			} else {
+				(1, img.bit_count) // planes and bit count
+			};
+			buf.extend_from_slice(&field1.to_le_bytes());
+			buf.extend_from_slice(&field2.to_le_bytes());
+			buf.extend_from_slice(&(img.data.len() as u32).to_le_bytes());
+			buf.extend_from_slice(&(img_off as u32).to_le_bytes());
+			img_off += img.data.len();
+		}
+
+		self.images.iter()
+			.for_each(|img| buf.extend_from_slice(&img.data));
+		buf
+	}
+
+	/// Returns the image which best fits the given size in pixels: the smallest
+	/// one which is at least that size, or the largest one if none is. Among
+	/// images of the same size, the one with more colors is chosen.
+	///
+	/// Returns `None` if there are no images.
+	#[must_use]
+	pub fn best_for_size(&self, size: u32) -> Option<&IconImage> {
+		let dim = |img: &IconImage| img.width.max(img.height);
+		self.images.iter()
+			.filter(|img| dim(img) >= size)
+			.min_by_key(|img| (dim(img), std::cmp::Reverse(img.bit_count)))
+			.or_else(|| self.images.iter()
+				.max_by_key(|img| (dim(img), img.bit_count)))
+	}
+
+	/// Returns the image which best fits the given size, in pixels at 96 DPI,
+	/// scaled to the given DPI. For example, a `base_size` of 16 at 144 DPI
+	/// looks for a 24 pixel image.
+	///
+	/// Returns `None` if there are no images.
+	#[must_use]
+	pub fn best_for_dpi(&self, base_size: u32, dpi: u32) -> Option<&IconImage> {
+		self.best_for_size(base_size * dpi / 96)
+	}
+
+	/// Creates an icon with the given size in pixels, from the image chosen by
+	/// [`IconFile::best_for_size`](crate::IconFile::best_for_size).
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if there are no images.
+	#[must_use]
+	pub fn to_hicon(&self, size: u32) -> SysResult<DestroyIconGuard> {
+		self.best_for_size(size)
+			.ok_or(co::ERROR::INVALID_DATA)?
+			.to_hicon(SIZE::new(size as _, size as _))
+	}
+
+	/// Creates a cursor with the given size in pixels, from the image chosen by
+	/// [`IconFile::best_for_size`](crate::IconFile::best_for_size).
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if there are no images.
+	#[must_use]
+	pub fn to_hcursor(&self, size: u32) -> SysResult<DestroyCursorGuard> {
+		self.best_for_size(size)
+			.ok_or(co::ERROR::INVALID_DATA)?
+			.to_hcursor(SIZE::new(size as _, size as _))
+	}
+}
+
+/// Retrieves the pixels of a bitmap as a bottom-up, 32-bit DIB.
+fn dib_bits_32(hdc: &HDC, hbm: &HBITMAP, cx: i32, cy: i32) -> SysResult<Vec<u8>> {
+	let mut bi = BITMAPINFO::default();
+	bi.bmiHeader.biWidth = cx;
+	bi.bmiHeader.biHeight = cy;
+	bi.bmiHeader.biPlanes = 1;
+	bi.bmiHeader.biBitCount = 32;
+	bi.bmiHeader.biCompression = co::BI::RGB;
+
+	let mut buf = vec![0u8; (cx * cy * 4) as usize];
+	unsafe {
+		hdc.GetDIBits(hbm, 0, cy as _, Some(&mut buf), &mut bi, co::DIB::RGB_COLORS)?;
+	}
+	Ok(buf)
+}
@@ -3,6 +3,8 @@
 mod enums;
 mod funcs;
 mod handles;
+mod icon_file;
+mod pixel_buffer;
 mod structs;
 
 pub(in crate::gdi) mod ffi;
@@ -15,6 +17,8 @@ pub mod decl {
 	pub use super::enums::*;
 	pub use super::funcs::*;
 	pub use super::handles::decl::*;
+	pub use super::icon_file::{IconFile, IconImage};
+	pub use super::pixel_buffer::PixelBuffer;
 	pub use super::structs::*;
 }
 
@@ -0,0 +1,370 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::kernel::privs::bytes::*;
+use crate::prelude::*;
+
+/// A 32-bit image in memory, with its pixels stored top-down in the blue,
+/// green, red, alpha order – the same layout of a top-down 32-bit DIB.
+///
+/// It can be decoded from and encoded to `.bmp` files and packed DIBs, and
+/// converted from and to [`HBITMAP`](crate::HBITMAP).
+///
+/// # Examples
+///
+/// Saving a screenshot of a window:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let hwnd: w::HWND; // initialized somewhere
+/// # let hwnd = w::HWND::NULL;
+///
+/// let rc = hwnd.GetClientRect()?;
+/// let hdc = hwnd.GetDC()?;
+/// let hbmp = hdc.CreateCompatibleBitmap(rc.right, rc.bottom)?;
+/// let hdc_mem = hdc.CreateCompatibleDC()?;
+/// let _prev = hdc_mem.SelectObject(&*hbmp)?;
+/// hdc_mem.BitBlt(w::POINT::default(), w::SIZE::new(rc.right, rc.bottom),
+///     &hdc, w::POINT::default(), co::ROP::SRCCOPY)?;
+/// drop(_prev);
+///
+/// let pixels = w::PixelBuffer::from_hbitmap(&hbmp)?;
+/// let fo = w::File::open("C:\\Temp\\shot.bmp", w::FileAccess::OpenOrCreateRW)?;
+/// fo.set_size(0)?;
+/// fo.write(&pixels.to_bmp(false))?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PixelBuffer {
+	width: u32,
+	height: u32,
+	data: Vec<u8>,
+}
+
+impl PixelBuffer {
+	/// Creates a new buffer with all pixels set to zero, which is transparent
+	/// black.
+	#[must_use]
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			data: vec![0x00; width as usize * height as usize * 4],
+		}
+	}
+
+	/// Creates a new buffer over existing pixel data, which must have exactly
+	/// `width * height * 4` bytes, in the blue, green, red, alpha order, with
+	/// the rows stored top-down.
+	///
+	/// Fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if the data length doesn't match.
+	pub fn from_bgra(width: u32, height: u32, data: Vec<u8>) -> SysResult<Self> {
+		if data.len() != width as usize * height as usize * 4 {
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+		Ok(Self { width, height, data })
+	}
+
+	/// Decodes the contents of a `.bmp` file, which is a
+	/// [`BITMAPFILEHEADER`](crate::BITMAPFILEHEADER) followed by a DIB.
+	///
+	/// See [`PixelBuffer::from_dib`](crate::PixelBuffer::from_dib) for the
+	/// supported formats.
+	pub fn from_bmp(data: &[u8]) -> SysResult<Self> {
+		if slice_at(data, 0, 2)? != b"BM" {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let bits_off = read_u32(data, 10)? as usize;
+		let dib = slice_at(data, 14, data.len().saturating_sub(14))?;
+		Self::decode(dib, Some(bits_off.checked_sub(14).ok_or(co::ERROR::INVALID_DATA)?))
+	}
+
+	/// Decodes a packed DIB, which is a
+	/// [`BITMAPINFOHEADER`](crate::BITMAPINFOHEADER) – or any of its later
+	/// versions, or the old `BITMAPCOREHEADER` – followed by the color table
+	/// and the pixels, like the `CF_DIB` clipboard format.
+	///
+	/// Supports 1, 4, 8, 16, 24 and 32 bits per pixel, with `BI_RGB` and
+	/// `BI_BITFIELDS` compression, stored either top-down or bottom-up. Pixels
+	/// in formats without alpha are decoded as opaque; the fourth byte of
+	/// 32-bit `BI_RGB` pixels is kept as it is.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is malformed, or with
+	/// [`co::ERROR::NOT_SUPPORTED`](crate::co::ERROR::NOT_SUPPORTED) if the
+	/// format is not supported, like RLE, JPEG or PNG compression.
+	pub fn from_dib(data: &[u8]) -> SysResult<Self> {
+		Self::decode(data, None)
+	}
+
+	fn decode(dib: &[u8], bits_off: Option<usize>) -> SysResult<Self> {
+		let header_sz = read_u32(dib, 0)? as usize;
+		let is_core = header_sz == 12;
+		let (width, height, bit_count, compression, clr_used) = if is_core {
+			(
+				read_u16(dib, 4)? as i32,
+				read_u16(dib, 6)? as i32,
+				read_u16(dib, 10)?,
+				co::BI::RGB,
+				0,
+			)
+		} else if header_sz >= 40 {
+			(
+				read_u32(dib, 4)? as i32,
+				read_u32(dib, 8)? as i32,
+				read_u16(dib, 14)?,
+				unsafe { co::BI::from_raw(read_u32(dib, 16)?) },
+				read_u32(dib, 32)? as usize,
+			)
+		} else {
+			return Err(co::ERROR::INVALID_DATA);
+		};
+
+		if width <= 0 || height == 0 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let is_top_down = height < 0;
+		let (width, height) = (width as u32, height.unsigned_abs());
+
+		let mut pos = header_sz;
+		let masks = match compression {
+			co::BI::RGB => match bit_count {
+				16 => Some([0x7c00, 0x03e0, 0x001f, 0]),
+				1 | 4 | 8 | 24 | 32 => None,
+				_ => return Err(co::ERROR::NOT_SUPPORTED),
+			},
+			co::BI::BITFIELDS if bit_count == 16 || bit_count == 32 => {
+				let mask_src = if header_sz >= 52 {
+					slice_at(dib, 40, 16)? // masks are part of the header
+				} else {
+					pos += 12; // masks come right after the header
+					slice_at(dib, 40, 12)?
+				};
+				Some([
+					read_u32(mask_src, 0)?,
+					read_u32(mask_src, 4)?,
+					read_u32(mask_src, 8)?,
+					if header_sz >= 56 { read_u32(mask_src, 12)? } else { 0 },
+				])
+			},
+			_ => return Err(co::ERROR::NOT_SUPPORTED),
+		};
+
+		let palette = if bit_count <= 8 {
+			let entry_sz = if is_core { 3 } else { 4 };
+			let count = if clr_used == 0 { 1 << bit_count } else { clr_used.min(256) };
+			let palette = slice_at(dib, pos, count * entry_sz)?
+				.chunks_exact(entry_sz)
+				.map(|e| [e[0], e[1], e[2], 0xff])
+				.collect::<Vec<_>>();
+			pos += count * entry_sz;
+			palette
+		} else {
+			Vec::default()
+		};
+
+		let stride = (width as usize * bit_count as usize).div_ceil(32) * 4;
+		let bits = slice_at(dib,
+			bits_off.unwrap_or(pos),
+			stride.checked_mul(height as usize).ok_or(co::ERROR::INVALID_DATA)?,
+		)?;
+
+		let mut buf = Self::new(width, height);
+		for y in 0..height as usize {
+			let src_y = if is_top_down { y } else { height as usize - 1 - y };
+			let row = &bits[src_y * stride..(src_y + 1) * stride];
+			let dest = &mut buf.data[y * width as usize * 4..(y + 1) * width as usize * 4];
+
+			for (x, px) in dest.chunks_exact_mut(4).enumerate() {
+				px.copy_from_slice(&match (bit_count, masks) {
+					(1 | 4 | 8, _) => {
+						let bit_pos = x * bit_count as usize;
+						let byte = row[bit_pos / 8];
+						let idx = (byte >> (8 - bit_count as usize - bit_pos % 8)) & ((1 << bit_count) - 1) as u8;
+						*palette.get(idx as usize).ok_or(co::ERROR::INVALID_DATA)?
+					},
+					(24, _) => [row[x * 3], row[x * 3 + 1], row[x * 3 + 2], 0xff],
+					(32, None) => [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]],
+					(_, Some(masks)) => {
+						let val = if bit_count == 16 {
+							u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+						} else {
+							u32::from_le_bytes([row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]])
+						};
+						[
+							extract_channel(val, masks[2]),
+							extract_channel(val, masks[1]),
+							extract_channel(val, masks[0]),
+							if masks[3] == 0 { 0xff } else { extract_channel(val, masks[3]) },
+						]
+					},
+					_ => unreachable!(),
+				});
+			}
+		}
+		Ok(buf)
+	}
+
+	/// Retrieves the pixels of a bitmap with
+	/// [`HDC::GetDIBits`](crate::prelude::gdi_Hdc::GetDIBits).
+	///
+	/// The bitmap must not be selected into a device context.
+	pub fn from_hbitmap(hbmp: &HBITMAP) -> SysResult<Self> {
+		let mut bmp = BITMAP::default();
+		hbmp.GetObject(&mut bmp)?;
+		let mut buf = Self::new(bmp.bmWidth as _, bmp.bmHeight as _);
+
+		let mut bi = Self::bitmap_info(bmp.bmWidth, bmp.bmHeight);
+		let hdc = HWND::DESKTOP.GetDC()?;
+		unsafe {
+			hdc.GetDIBits(hbmp, 0, bmp.bmHeight as _,
+				Some(&mut buf.data), &mut bi, co::DIB::RGB_COLORS)?;
+		}
+		Ok(buf)
+	}
+
+	/// Creates a bitmap compatible with the screen, with
+	/// [`HDC::CreateCompatibleBitmap`](crate::prelude::gdi_Hdc::CreateCompatibleBitmap),
+	/// and copies the pixels into it with
+	/// [`HDC::SetDIBits`](crate::prelude::gdi_Hdc::SetDIBits).
+	#[must_use]
+	pub fn to_hbitmap(&self) -> SysResult<DeleteObjectGuard<HBITMAP>> {
+		let hdc = HWND::DESKTOP.GetDC()?;
+		let hbmp = hdc.CreateCompatibleBitmap(self.width as _, self.height as _)?;
+		hdc.SetDIBits(&hbmp, 0, self.height, &self.data,
+			&Self::bitmap_info(self.width as _, self.height as _), co::DIB::RGB_COLORS)?;
+		Ok(hbmp)
+	}
+
+	/// Encodes the pixels as a packed DIB, which is a
+	/// [`BITMAPINFOHEADER`](crate::BITMAPINFOHEADER) followed by the pixels,
+	/// stored bottom-up with `BI_RGB` compression. If `with_alpha` is `true`,
+	/// the pixels are written with 32 bits, otherwise the alpha is discarded
+	/// and they're written with 24 bits.
+	#[must_use]
+	pub fn to_dib(&self, with_alpha: bool) -> Vec<u8> {
+		let bit_count: u16 = if with_alpha { 32 } else { 24 };
+		let stride = (self.width as usize * bit_count as usize).div_ceil(32) * 4;
+
+		let mut bih = BITMAPINFOHEADER::default();
+		bih.biWidth = self.width as _;
+		bih.biHeight = self.height as _;
+		bih.biPlanes = 1;
+		bih.biBitCount = bit_count;
+		bih.biCompression = co::BI::RGB;
+		bih.biSizeImage = (stride * self.height as usize) as _;
+
+		let mut buf = bih.serialize().to_vec();
+		buf.reserve(bih.biSizeImage as _);
+		let row_len = self.width as usize * 4; // zero if the buffer is empty
+		for y in (0..self.height as usize).rev() { // bottom-up
+			let row = &self.data[y * row_len..(y + 1) * row_len];
+			let row_start = buf.len();
+			if with_alpha {
+				buf.extend_from_slice(row);
+			} else {
+				row.chunks_exact(4)
+					.for_each(|px| buf.extend_from_slice(&px[..3]));
+			}
+			buf.resize(row_start + stride, 0x00);
+		}
+		buf
+	}
+
+	/// Encodes the pixels as the contents of a `.bmp` file, which is a
+	/// [`BITMAPFILEHEADER`](crate::BITMAPFILEHEADER) followed by the DIB
+	/// produced by [`PixelBuffer::to_dib`](crate::PixelBuffer::to_dib).
+	#[must_use]
+	pub fn to_bmp(&self, with_alpha: bool) -> Vec<u8> {
+		let dib = self.to_dib(with_alpha);
+		let mut bfh = BITMAPFILEHEADER::default();
+		bfh.bfOffBits = (std::mem::size_of::<BITMAPFILEHEADER>()
+			+ std::mem::size_of::<BITMAPINFOHEADER>()) as _;
+		bfh.bfSize = (std::mem::size_of::<BITMAPFILEHEADER>() + dib.len()) as _;
+
+		let mut buf = bfh.serialize().to_vec();
+		buf.extend_from_slice(&dib);
+		buf
+	}
+
+	/// Returns the width in pixels.
+	#[must_use]
+	pub const fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// Returns the height in pixels.
+	#[must_use]
+	pub const fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Returns the pixel data, with 4 bytes per pixel in the blue, green, red,
+	/// alpha order, with the rows stored top-down.
+	#[must_use]
+	pub fn as_bgra(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Returns the pixel data, with 4 bytes per pixel in the blue, green, red,
+	/// alpha order, with the rows stored top-down.
+	#[must_use]
+	pub fn as_bgra_mut(&mut self) -> &mut [u8] {
+		&mut self.data
+	}
+
+	/// Returns the pixel at the given position, as blue, green, red and alpha,
+	/// or `None` if out of bounds.
+	#[must_use]
+	pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+		self.pixel_index(x, y)
+			.map(|idx| [self.data[idx], self.data[idx + 1], self.data[idx + 2], self.data[idx + 3]])
+	}
+
+	/// Sets the pixel at the given position, as blue, green, red and alpha.
+	/// Does nothing if out of bounds.
+	pub fn set_pixel(&mut self, x: u32, y: u32, bgra: [u8; 4]) {
+		if let Some(idx) = self.pixel_index(x, y) {
+			self.data[idx..idx + 4].copy_from_slice(&bgra);
+		}
+	}
+
+	#[must_use]
+	fn pixel_index(&self, x: u32, y: u32) -> Option<usize> {
+		if x < self.width && y < self.height {
+			Some((y as usize * self.width as usize + x as usize) * 4)
+		} else {
+			None
+		}
+	}
+
+	/// Header of a top-down, 32-bit DIB.
+	#[must_use]
+	fn bitmap_info(cx: i32, cy: i32) -> BITMAPINFO {
+		let mut bi = BITMAPINFO::default();
+		bi.bmiHeader.biWidth = cx;
+		bi.bmiHeader.biHeight = -cy;
+		bi.bmiHeader.biPlanes = 1;
+		bi.bmiHeader.biBitCount = 32;
+		bi.bmiHeader.biCompression = co::BI::RGB;
+		bi
+	}
+}
+
+/// Extracts the color channel selected by the bit mask, scaled to 8 bits.
+#[must_use]
+fn extract_channel(val: u32, mask: u32) -> u8 {
+	if mask == 0 {
+		return 0;
+	}
+	let bits = mask.count_ones();
+	let chan = (val & mask) >> mask.trailing_zeros();
+	if bits >= 8 {
+		(chan >> (bits - 8)) as _
+	} else {
+		(chan * 255 / ((1 << bits) - 1)) as _
+	}
+}
@@ -4,12 +4,21 @@ use crate::gui::{*, privs::*};
 use crate::msg::*;
 use crate::prelude::*;
 
+/// Where the dialog is loaded from.
+pub(in crate::gui) enum DlgSrc {
+	/// A dialog resource ID.
+	Id(u16),
+	/// An in-memory template, serialized with
+	/// [`DlgTemplate::serialize_aligned`](crate::DlgTemplate::serialize_aligned).
+	Template(Vec<u32>),
+}
+
 /// Base to all dialog windows.
 ///
 /// Owns the window procedure for all dialog windows.
 pub(in crate::gui) struct DlgBase {
 	base: Base,
-	dialog_id: u16,
+	dialog_src: DlgSrc,
 }
 
 impl Drop for DlgBase {
@@ -24,12 +33,12 @@ impl DlgBase {
 	#[must_use]
 	pub(in crate::gui) fn new(
 		parent: Option<&impl AsRef<Base>>,
-		dialog_id: u16,
+		dialog_src: DlgSrc,
 	) -> Self
 	{
 		Self {
 			base: Base::new(true, parent),
-			dialog_id,
+			dialog_src,
 		}
 	}
 
@@ -45,15 +54,19 @@ impl DlgBase {
 
 		// Our hwnd member is set during WM_INITDIALOG processing; already set
 		// when CreateDialogParam returns.
+		let hinst = self.base.parent_hinstance()?;
+		let hparent = self.base.parent().map(|parent| parent.hwnd());
+		// Pass pointer to Self.
+		// At this moment, the parent struct is already created and pinned.
+		let init_param = Some(self as *const _ as _);
+
 		unsafe {
-			self.base.parent_hinstance()?.CreateDialogParam(
-				IdStr::Id(self.dialog_id),
-				self.base.parent().map(|parent| parent.hwnd()),
-				Self::dialog_proc,
-				// Pass pointer to Self.
-				// At this moment, the parent struct is already created and pinned.
-				Some(self as *const _ as _),
-			)?;
+			match &self.dialog_src {
+				DlgSrc::Id(id) => hinst.CreateDialogParam(
+					IdStr::Id(*id), hparent, Self::dialog_proc, init_param)?,
+				DlgSrc::Template(buf) => hinst.CreateDialogIndirectParam(
+					&*(buf.as_ptr() as *const DLGTEMPLATE), hparent, Self::dialog_proc, init_param)?,
+			};
 		}
 
 		Ok(())
@@ -66,15 +79,19 @@ impl DlgBase {
 
 		// Our hwnd member is set during WM_INITDIALOG processing; already set
 		// when DialogBoxParam returns.
+		let hinst = self.base.parent_hinstance()?;
+		let hparent = self.base.parent().map(|parent| parent.hwnd());
+		// Pass pointer to Self.
+		// At this moment, the parent struct is already created and pinned.
+		let init_param = Some(self as *const _ as _);
+
 		let ret = unsafe {
-			self.base.parent_hinstance()?.DialogBoxParam(
-				IdStr::Id(self.dialog_id),
-				self.base.parent().map(|parent| parent.hwnd()),
-				Self::dialog_proc,
-				// Pass pointer to Self.
-				// At this moment, the parent struct is already created and pinned.
-				Some(self as *const _ as _),
-			)?
+			match &self.dialog_src {
+				DlgSrc::Id(id) => hinst.DialogBoxParam(
+					IdStr::Id(*id), hparent, Self::dialog_proc, init_param)?,
+				DlgSrc::Template(buf) => hinst.DialogBoxIndirectParam(
+					&*(buf.as_ptr() as *const DLGTEMPLATE), hparent, Self::dialog_proc, init_param)?,
+			}
 		};
 
 		Ok(ret as _)
@@ -21,7 +21,7 @@ mod window_modeless;
 
 pub(in crate::gui) mod privs {
 	pub(in crate::gui) use super::base::Base;
-	pub(in crate::gui) use super::dlg_base::DlgBase;
+	pub(in crate::gui) use super::dlg_base::{DlgBase, DlgSrc};
 	pub(in crate::gui) use super::dlg_control::DlgControl;
 	pub(in crate::gui) use super::dlg_main::DlgMain;
 	pub(in crate::gui) use super::dlg_modal::DlgModal;
@@ -69,7 +69,59 @@ impl WindowMain {
 	{
 		Self(
 			RawDlg::Dlg(
-				DlgMain::new(dialog_id, icon_id, accel_table_id),
+				DlgMain::new(DlgSrc::Id(dialog_id), icon_id, accel_table_id),
+			),
+		)
+	}
+
+	/// Instantiates a new `WindowMain` object, to be created from an in-memory
+	/// dialog template with
+	/// [`HINSTANCE::CreateDialogIndirectParam`](crate::prelude::user_Hinstance::CreateDialogIndirectParam).
+	///
+	/// The child controls are loaded with their `new_dlg` constructors, using
+	/// the IDs of the template items.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co, gui};
+	///
+	/// let template = w::DlgTemplate {
+	///     title: "Generated form".to_owned(),
+	///     window_style: co::WS::POPUP | co::WS::CAPTION | co::WS::SYSMENU
+	///         | co::WS::MINIMIZEBOX,
+	///     items: vec![
+	///         w::DlgItem {
+	///             class: w::DlgItemClass::Button,
+	///             title: w::IdStr::from_str("&Click me"),
+	///             window_style: co::WS::CHILD | co::WS::VISIBLE | co::WS::TABSTOP,
+	///             x: 10, y: 10, cx: 60, cy: 14,
+	///             id: 1001,
+	///             ..Default::default()
+	///         },
+	///     ],
+	///     ..Default::default()
+	/// };
+	///
+	/// let wnd = gui::WindowMain::new_dlg_template(&template, None, None);
+	/// let btn = gui::Button::new_dlg(&wnd, 1001, (gui::Horz::None, gui::Vert::None));
+	/// wnd.run_main(None)?;
+	/// # w::AnyResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn new_dlg_template(
+		template: &DlgTemplate,
+		icon_id: Option<u16>,
+		accel_table_id: Option<u16>,
+	) -> Self
+	{
+		Self(
+			RawDlg::Dlg(
+				DlgMain::new(
+					DlgSrc::Template(template.serialize_aligned()),
+					icon_id,
+					accel_table_id,
+				),
 			),
 		)
 	}
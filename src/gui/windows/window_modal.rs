@@ -61,7 +61,22 @@ impl WindowModal {
 	pub fn new_dlg(parent: &impl GuiParent, dialog_id: u16) -> Self {
 		Self(
 			RawDlg::Dlg(
-				DlgModal::new(parent, dialog_id),
+				DlgModal::new(parent, DlgSrc::Id(dialog_id)),
+			),
+		)
+	}
+
+	/// Instantiates a new `WindowModal` object, to be created from an
+	/// in-memory dialog template with
+	/// [`HINSTANCE::DialogBoxIndirectParam`](crate::prelude::user_Hinstance::DialogBoxIndirectParam).
+	///
+	/// The child controls are loaded with their `new_dlg` constructors, using
+	/// the IDs of the template items.
+	#[must_use]
+	pub fn new_dlg_template(parent: &impl GuiParent, template: &DlgTemplate) -> Self {
+		Self(
+			RawDlg::Dlg(
+				DlgModal::new(parent, DlgSrc::Template(template.serialize_aligned())),
 			),
 		)
 	}
@@ -74,8 +89,9 @@ impl WindowModal {
 	/// is always zero.
 	///
 	/// For a modal created with
-	/// [`WindowModal::new_dlg`](crate::gui::WindowModal::new_dlg), the returned
-	/// `i32` is the value passed to
+	/// [`WindowModal::new_dlg`](crate::gui::WindowModal::new_dlg) or
+	/// [`WindowModal::new_dlg_template`](crate::gui::WindowModal::new_dlg_template),
+	/// the returned `i32` is the value passed to
 	/// [`HWND::EndDialog`](crate::prelude::user_Hwnd::EndDialog). Note that, if
 	/// the user clicks the "X" to close the modal, the default behavior is to
 	/// call `EndDialog(0)`. To override this behavior, handle the
@@ -350,12 +350,16 @@ const_bitflag! { FILE_CACHE: u32;
 }
 
 const_bitflag! { FILE_FLAG: u32;
-	/// [`HFILE::CreateFile`](crate::prelude::kernel_Hfile::CreateFile) `flags`
-	/// (`u32`).
+	/// [`HFILE::CreateFile`](crate::prelude::kernel_Hfile::CreateFile) and
+	/// [`HPIPE::CreateNamedPipe`](crate::prelude::kernel_Hpipe::CreateNamedPipe)
+	/// `flags` (`u32`).
 	=>
 	=>
 	BACKUP_SEMANTICS 0x0200_0000
 	DELETE_ON_CLOSE 0x0400_0000
+	/// Used only with
+	/// [`HPIPE::CreateNamedPipe`](crate::prelude::kernel_Hpipe::CreateNamedPipe).
+	FIRST_PIPE_INSTANCE 0x0008_0000
 	NO_BUFFERING 0x2000_0000
 	OPEN_NO_RECALL 0x0010_0000
 	OPEN_REPARSE_POINT 0x0020_0000
@@ -548,6 +552,15 @@ const_bitflag! { GR: u32;
 	USEROBJECTS_PEAK 4
 }
 
+const_bitflag! { HANDLE_FLAG: u32;
+	/// [`HPIPE::SetHandleInformation`](crate::prelude::kernel_Hpipe::SetHandleInformation)
+	/// `mask` and `flags` (`u32`).
+	=>
+	=>
+	INHERIT 0x0000_0001
+	PROTECT_FROM_CLOSE 0x0000_0002
+}
+
 const_bitflag! { HEAP_ALLOC: u32;
 	/// [`HHEAP::HeapAlloc`](crate::prelude::kernel_Hheap::HeapAlloc) `flags`
 	/// (`u32`).
@@ -900,6 +913,39 @@ const_ordinary! { PBT: u32;
 	POWERSETTINGCHANGE 0x8013
 }
 
+const_bitflag! { PIPE: u32;
+	/// [`HPIPE::CreateNamedPipe`](crate::prelude::kernel_Hpipe::CreateNamedPipe)
+	/// `pipe_mode` and
+	/// [`HPIPE::GetNamedPipeInfo`](crate::prelude::kernel_Hpipe::GetNamedPipeInfo)
+	/// flags (`u32`).
+	=>
+	=>
+	WAIT 0x0000_0000
+	NOWAIT 0x0000_0001
+	READMODE_BYTE 0x0000_0000
+	READMODE_MESSAGE 0x0000_0002
+	TYPE_BYTE 0x0000_0000
+	TYPE_MESSAGE 0x0000_0004
+	ACCEPT_REMOTE_CLIENTS 0x0000_0000
+	REJECT_REMOTE_CLIENTS 0x0000_0008
+	/// Returned only by
+	/// [`HPIPE::GetNamedPipeInfo`](crate::prelude::kernel_Hpipe::GetNamedPipeInfo).
+	CLIENT_END 0x0000_0000
+	/// Returned only by
+	/// [`HPIPE::GetNamedPipeInfo`](crate::prelude::kernel_Hpipe::GetNamedPipeInfo).
+	SERVER_END 0x0000_0001
+}
+
+const_bitflag! { PIPE_ACCESS: u32;
+	/// [`HPIPE::CreateNamedPipe`](crate::prelude::kernel_Hpipe::CreateNamedPipe)
+	/// `open_mode` (`u32`).
+	=>
+	=>
+	INBOUND 0x0000_0001
+	OUTBOUND 0x0000_0002
+	DUPLEX 0x0000_0003
+}
+
 const_bitflag! { PRIORITY_CLASS: u32;
 	/// [`GetPriorityClass`](crate::prelude::kernel_Hprocess::GetPriorityClass)
 	/// and
@@ -1338,6 +1384,18 @@ const_ordinary! { RT: u16;
 	VXD 20
 }
 
+const_ordinary! { SC_ACTION_TYPE: u32;
+	/// [`SC_ACTION`](crate::SC_ACTION) `Type` (`u32`).
+	///
+	/// Originally has `SC_ACTION` prefix.
+	=>
+	=>
+	NONE 0
+	RESTART 1
+	REBOOT 2
+	RUN_COMMAND 3
+}
+
 const_bitflag! { SC_MANAGER: u32;
 	/// Service Control Manager access rights
 	/// [`flags`](https://learn.microsoft.com/en-us/windows/win32/services/service-security-and-access-rights)
@@ -1573,6 +1631,18 @@ const_bitflag! { SERVICE_CONTROL: u32;
 	USERMODEREBOOT 0x0000_0040
 }
 
+const_ordinary! { SERVICE_ENUM_STATE: u32;
+	/// [`HSC::EnumServicesStatusEx`](crate::prelude::kernel_Hsc::EnumServicesStatusEx)
+	/// `service_state` (`u32`).
+	///
+	/// Originally `SERVICE_ACTIVE`, `SERVICE_INACTIVE` and `SERVICE_STATE_ALL`.
+	=>
+	=>
+	ACTIVE 0x0000_0001
+	INACTIVE 0x0000_0002
+	ALL 0x0000_0003
+}
+
 const_ordinary! { SERVICE_ERROR: u32;
 	/// [`HSC::CreateService`](crate::prelude::kernel_Hsc::CreateService)
 	/// `error_control` (`u32`).
@@ -1628,6 +1698,13 @@ const_ordinary! { SERVICE_TYPE: u32;
 
 	WIN32_OWN_PROCESS_INTERACTIVE Self::WIN32_OWN_PROCESS.0 | 0x0000_0100
 	WIN32_SHARE_PROCESS_INTERACTIVE Self::WIN32_SHARE_PROCESS.0 | 0x0000_0100
+
+	/// Used only with
+	/// [`HSC::EnumServicesStatusEx`](crate::prelude::kernel_Hsc::EnumServicesStatusEx).
+	DRIVER Self::KERNEL_DRIVER.0 | Self::FILE_SYSTEM_DRIVER.0 | Self::RECOGNIZER_DRIVER.0
+	/// Used only with
+	/// [`HSC::EnumServicesStatusEx`](crate::prelude::kernel_Hsc::EnumServicesStatusEx).
+	WIN32 Self::WIN32_OWN_PROCESS.0 | Self::WIN32_SHARE_PROCESS.0
 }
 
 const_bitflag! { SHTDN_REASON: u32;
@@ -41,6 +41,16 @@ impl std::error::Error for ERROR {
 	}
 }
 
+impl From<ERROR> for std::io::Error {
+	/// Converts the system error code into a
+	/// [`std::io::Error`](std::io::Error), by calling
+	/// [`from_raw_os_error`](std::io::Error::from_raw_os_error), so the
+	/// [`ErrorKind`](std::io::ErrorKind) is properly mapped.
+	fn from(err: ERROR) -> Self {
+		Self::from_raw_os_error(err.0 as _)
+	}
+}
+
 impl std::fmt::Display for ERROR {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "[{}] {}", self.0, self.FormatMessage().trim_end())
@@ -1,5 +1,19 @@
 #![allow(non_camel_case_types)]
 
+const_guid! { NAMESPACE;
+	/// Predefined namespace
+	/// [identifiers](https://www.rfc-editor.org/rfc/rfc4122#appendix-C) for
+	/// name-based `GUID` generation with
+	/// [`GUID::new_v5`](crate::GUID::new_v5) (`GUID`).
+	///
+	/// Originally has `NameSpace` prefix.
+	=>
+	DNS "6ba7b810-9dad-11d1-80b4-00c04fd430c8"
+	URL "6ba7b811-9dad-11d1-80b4-00c04fd430c8"
+	OID "6ba7b812-9dad-11d1-80b4-00c04fd430c8"
+	X500 "6ba7b814-9dad-11d1-80b4-00c04fd430c8"
+}
+
 const_guid! { POWER_SAVINGS;
 	/// Power scheme
 	/// [identifiers](https://learn.microsoft.com/en-us/windows/win32/power/power-setting-guids)
@@ -23,6 +23,25 @@ pub enum DisabPriv<'a> {
 	Privs(&'a TOKEN_PRIVILEGES)
 }
 
+/// The variant of a [`GUID`](crate::GUID), which determines the layout of its
+/// bits, as defined in
+/// [RFC 4122](https://www.rfc-editor.org/rfc/rfc4122#section-4.1.1).
+///
+/// Returned by:
+///
+/// * [`GUID::variant`](crate::GUID::variant).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GuidVariant {
+	/// Reserved, NCS backward compatibility.
+	Ncs,
+	/// The variant specified in RFC 4122.
+	Rfc4122,
+	/// Reserved, Microsoft Corporation backward compatibility.
+	Microsoft,
+	/// Reserved for future definition.
+	Future,
+}
+
 /// A resource identifier.
 ///
 /// Variable parameter for:
@@ -38,7 +57,7 @@ pub enum DisabPriv<'a> {
 /// * [`BmpIdbRes`](crate::BmpIdbRes);
 /// * [`IconRes`](crate::IconRes);
 /// * [`ResStrs`](crate::ResStrs).
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IdStr {
 	/// A resource ID.
 	Id(u16),
@@ -127,12 +146,68 @@ pub enum PowerSettingLid {
 ///
 /// This is a high-level abstraction over the [`co::REG`](crate::co::REG)
 /// constants, plus the value they carry.
-#[derive(Clone, Debug)]
+///
+/// Raw registry data can be decoded with
+/// [`TryFrom<(&[u8], co::REG)>`](std::convert::TryFrom), and encoded back with
+/// [`RegistryValue::to_bytes`](crate::RegistryValue::to_bytes). Decoding any
+/// data block and encoding it again yields the very same bytes: data which is
+/// not in the canonical form of its type is kept untouched in
+/// [`RegistryValue::Other`](crate::RegistryValue::Other).
+///
+/// Values retrieved by [`HKEY`](crate::HKEY) methods, like
+/// [`HKEY::RegQueryValueEx`](crate::prelude::kernel_Hkey::RegQueryValueEx),
+/// are decoded leniently instead: strings without their terminating null, or
+/// with extra trailing nulls, are still returned as
+/// [`RegistryValue::Sz`](crate::RegistryValue::Sz),
+/// [`RegistryValue::ExpandSz`](crate::RegistryValue::ExpandSz) and
+/// [`RegistryValue::MultiSz`](crate::RegistryValue::MultiSz).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let raw = [0x00, 0x00, 0x01, 0x00];
+/// let val = w::RegistryValue::try_from((&raw[..], co::REG::DWORD_BIG_ENDIAN))?;
+/// assert_eq!(val, w::RegistryValue::DwordBigEndian(256));
+/// assert_eq!(val.to_bytes(), raw);
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Malformed data blocks also survive the round-trip:
+///
+/// ```
+/// use winsafe::{self as w, co};
+///
+/// let blocks: [(&[u8], co::REG); 5] = [
+///     (&[0x61, 0x00, 0x00, 0x00, 0x62, 0x00, 0x00, 0x00], co::REG::SZ), // embedded null
+///     (&[0x61, 0x00, 0x62, 0x00], co::REG::SZ), // missing terminating null
+///     (&[0x61, 0x00, 0x62], co::REG::EXPAND_SZ), // odd length
+///     (&[0x00, 0xd8, 0x00, 0x00], co::REG::SZ), // unpaired surrogate
+///     (&[0x01, 0x02, 0x03], co::REG::DWORD), // wrong length
+/// ];
+///
+/// for (data, reg_type) in blocks {
+///     let val = w::RegistryValue::try_from((data, reg_type))?;
+///     assert_eq!(val, w::RegistryValue::Other(reg_type, data.to_vec()));
+///     assert_eq!(val.to_bytes(), data);
+/// }
+///
+/// let data = [0x61, 0x00, 0x00, 0x00, 0x00, 0x00, 0x62, 0x00, 0x00, 0x00, 0x00, 0x00];
+/// let val = w::RegistryValue::try_from((&data[..], co::REG::MULTI_SZ))?; // empty entry
+/// assert_eq!(val, w::RegistryValue::MultiSz(vec!["a".to_owned(), "".to_owned(), "b".to_owned()]));
+/// assert_eq!(val.to_bytes(), data);
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RegistryValue {
 	/// Binary value, defined as [`REG::BINARY`](crate::co::REG::BINARY).
 	Binary(Vec<u8>),
 	/// An `u32` integer value, defined as [`REG::DWORD`](crate::co::REG::DWORD).
 	Dword(u32),
+	/// An `u32` integer value stored in big-endian format, defined as
+	/// [`REG::DWORD_BIG_ENDIAN`](crate::co::REG::DWORD_BIG_ENDIAN).
+	DwordBigEndian(u32),
 	/// An `u64` integer value, defined as [`REG::QWORD`](crate::co::REG::QWORD).
 	Qword(u64),
 	/// String value, defined as [`REG::SZ`](crate::co::REG::SZ).
@@ -142,28 +217,42 @@ pub enum RegistryValue {
 	/// references, use
 	/// [`ExpandEnvironmentStrings`](crate::ExpandEnvironmentStrings).
 	ExpandSz(String),
+	/// Path of a registry symbolic link, defined as
+	/// [`REG::LINK`](crate::co::REG::LINK). Unlike the other strings, it's not
+	/// null-terminated.
+	Link(String),
 	/// Multiple strings, defined as [`REG::MULTI_SZ`](crate::co::REG::MULTI_SZ).
 	MultiSz(Vec<String>),
-	/// No value, defined as [`REG::NONE`](crate::co::REG::NONE). Also used for
-	/// non-implemented value types.
+	/// Device-driver resource list, defined as
+	/// [`REG::RESOURCE_LIST`](crate::co::REG::RESOURCE_LIST).
+	ResourceList(Vec<u8>),
+	/// Hardware resource descriptor, defined as
+	/// [`REG::FULL_RESOURCE_DESCRIPTOR`](crate::co::REG::FULL_RESOURCE_DESCRIPTOR).
+	FullResourceDescriptor(Vec<u8>),
+	/// Hardware resource requirements list, defined as
+	/// [`REG::RESOURCE_REQUIREMENTS_LIST`](crate::co::REG::RESOURCE_REQUIREMENTS_LIST).
+	ResourceRequirementsList(Vec<u8>),
+	/// No value, defined as [`REG::NONE`](crate::co::REG::NONE), without any
+	/// data.
 	None,
+	/// Data which doesn't fit any of the other variants: a
+	/// [`REG::NONE`](crate::co::REG::NONE) value which carries data, a value
+	/// whose type is not defined in [`co::REG`](crate::co::REG), or data which
+	/// is not in the canonical form of its type – like a string without its
+	/// terminating null. The data is kept untouched.
+	Other(co::REG, Vec<u8>),
 }
 
 impl std::fmt::Display for RegistryValue {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Self::Binary(b) => write!(
-				f,
-				"[REG_BINARY] {}",
-				b.iter()
-					.map(|n| format!("{:02}", *n))
-					.collect::<Vec<_>>()
-					.join(" "),
-			),
+			Self::Binary(b) => write!(f, "[REG_BINARY] {}", Self::format_bytes(b)),
 			Self::Dword(n) => write!(f, "[REG_DWORD] {}", *n),
+			Self::DwordBigEndian(n) => write!(f, "[REG_DWORD_BIG_ENDIAN] {}", *n),
 			Self::Qword(n) => write!(f, "[REG_QWORD] {}", *n),
 			Self::Sz(s) => write!(f, "[REG_SZ] \"{}\"", s),
 			Self::ExpandSz(s) => write!(f, "[REG_EXPAND_SZ] \"{}\"", s),
+			Self::Link(s) => write!(f, "[REG_LINK] \"{}\"", s),
 			Self::MultiSz(v) => write!(
 				f,
 				"[REG_MULTI_SZ] {}",
@@ -172,11 +261,37 @@ impl std::fmt::Display for RegistryValue {
 					.collect::<Vec<_>>()
 					.join(", "),
 			),
+			Self::ResourceList(b) => write!(f, "[REG_RESOURCE_LIST] {}", Self::format_bytes(b)),
+			Self::FullResourceDescriptor(b) => write!(f, "[REG_FULL_RESOURCE_DESCRIPTOR] {}", Self::format_bytes(b)),
+			Self::ResourceRequirementsList(b) => write!(f, "[REG_RESOURCE_REQUIREMENTS_LIST] {}", Self::format_bytes(b)),
 			Self::None => write!(f, "[REG_NONE]"),
+			Self::Other(t, b) => write!(f, "[{}] {}", t, Self::format_bytes(b)),
 		}
 	}
 }
 
+impl TryFrom<(&[u8], co::REG)> for RegistryValue {
+	type Error = co::ERROR;
+
+	/// Decodes a raw registry data block, according to its type.
+	///
+	/// Data which is not in the canonical form of its type – like a
+	/// [`REG::DWORD`](crate::co::REG::DWORD) which is not 4 bytes long, or a
+	/// string with an odd number of bytes, without its terminating null, with
+	/// embedded nulls or with invalid UTF-16 – is returned as
+	/// [`RegistryValue::Other`](crate::RegistryValue::Other), so
+	/// [`RegistryValue::to_bytes`](crate::RegistryValue::to_bytes) will always
+	/// give back the very same bytes. Therefore this conversion never fails.
+	fn try_from(value: (&[u8], co::REG)) -> Result<Self, Self::Error> {
+		let (data, reg_type) = value;
+		Ok(
+			Self::decode(data, reg_type)
+				.filter(|val| val.to_bytes() == data) // anything not canonical is kept raw
+				.unwrap_or_else(|| Self::Other(reg_type, data.to_vec())),
+		)
+	}
+}
+
 impl RegistryValue {
 	/// Parses a binary data block as a `RegistryValue`.
 	///
@@ -185,70 +300,31 @@ impl RegistryValue {
 	///
 	/// # Safety
 	///
-	/// Assumes the binary data block has the correct content, according to the
-	/// informed [`co::REG`](crate::co::REG).
+	/// This method is now safe, and it's kept only for compatibility.
+	#[deprecated = "use TryFrom<(&[u8], co::REG)> instead"]
 	#[must_use]
 	pub unsafe fn from_raw(buf: Vec<u8>, reg_type: co::REG) -> RegistryValue {
-		match reg_type {
-			co::REG::NONE => RegistryValue::None,
-			co::REG::DWORD => RegistryValue::Dword(
-				u32::from_ne_bytes(unsafe {
-					*std::mem::transmute::<_, *const [u8; 4]>(buf.as_ptr())
-				})
-			),
-			co::REG::QWORD => RegistryValue::Qword(
-				u64::from_ne_bytes(unsafe {
-					*std::mem::transmute::<_, *const [u8; 8]>(buf.as_ptr())
-				})
-			),
-			co::REG::SZ => {
-				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
-				RegistryValue::Sz(WString::from_wchars_slice(&vec16).to_string())
-			},
-			co::REG::EXPAND_SZ => {
-				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
-				RegistryValue::Sz(WString::from_wchars_slice(&vec16).to_string())
-			},
-			co::REG::MULTI_SZ => {
-				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
-				RegistryValue::MultiSz(parse_multi_z_str(vec16.as_ptr()))
-			},
-			co::REG::BINARY => RegistryValue::Binary(buf),
-			_ => RegistryValue::None, // other types not implemented yet
-		}
+		Self::try_from((&buf[..], reg_type))
+			.unwrap_or(Self::Other(reg_type, buf))
 	}
 
 	/// Returns a pointer to the raw data, along with the raw data length.
+	///
+	/// The raw data is copied into `str_buf`, so the pointer is valid as long
+	/// as `str_buf` is alive and not modified.
+	#[deprecated = "use RegistryValue::to_bytes instead"]
 	#[must_use]
 	pub fn as_ptr_with_len(&self,
 		str_buf: &mut WString,
 	) -> (*const std::ffi::c_void, u32)
 	{
-		match self {
-			Self::Binary(b) => (b.as_ptr() as _, b.len() as _),
-			Self::Dword(n) => (n as *const _ as _, std::mem::size_of::<u32>() as _),
-			Self::Qword(n) => (n as *const _ as _, std::mem::size_of::<u64>() as _),
-			Self::Sz(s) => {
-				*str_buf = WString::from_str(s);
-				Self::as_ptr_with_len_str(&str_buf)
-			},
-			Self::ExpandSz(s) => {
-				*str_buf = WString::from_str(s);
-				Self::as_ptr_with_len_str(&str_buf)
-			},
-			Self::MultiSz(v) => {
-				*str_buf = WString::from_str_vec(v);
-				Self::as_ptr_with_len_str(&str_buf)
-			},
-			Self::None => (std::ptr::null(), 0),
-		}
-	}
-
-	fn as_ptr_with_len_str(str_buf: &WString) -> (*const std::ffi::c_void, u32) {
-		(
-			str_buf.as_ptr() as _,
-			(str_buf.buf_len() * std::mem::size_of::<u16>()) as _, // will include terminating null
-		)
+		let data = self.to_bytes();
+		*str_buf = WString::new_alloc_buf(data.len().div_ceil(2));
+		str_buf.as_mut_slice()
+			.iter_mut()
+			.zip(data.chunks(2))
+			.for_each(|(ch, b)| *ch = u16::from_le_bytes([b[0], *b.get(1).unwrap_or(&0)]));
+		(vec_ptr(str_buf.as_slice()) as _, data.len() as _)
 	}
 
 	/// Returns the correspondent [`co::REG`](crate::co::REG) constant.
@@ -257,13 +333,161 @@ impl RegistryValue {
 		match self {
 			Self::Binary(_) => co::REG::BINARY,
 			Self::Dword(_) => co::REG::DWORD,
+			Self::DwordBigEndian(_) => co::REG::DWORD_BIG_ENDIAN,
 			Self::Qword(_) => co::REG::QWORD,
 			Self::Sz(_) => co::REG::SZ,
 			Self::ExpandSz(_) => co::REG::EXPAND_SZ,
+			Self::Link(_) => co::REG::LINK,
 			Self::MultiSz(_) => co::REG::MULTI_SZ,
+			Self::ResourceList(_) => co::REG::RESOURCE_LIST,
+			Self::FullResourceDescriptor(_) => co::REG::FULL_RESOURCE_DESCRIPTOR,
+			Self::ResourceRequirementsList(_) => co::REG::RESOURCE_REQUIREMENTS_LIST,
 			Self::None => co::REG::NONE,
+			Self::Other(t, _) => *t,
 		}
 	}
+
+	/// Encodes the value into the raw data block stored in the registry, which
+	/// can be decoded back with
+	/// [`TryFrom<(&[u8], co::REG)>`](std::convert::TryFrom).
+	///
+	/// Strings are encoded as UTF-16 with a terminating null, except for
+	/// [`RegistryValue::Link`](crate::RegistryValue::Link). Multiple strings
+	/// have an additional terminating null.
+	#[must_use]
+	pub fn to_bytes(&self) -> Vec<u8> {
+		match self {
+			Self::Binary(b)
+				| Self::ResourceList(b)
+				| Self::FullResourceDescriptor(b)
+				| Self::ResourceRequirementsList(b)
+				| Self::Other(_, b) => b.clone(),
+			Self::Dword(n) => n.to_le_bytes().to_vec(),
+			Self::DwordBigEndian(n) => n.to_be_bytes().to_vec(),
+			Self::Qword(n) => n.to_le_bytes().to_vec(),
+			Self::Sz(s) | Self::ExpandSz(s) => Self::encode_utf16(
+				s.encode_utf16().chain(std::iter::once(0x0000))),
+			Self::Link(s) => Self::encode_utf16(s.encode_utf16()),
+			Self::MultiSz(v) => Self::encode_utf16(
+				v.iter()
+					.flat_map(|s| s.encode_utf16().chain(std::iter::once(0x0000)))
+					.chain(std::iter::once(0x0000)),
+			),
+			Self::None => Vec::default(),
+		}
+	}
+
+	/// Decodes a data block retrieved from the registry. Unlike
+	/// [`TryFrom`](std::convert::TryFrom), strings are decoded leniently, the
+	/// way Windows reads them: a missing terminating null or a dangling odd
+	/// byte is ignored, a string ends at its first null, and any trailing
+	/// nulls of multiple strings are dropped. Only invalid UTF-16 is kept in
+	/// [`RegistryValue::Other`](crate::RegistryValue::Other).
+	#[must_use]
+	pub(crate) fn from_retrieved(data: &[u8], reg_type: co::REG) -> Self {
+		if let Some(val) = Self::decode(data, reg_type)
+			.filter(|val| val.to_bytes() == data) // canonical data, no leniency needed
+		{
+			return val;
+		}
+
+		let units = data.chunks_exact(2) // a dangling odd byte is dropped
+			.map(|b| u16::from_le_bytes([b[0], b[1]]))
+			.collect::<Vec<_>>();
+		let sz = || String::from_utf16(
+			units.split(|ch| *ch == 0x0000).next().unwrap_or_default()).ok();
+
+		match reg_type {
+			co::REG::SZ => sz().map(Self::Sz),
+			co::REG::EXPAND_SZ => sz().map(Self::ExpandSz),
+			co::REG::MULTI_SZ => {
+				let len = units.iter()
+					.rposition(|ch| *ch != 0x0000)
+					.map_or(0, |idx| idx + 1);
+				if len == 0 {
+					Some(Self::MultiSz(Vec::default()))
+				} else {
+					units[..len].split(|ch| *ch == 0x0000)
+						.map(|s| String::from_utf16(s).ok())
+						.collect::<Option<Vec<_>>>()
+						.map(Self::MultiSz)
+				}
+			},
+			_ => None,
+		}.unwrap_or_else(|| Self::Other(reg_type, data.to_vec()))
+	}
+
+	/// Decodes the data block according to its type, returning `None` if it
+	/// cannot be represented by the correspondent variant.
+	#[must_use]
+	fn decode(data: &[u8], reg_type: co::REG) -> Option<Self> {
+		Some(match reg_type {
+			co::REG::NONE if data.is_empty() => Self::None,
+			co::REG::SZ => Self::Sz(Self::decode_sz(data)?),
+			co::REG::EXPAND_SZ => Self::ExpandSz(Self::decode_sz(data)?),
+			co::REG::BINARY => Self::Binary(data.to_vec()),
+			co::REG::DWORD => Self::Dword(u32::from_le_bytes(data.try_into().ok()?)),
+			co::REG::DWORD_BIG_ENDIAN => Self::DwordBigEndian(u32::from_be_bytes(data.try_into().ok()?)),
+			co::REG::LINK => Self::Link(String::from_utf16(&Self::utf16_units(data)?).ok()?),
+			co::REG::MULTI_SZ => Self::MultiSz(Self::decode_multi_sz(data)?),
+			co::REG::RESOURCE_LIST => Self::ResourceList(data.to_vec()),
+			co::REG::FULL_RESOURCE_DESCRIPTOR => Self::FullResourceDescriptor(data.to_vec()),
+			co::REG::RESOURCE_REQUIREMENTS_LIST => Self::ResourceRequirementsList(data.to_vec()),
+			co::REG::QWORD => Self::Qword(u64::from_le_bytes(data.try_into().ok()?)),
+			_ => return None,
+		})
+	}
+
+	/// Decodes a string with a single terminating null, and no embedded nulls.
+	#[must_use]
+	fn decode_sz(data: &[u8]) -> Option<String> {
+		let units = Self::utf16_units(data)?;
+		match units.split_last()? {
+			(0x0000, s) if !s.contains(&0x0000) => String::from_utf16(s).ok(),
+			_ => None,
+		}
+	}
+
+	/// Decodes strings which are each null-terminated, followed by an
+	/// additional terminating null. Empty strings are kept.
+	#[must_use]
+	fn decode_multi_sz(data: &[u8]) -> Option<Vec<String>> {
+		let units = Self::utf16_units(data)?;
+		match units.as_slice() {
+			[0x0000] => Some(Vec::default()), // no strings at all
+			[s @ .., 0x0000, 0x0000] => s.split(|ch| *ch == 0x0000)
+				.map(|s| String::from_utf16(s).ok())
+				.collect(),
+			_ => None,
+		}
+	}
+
+	/// Reads the bytes as little-endian UTF-16 code units, regardless of the
+	/// buffer alignment.
+	#[must_use]
+	fn utf16_units(data: &[u8]) -> Option<Vec<u16>> {
+		if !data.len().is_multiple_of(2) {
+			return None;
+		}
+		Some(
+			data.chunks_exact(2)
+				.map(|b| u16::from_le_bytes([b[0], b[1]]))
+				.collect(),
+		)
+	}
+
+	#[must_use]
+	fn encode_utf16(units: impl Iterator<Item = u16>) -> Vec<u8> {
+		units.flat_map(|ch| ch.to_le_bytes()).collect()
+	}
+
+	#[must_use]
+	fn format_bytes(b: &[u8]) -> String {
+		b.iter()
+			.map(|n| format!("{:02}", *n))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
 }
 
 /// A predefined resource identifier.
@@ -276,7 +500,7 @@ impl RegistryValue {
 /// * [`HINSTANCE::FindResource`](crate::prelude::kernel_Hinstance::FindResource);
 /// * [`HINSTANCE::FindResourceEx`](crate::prelude::kernel_Hinstance::FindResourceEx);
 /// * [`HUPDATERSRC`](crate::prelude::kernel_Hupdatersrc::UpdateResource).
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RtStr {
 	/// A predefined resource ID.
 	Rt(co::RT),
@@ -325,6 +549,37 @@ impl RtStr {
 	}
 }
 
+/// Variant parameter for:
+///
+/// * [`HSERVICE::ChangeServiceConfig2`](crate::prelude::kernel_Hservice::ChangeServiceConfig2).
+pub enum ServiceConfigInfo<'a> {
+	/// Whether an auto-start service is started after the other auto-start
+	/// services, plus a short delay.
+	DelayedAutoStart(bool),
+	/// Description of the service. An empty string deletes it.
+	Description(&'a str),
+	/// Actions taken by the Service Control Manager when the service fails.
+	FailureActions {
+		/// Seconds without failures after which the failure count is reset
+		/// to zero. If `None`, the count is never reset.
+		reset_period: Option<u32>,
+		/// Message broadcast before rebooting. If `None`, it's left unchanged;
+		/// an empty string deletes it.
+		reboot_msg: Option<&'a str>,
+		/// Command line run by the
+		/// [`co::SC_ACTION_TYPE::RUN_COMMAND`](crate::co::SC_ACTION_TYPE::RUN_COMMAND)
+		/// action. If `None`, it's left unchanged; an empty string deletes it.
+		command: Option<&'a str>,
+		/// Action for the first failure, the second one, and so on; the last
+		/// action is repeated for the subsequent failures. An empty slice
+		/// deletes the actions.
+		actions: &'a [SC_ACTION],
+	},
+	/// Whether the failure actions are also taken when the service stops with
+	/// a non-zero exit code, and not only when it crashes.
+	FailureActionsOnNonCrashFailures(bool),
+}
+
 /// Notification content for
 /// [`HSERVICESTATUS::RegisterServiceCtrlHandlerEx`](crate::prelude::kernel_Hservicestatus::RegisterServiceCtrlHandlerEx)
 /// callback, describing [`co::SERVICE_CONTROL`](crate::co::SERVICE_CONTROL).
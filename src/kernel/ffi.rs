@@ -3,9 +3,12 @@ use crate::kernel::ffi_types::*;
 extern_sys! { "advapi32";
 	AdjustTokenPrivileges(HANDLE, BOOL, PCVOID, u32, PVOID, *mut u32) -> BOOL
 	AllocateAndInitializeSid(PCVOID, u8, u32, u32, u32, u32, u32, u32, u32, u32, *mut u8) -> BOOL
+	ChangeServiceConfig2W(HANDLE, u32, PVOID) -> BOOL
+	ChangeServiceConfigW(HANDLE, u32, u32, u32, PCSTR, PCSTR, *mut u32, PCSTR, PCSTR, PCSTR, PCSTR) -> BOOL
 	CheckTokenCapability(HANDLE, PCVOID, *mut BOOL) -> BOOL
 	CheckTokenMembership(HANDLE, PCVOID, *mut BOOL) -> BOOL
 	CloseServiceHandle(HANDLE) -> BOOL
+	ControlService(HANDLE, u32, PVOID) -> BOOL
 	ConvertSidToStringSidW(PCVOID, *mut PSTR) -> BOOL
 	ConvertStringSidToSidW(PCSTR, *mut *mut u8) -> BOOL
 	CopySid(u32, PVOID, PCVOID) -> BOOL
@@ -17,6 +20,7 @@ extern_sys! { "advapi32";
 	DuplicateToken(HANDLE, u32, *mut HANDLE) -> BOOL
 	EncryptFileW(PCSTR) -> BOOL
 	EncryptionDisable(PCSTR, BOOL) -> BOOL
+	EnumServicesStatusExW(HANDLE, u32, u32, u32, *mut u8, u32, *mut u32, *mut u32, *mut u32, PCSTR) -> BOOL
 	EqualDomainSid(PVOID, PVOID, *mut BOOL) -> BOOL
 	EqualPrefixSid(PVOID, PVOID) -> BOOL
 	EqualSid(PVOID, PVOID) -> BOOL
@@ -44,6 +48,8 @@ extern_sys! { "advapi32";
 	OpenSCManagerW(PCSTR, PCSTR, u32) -> HANDLE
 	OpenServiceW(HANDLE, PCSTR, u32) -> HANDLE
 	OpenThreadToken(HANDLE, u32, BOOL, *mut HANDLE) -> BOOL
+	QueryServiceConfigW(HANDLE, PVOID, u32, *mut u32) -> BOOL
+	QueryServiceStatusEx(HANDLE, u32, *mut u8, u32, *mut u32) -> BOOL
 	RegCloseKey(HANDLE) -> i32
 	RegConnectRegistryW(PCSTR, HANDLE, *mut HANDLE) -> i32
 	RegCopyTreeW(HANDLE, PCSTR, HANDLE) -> i32
@@ -82,23 +88,30 @@ extern_sys! { "advapi32";
 	RegUnLoadKeyW(HANDLE, PCSTR) -> i32
 	ReportEventW(HANDLE, u16, u16, u32, PCVOID, u16, u32, *const PCSTR, PCVOID) -> BOOL
 	SetServiceStatus(HANDLE, PCVOID) -> BOOL
+	StartServiceCtrlDispatcherW(PCVOID) -> BOOL
+	StartServiceW(HANDLE, u32, *const PCSTR) -> BOOL
+	SystemFunction036(PVOID, u32) -> u8
 }
 
 extern_sys! { "kernel32";
 	BeginUpdateResourceW(PCSTR, BOOL) -> HANDLE
 	CheckRemoteDebuggerPresent(HANDLE, *mut BOOL) -> BOOL
+	CancelIoEx(HANDLE, PVOID) -> BOOL
 	CloseHandle(HANDLE) -> BOOL
+	ConnectNamedPipe(HANDLE, PVOID) -> BOOL
 	CopyFileW(PCSTR, PCSTR, BOOL) -> BOOL
 	CreateDirectoryW(PCSTR, PVOID) -> BOOL
 	CreateEventExW(PCVOID, PCSTR, u32, u32) -> HANDLE
 	CreateEventW(PCVOID, BOOL, BOOL, PCSTR) -> HANDLE
 	CreateFileMappingFromApp(HANDLE, PVOID, u32, u64, PCSTR) -> HANDLE
 	CreateFileW(PCSTR, u32, u32, PVOID, u32, u32, HANDLE) -> HANDLE
+	CreateNamedPipeW(PCSTR, u32, u32, u32, u32, u32, u32, PVOID) -> HANDLE
 	CreatePipe(*mut HANDLE, *mut HANDLE, PVOID, u32) -> BOOL
 	CreateProcessW(PCSTR, PSTR, PVOID, PVOID, BOOL, u32, PVOID, PCSTR, PVOID, PVOID) -> BOOL
 	CreateThread(PVOID, usize, PVOID, PVOID, u32, *mut u32) -> HANDLE
 	CreateToolhelp32Snapshot(u32, u32) -> HANDLE
 	DeleteFileW(PCSTR) -> BOOL
+	DisconnectNamedPipe(HANDLE) -> BOOL
 	EndUpdateResourceW(HANDLE, BOOL) -> BOOL
 	EnumResourceLanguagesW(HANDLE, PCSTR, PCSTR, PFUNC, isize) -> BOOL
 	EnumResourceNamesW(HANDLE, PCSTR, PFUNC, isize) -> BOOL
@@ -113,6 +126,7 @@ extern_sys! { "kernel32";
 	FindResourceExW(HANDLE, PCSTR, PCSTR, u16) -> HANDLE
 	FindResourceW(HANDLE, PCSTR, PCSTR) -> HANDLE
 	FlushConsoleInputBuffer(HANDLE) -> BOOL
+	FlushFileBuffers(HANDLE) -> BOOL
 	FlushInstructionCache(HANDLE, PCVOID, usize) -> BOOL
 	FlushProcessWriteBuffers()
 	FlushViewOfFile(PVOID, usize) -> BOOL
@@ -148,7 +162,11 @@ extern_sys! { "kernel32";
 	GetLogicalDriveStringsW(u32, PSTR) -> u32
 	GetModuleFileNameW(HANDLE, PSTR, u32) -> u32
 	GetModuleHandleW(PCSTR) -> HANDLE
+	GetNamedPipeClientProcessId(HANDLE, *mut u32) -> BOOL
+	GetNamedPipeInfo(HANDLE, *mut u32, *mut u32, *mut u32, *mut u32) -> BOOL
+	GetNamedPipeServerProcessId(HANDLE, *mut u32) -> BOOL
 	GetNativeSystemInfo(PVOID)
+	GetOverlappedResult(HANDLE, PVOID, *mut u32, BOOL) -> BOOL
 	GetPriorityClass(HANDLE) -> u32
 	GetPrivateProfileSectionNamesW(PSTR, u32, PCSTR) -> u32
 	GetPrivateProfileSectionW(PCSTR, PSTR, u32, PCSTR) -> u32
@@ -224,6 +242,7 @@ extern_sys! { "kernel32";
 	OpenEventW(u32, BOOL, PCSTR) -> HANDLE
 	OpenProcess(u32, BOOL, u32) -> HANDLE
 	OutputDebugStringW(PCSTR)
+	PeekNamedPipe(HANDLE, PVOID, u32, *mut u32, *mut u32, *mut u32) -> BOOL
 	Process32FirstW(HANDLE, PVOID) -> BOOL
 	Process32NextW(HANDLE, PVOID) -> BOOL
 	PulseEvent(HANDLE) -> BOOL
@@ -243,6 +262,8 @@ extern_sys! { "kernel32";
 	SetFileAttributesW(PCSTR, u32) -> BOOL
 	SetFilePointerEx(HANDLE, i64, *mut i64, u32) -> BOOL
 	SetFileTime(HANDLE, PCVOID, PCVOID, PCVOID) -> BOOL
+	SetHandleInformation(HANDLE, u32, u32) -> BOOL
+	SetNamedPipeHandleState(HANDLE, *mut u32, *mut u32, *mut u32) -> BOOL
 	SetLastError(u32)
 	SetPriorityClass(HANDLE, u32) -> BOOL
 	SetProcessAffinityUpdateMode(HANDLE, u32) -> BOOL
@@ -267,6 +288,7 @@ extern_sys! { "kernel32";
 	VerifyVersionInfoW(PVOID, u32, u64) -> BOOL
 	VerSetConditionMask(u64, u32, u8) -> u64
 	WaitForSingleObject(HANDLE, u32) -> u32
+	WaitNamedPipeW(PCSTR, u32) -> BOOL
 	WideCharToMultiByte(u32, u32, PCSTR, i32, PSTR, i32, *const u8, *mut BOOL) -> i32
 	WriteConsoleW(HANDLE, PCVOID, u32, *mut u32, PVOID) -> BOOL
 	WriteFile(HANDLE, PCVOID, u32, *mut u32, PVOID) -> BOOL
@@ -3,7 +3,7 @@
 use crate::co;
 use crate::decl::*;
 use crate::guard::*;
-use crate::kernel::{ffi, ffi_types::*, privs::*};
+use crate::kernel::{ffi, ffi_types::*, privs::*, utilities::split_entry};
 use crate::prelude::*;
 
 /// [`AllocateAndInitializeSid`](https://learn.microsoft.com/en-us/windows/win32/api/securitybaseapi/nf-securitybaseapi-allocateandinitializesid)
@@ -100,7 +100,8 @@ pub fn AllocateAndInitializeSid(
 ///
 /// You don't need to call this function directly, because [`SID`](crate::SID)
 /// implements [`Display`](std::fmt::Display) and
-/// [`ToString`](std::string::ToString) traits, which call it.
+/// [`ToString`](std::string::ToString) traits, which produce the same result
+/// in pure Rust.
 ///
 /// # Related functions
 ///
@@ -438,7 +439,7 @@ pub fn EqualPrefixSid(sid1: &SID, sid2: &SID) -> SysResult<bool> {
 ///
 /// You don't need to call this function directly, because [`SID`](crate::SID)
 /// implements [`PartialEq`](std::cmp::PartialEq) and [`Eq`](std::cmp::Eq)
-/// traits, which call it.
+/// traits, which compare the fields in pure Rust.
 ///
 /// # Related functions
 ///
@@ -728,9 +729,7 @@ pub fn GetEnvironmentStrings() -> SysResult<Vec<(String, String)>> {
 			unsafe { ffi::FreeEnvironmentStringsW(ptr); }
 			vec_entries.iter()
 				.map(|env_str| {
-					let mut pair = env_str.split("="); // assumes correctly formatted pairs
-					let key = pair.next().unwrap();
-					let val = pair.next().unwrap();
+					let (key, val) = split_entry(env_str); // keeps entries like "=C:"
 					(key.to_owned(), val.to_owned())
 				})
 				.collect()
@@ -45,6 +45,123 @@ pub trait kernel_Hkey: Handle {
 	predef_key!(PERFORMANCE_TEXT, 0x8000_0050);
 	predef_key!(PERFORMANCE_NLSTEXT, 0x8000_0060);
 
+	/// Reads the given subkey, its values and all its subkeys recursively,
+	/// returning them as a [`RegFile`](crate::RegFile), which can be written
+	/// as a `.reg` file. Calls
+	/// [`HKEY::RegEnumKeyEx`](crate::prelude::kernel_Hkey::RegEnumKeyEx),
+	/// [`HKEY::RegEnumValue`](crate::prelude::kernel_Hkey::RegEnumValue) and
+	/// [`HKEY::RegQueryValueEx`](crate::prelude::kernel_Hkey::RegQueryValueEx).
+	///
+	/// Since the key paths in a `.reg` file start with the name of the
+	/// predefined key, this method must be called on one of:
+	///
+	/// - [`HKEY::CLASSES_ROOT`](crate::prelude::kernel_Hkey::CLASSES_ROOT);
+	/// - [`HKEY::CURRENT_USER`](crate::prelude::kernel_Hkey::CURRENT_USER);
+	/// - [`HKEY::LOCAL_MACHINE`](crate::prelude::kernel_Hkey::LOCAL_MACHINE);
+	/// - [`HKEY::USERS`](crate::prelude::kernel_Hkey::USERS);
+	/// - [`HKEY::CURRENT_CONFIG`](crate::prelude::kernel_Hkey::CURRENT_CONFIG).
+	///
+	/// Otherwise, fails with
+	/// [`co::ERROR::INVALID_HANDLE`](crate::co::ERROR::INVALID_HANDLE).
+	///
+	/// Values whose data cannot be decoded – like a string without its
+	/// terminating null – are exported untouched in the `hex(n):` form, like
+	/// the Registry Editor does, so they don't make the export fail.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let reg_file = w::HKEY::CURRENT_USER.export_reg_file("Software\\My Company")?;
+	/// println!("{}", reg_file);
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	fn export_reg_file(&self, sub_key: &str) -> SysResult<RegFile> {
+		let root_name = PREDEF_KEY_NAMES.iter()
+			.find(|(hkey, _)| hkey.ptr() == self.ptr())
+			.map(|(_, name)| *name)
+			.ok_or(co::ERROR::INVALID_HANDLE)?;
+
+		let hkey = self.RegOpenKeyEx(Some(sub_key), co::REG_OPTION::default(), co::KEY::READ)?;
+		let mut keys = Vec::<RegFileKey>::default();
+		let path = if sub_key.is_empty() {
+			root_name.to_owned()
+		} else {
+			format!("{}\\{}", root_name, sub_key.trim_matches('\\'))
+		};
+		export_reg_key(&hkey, path, &mut keys)?;
+		Ok(RegFile { keys })
+	}
+
+	/// Writes the contents of a [`RegFile`](crate::RegFile) into the registry,
+	/// like the Registry Editor does when importing a `.reg` file. Calls
+	/// [`HKEY::RegCreateKeyEx`](crate::prelude::kernel_Hkey::RegCreateKeyEx),
+	/// [`HKEY::RegSetKeyValue`](crate::prelude::kernel_Hkey::RegSetKeyValue),
+	/// [`HKEY::RegDeleteValue`](crate::prelude::kernel_Hkey::RegDeleteValue)
+	/// and [`HKEY::RegDeleteTree`](crate::prelude::kernel_Hkey::RegDeleteTree).
+	///
+	/// Keys and values to be deleted which don't exist are ignored. Key paths
+	/// must start with the full name of a predefined key, like
+	/// `HKEY_CURRENT_USER`, or one of its usual abbreviations, like `HKCU`;
+	/// otherwise, fails with
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA). It also
+	/// fails if a key to be deleted has no subkey, which would delete the whole
+	/// predefined key.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let reg_file = w::RegFile::parse(
+	///     "Windows Registry Editor Version 5.00\r\n\
+	///     \r\n\
+	///     [HKEY_CURRENT_USER\\Software\\My Company]\r\n\
+	///     \"Color\"=\"blue\"\r\n",
+	/// )?;
+	///
+	/// w::HKEY::import_reg_file(&reg_file)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn import_reg_file(reg_file: &RegFile) -> SysResult<()> {
+		for key in reg_file.keys.iter() {
+			let (root_name, sub_key) = key.path.split_once('\\')
+				.unwrap_or((&key.path, ""));
+			let root = PREDEF_KEY_NAMES.iter()
+				.chain(PREDEF_KEY_ABBREVS.iter())
+				.find(|(_, name)| name.eq_ignore_ascii_case(root_name))
+				.map(|(hkey, _)| hkey)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+
+			if key.delete {
+				if sub_key.trim_matches('\\').is_empty() { // would delete the whole root
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				match root.RegDeleteTree(Some(sub_key)) {
+					Ok(_) | Err(co::ERROR::FILE_NOT_FOUND) => {},
+					Err(e) => return Err(e),
+				}
+				continue;
+			}
+
+			let (hkey, _) = root.RegCreateKeyEx(sub_key, None,
+				co::REG_OPTION::default(), co::KEY::READ | co::KEY::WRITE, None)?;
+
+			for val in key.values.iter() {
+				match &val.data {
+					Some(data) => hkey.RegSetKeyValue(None, val.name.as_deref(), data.clone())?,
+					None => match hkey.RegDeleteValue(val.name.as_deref()) {
+						Ok(_) | Err(co::ERROR::FILE_NOT_FOUND) => {},
+						Err(e) => return Err(e),
+					},
+				}
+			}
+		}
+		Ok(())
+	}
+
 	/// [`RegConnectRegistry`](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regconnectregistryw)
 	/// function.
 	///
@@ -346,8 +463,13 @@ pub trait kernel_Hkey: Handle {
 	/// function.
 	///
 	/// Note that this method validates some race conditions, returning
-	/// [`co::ERROR::TRANSACTION_REQUEST_NOT_VALID`](crate::co::ERROR::TRANSACTION_REQUEST_NOT_VALID)
-	/// and [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	/// [`co::ERROR::TRANSACTION_REQUEST_NOT_VALID`](crate::co::ERROR::TRANSACTION_REQUEST_NOT_VALID).
+	///
+	/// Strings are decoded leniently, so a string without its terminating null
+	/// is still returned as [`RegistryValue::Sz`](crate::RegistryValue::Sz);
+	/// data which cannot be decoded, like invalid UTF-16 or a
+	/// [`REG::DWORD`](crate::co::REG::DWORD) which is not 4 bytes long, is
+	/// returned as [`RegistryValue::Other`](crate::RegistryValue::Other).
 	///
 	/// # Examples
 	///
@@ -381,6 +503,7 @@ pub trait kernel_Hkey: Handle {
 	///         println!("");
 	///     },
 	///     w::RegistryValue::None => println!("No value"),
+	///     other => println!("Other: {}", other),
 	/// }
 	/// # w::SysResult::Ok(())
 	/// ```
@@ -642,6 +765,7 @@ pub trait kernel_Hkey: Handle {
 	///             println!("");
 	///         },
 	///         w::RegistryValue::None => println!("No value"),
+	///         other => println!("Other: {}", other),
 	///     }
 	/// }
 	///
@@ -711,13 +835,8 @@ pub trait kernel_Hkey: Handle {
 
 		Ok(
 			valents2.iter() // first VALENT array is not filled with len/type values
-				.map(|v2| unsafe {
-					RegistryValue::from_raw(
-						v2.buf_projection(&buf).to_vec(),
-						v2.ve_type,
-					)
-				})
-				.collect::<Vec<_>>()
+				.map(|v2| RegistryValue::from_retrieved(unsafe { v2.buf_projection(&buf) }, v2.ve_type))
+				.collect(),
 		)
 	}
 
@@ -738,8 +857,13 @@ pub trait kernel_Hkey: Handle {
 	/// [`HKEY::RegQueryMultipleValues`](crate::prelude::kernel_Hkey::RegQueryMultipleValues).
 	///
 	/// Note that this method validates some race conditions, returning
-	/// [`co::ERROR::TRANSACTION_REQUEST_NOT_VALID`](crate::co::ERROR::TRANSACTION_REQUEST_NOT_VALID)
-	/// and [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	/// [`co::ERROR::TRANSACTION_REQUEST_NOT_VALID`](crate::co::ERROR::TRANSACTION_REQUEST_NOT_VALID).
+	///
+	/// Strings are decoded leniently, so a string without its terminating null
+	/// is still returned as [`RegistryValue::Sz`](crate::RegistryValue::Sz);
+	/// data which cannot be decoded, like invalid UTF-16 or a
+	/// [`REG::DWORD`](crate::co::REG::DWORD) which is not 4 bytes long, is
+	/// returned as [`RegistryValue::Other`](crate::RegistryValue::Other).
 	///
 	/// # Examples
 	///
@@ -776,6 +900,7 @@ pub trait kernel_Hkey: Handle {
 	///         println!("");
 	///     },
 	///     w::RegistryValue::None => println!("No value"),
+	///     other => println!("Other: {}", other),
 	/// }
 	/// # w::SysResult::Ok(())
 	/// ```
@@ -947,8 +1072,7 @@ pub trait kernel_Hkey: Handle {
 		data: RegistryValue,
 	) -> SysResult<()>
 	{
-		let mut str_buf = WString::new();
-		let (data_ptr, data_len) = data.as_ptr_with_len(&mut str_buf);
+		let data_buf = data.to_bytes();
 
 		error_to_sysresult(
 			unsafe {
@@ -957,8 +1081,8 @@ pub trait kernel_Hkey: Handle {
 					WString::from_opt_str(sub_key).as_ptr(),
 					WString::from_opt_str(value_name).as_ptr(),
 					data.reg_type().raw(),
-					data_ptr,
-					data_len,
+					vec_ptr(&data_buf) as _,
+					data_buf.len() as _,
 				)
 			},
 		)
@@ -992,8 +1116,7 @@ pub trait kernel_Hkey: Handle {
 		data: RegistryValue,
 	) -> SysResult<()>
 	{
-		let mut str_buf = WString::new();
-		let (data_ptr, data_len) = data.as_ptr_with_len(&mut str_buf);
+		let data_buf = data.to_bytes();
 
 		error_to_sysresult(
 			unsafe {
@@ -1002,8 +1125,8 @@ pub trait kernel_Hkey: Handle {
 					WString::from_opt_str(value_name).as_ptr(),
 					0,
 					data.reg_type().raw(),
-					data_ptr as _,
-					data_len,
+					vec_ptr(&data_buf) as _,
+					data_buf.len() as _,
 				)
 			},
 		)
@@ -1034,6 +1157,52 @@ impl HKEY {
 
 //------------------------------------------------------------------------------
 
+/// Predefined keys which can appear in `.reg` files.
+const PREDEF_KEY_NAMES: [(HKEY, &str); 5] = [
+	(HKEY::CLASSES_ROOT, "HKEY_CLASSES_ROOT"),
+	(HKEY::CURRENT_USER, "HKEY_CURRENT_USER"),
+	(HKEY::LOCAL_MACHINE, "HKEY_LOCAL_MACHINE"),
+	(HKEY::USERS, "HKEY_USERS"),
+	(HKEY::CURRENT_CONFIG, "HKEY_CURRENT_CONFIG"),
+];
+
+/// Usual abbreviations of the predefined keys.
+const PREDEF_KEY_ABBREVS: [(HKEY, &str); 5] = [
+	(HKEY::CLASSES_ROOT, "HKCR"),
+	(HKEY::CURRENT_USER, "HKCU"),
+	(HKEY::LOCAL_MACHINE, "HKLM"),
+	(HKEY::USERS, "HKU"),
+	(HKEY::CURRENT_CONFIG, "HKCC"),
+];
+
+/// Recursively appends the key, its values and its subkeys.
+fn export_reg_key(
+	hkey: &HKEY,
+	path: String,
+	keys: &mut Vec<RegFileKey>,
+) -> SysResult<()>
+{
+	let values = hkey.RegEnumValue()?
+		.map(|name_type| {
+			let (name, _) = name_type?;
+			let name = if name.is_empty() { None } else { Some(name) }; // default value
+			let data = hkey.RegQueryValueEx(name.as_deref())?;
+			Ok(RegFileValue { name, data: Some(data) })
+		})
+		.collect::<SysResult<Vec<_>>>()?;
+
+	let sub_keys = hkey.RegEnumKeyEx()?
+		.collect::<SysResult<Vec<_>>>()?;
+
+	keys.push(RegFileKey { path: path.clone(), delete: false, values });
+
+	for sub_key in sub_keys.iter() {
+		let hsub = hkey.RegOpenKeyEx(Some(sub_key), co::REG_OPTION::default(), co::KEY::READ)?;
+		export_reg_key(&hsub, format!("{}\\{}", path, sub_key), keys)?;
+	}
+	Ok(())
+}
+
 fn validate_retrieved_reg_val(
 	data_type1: co::REG,
 	data_len1: u32,
@@ -1052,23 +1221,5 @@ fn validate_retrieved_reg_val(
 		return Err(co::ERROR::TRANSACTION_REQUEST_NOT_VALID);
 	}
 
-	if data_type1 == co::REG::DWORD && data_len1 != 4
-		|| data_type1 == co::REG::QWORD && data_len1 != 8
-	{
-		// Data length makes no sense, possibly corrupted.
-		return Err(co::ERROR::INVALID_DATA);
-	}
-
-	if data_type1 == co::REG::SZ {
-		if data_len1 == 0 // empty data
-			|| data_len1 % 2 != 0 // odd number of bytes
-			|| buf[data_len1 as usize - 2] != 0 // terminating null
-			|| buf[data_len1 as usize - 1] != 0
-		{
-			// Bad string.
-			return Err(co::ERROR::INVALID_DATA);
-		}
-	}
-
-	Ok(unsafe { RegistryValue::from_raw(buf, data_type1) })
+	Ok(RegistryValue::from_retrieved(&buf, data_type1))
 }
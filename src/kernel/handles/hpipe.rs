@@ -1,5 +1,6 @@
 #![allow(non_camel_case_types, non_snake_case)]
 
+use crate::co;
 use crate::decl::*;
 use crate::guard::*;
 use crate::kernel::{ffi, privs::*};
@@ -7,8 +8,14 @@ use crate::prelude::*;
 
 impl_handle! { HPIPE;
 	/// Handle to an
-	/// [anonymous pipe](https://learn.microsoft.com/en-us/windows/win32/ipc/anonymous-pipes).
+	/// [anonymous pipe](https://learn.microsoft.com/en-us/windows/win32/ipc/anonymous-pipes)
+	/// or a
+	/// [named pipe](https://learn.microsoft.com/en-us/windows/win32/ipc/named-pipes).
 	/// Originally just a `HANDLE`.
+	///
+	/// For high-level named pipe abstractions, see
+	/// [`NamedPipeServer`](crate::NamedPipeServer) and
+	/// [`NamedPipeClient`](crate::NamedPipeClient).
 }
 
 impl kernel_Hpipe for HPIPE {}
@@ -22,6 +29,91 @@ impl kernel_Hpipe for HPIPE {}
 /// use winsafe::prelude::*;
 /// ```
 pub trait kernel_Hpipe: Handle {
+	/// [`ConnectNamedPipe`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-connectnamedpipe)
+	/// function.
+	///
+	/// Blocks until a client connects to this pipe instance. If the client
+	/// connected before the call, returns successfully as well, instead of
+	/// failing with [`co::ERROR::PIPE_CONNECTED`](crate::co::ERROR::PIPE_CONNECTED).
+	///
+	/// Note that asynchronous connection – which use the
+	/// [`OVERLAPPED`](crate::OVERLAPPED) struct – is not supported by this
+	/// method; [`NamedPipeServer`](crate::NamedPipeServer) implements it
+	/// internally.
+	fn ConnectNamedPipe(&self) -> SysResult<()> {
+		match bool_to_sysresult(
+			unsafe { ffi::ConnectNamedPipe(self.ptr(), std::ptr::null_mut()) },
+		) {
+			Err(co::ERROR::PIPE_CONNECTED) => Ok(()),
+			res => res,
+		}
+	}
+
+	/// [`CreateNamedPipe`](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-createnamedpipew)
+	/// function.
+	///
+	/// The `name` must have the form `\\.\pipe\name`. If `max_instances`
+	/// is `None`, the number of instances is unlimited.
+	///
+	/// # Examples
+	///
+	/// A server which echoes one message back to a single client:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hpipe = w::HPIPE::CreateNamedPipe(
+	///     "\\\\.\\pipe\\my-pipe",
+	///     co::PIPE_ACCESS::DUPLEX,
+	///     co::PIPE::TYPE_MESSAGE | co::PIPE::READMODE_MESSAGE,
+	///     Some(1),
+	///     4096,
+	///     4096,
+	///     0,
+	///     None,
+	///     None,
+	/// )?;
+	///
+	/// hpipe.ConnectNamedPipe()?;
+	/// let mut buf = [0u8; 4096];
+	/// let num_read = hpipe.ReadFile(&mut buf)?;
+	/// hpipe.WriteFile(&buf[..num_read as usize])?;
+	/// hpipe.FlushFileBuffers()?;
+	/// hpipe.DisconnectNamedPipe()?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	fn CreateNamedPipe(
+		name: &str,
+		open_mode: co::PIPE_ACCESS,
+		pipe_mode: co::PIPE,
+		max_instances: Option<u32>,
+		out_buffer_size: u32,
+		in_buffer_size: u32,
+		default_timeout: u32,
+		flags: Option<co::FILE_FLAG>,
+		security_attributes: Option<&mut SECURITY_ATTRIBUTES>,
+	) -> SysResult<CloseHandleGuard<HPIPE>>
+	{
+		unsafe {
+			match HPIPE::from_ptr(
+				ffi::CreateNamedPipeW(
+					WString::from_str(name).as_ptr(),
+					open_mode.raw() | flags.unwrap_or_default().raw(),
+					pipe_mode.raw(),
+					max_instances.unwrap_or(PIPE_UNLIMITED_INSTANCES),
+					out_buffer_size,
+					in_buffer_size,
+					default_timeout,
+					security_attributes.map_or(std::ptr::null_mut(), |lp| lp as *mut _ as _),
+				),
+			) {
+				HPIPE::NULL | HPIPE::INVALID => Err(GetLastError()),
+				handle => Ok(CloseHandleGuard::new(handle)),
+			}
+		}
+	}
+
 	/// [`CreatePipe`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-createpipe)
 	/// function.
 	///
@@ -45,11 +137,117 @@ pub trait kernel_Hpipe: Handle {
 		}
 	}
 
+	/// [`DisconnectNamedPipe`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-disconnectnamedpipe)
+	/// function.
+	///
+	/// Any unread data is discarded; call
+	/// [`HPIPE::FlushFileBuffers`](crate::prelude::kernel_Hpipe::FlushFileBuffers)
+	/// first to wait until the client reads it.
+	fn DisconnectNamedPipe(&self) -> SysResult<()> {
+		bool_to_sysresult(unsafe { ffi::DisconnectNamedPipe(self.ptr()) })
+	}
+
+	/// [`FlushFileBuffers`](https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-flushfilebuffers)
+	/// function.
+	///
+	/// Blocks until all data written to the pipe is read by the other end.
+	fn FlushFileBuffers(&self) -> SysResult<()> {
+		bool_to_sysresult(unsafe { ffi::FlushFileBuffers(self.ptr()) })
+	}
+
+	/// [`GetNamedPipeClientProcessId`](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getnamedpipeclientprocessid)
+	/// function.
+	#[must_use]
+	fn GetNamedPipeClientProcessId(&self) -> SysResult<u32> {
+		let mut pid = u32::default();
+		bool_to_sysresult(
+			unsafe { ffi::GetNamedPipeClientProcessId(self.ptr(), &mut pid) },
+		).map(|_| pid)
+	}
+
+	/// [`GetNamedPipeInfo`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-getnamedpipeinfo)
+	/// function.
+	///
+	/// Returns the pipe flags, the output buffer size, the input buffer size
+	/// and the maximum number of instances, which is
+	/// `None` if unlimited.
+	#[must_use]
+	fn GetNamedPipeInfo(&self) -> SysResult<(co::PIPE, u32, u32, Option<u32>)> {
+		let (mut flags, mut out_sz, mut in_sz, mut max_inst) =
+			(u32::default(), u32::default(), u32::default(), u32::default());
+		bool_to_sysresult(
+			unsafe {
+				ffi::GetNamedPipeInfo(
+					self.ptr(),
+					&mut flags,
+					&mut out_sz,
+					&mut in_sz,
+					&mut max_inst,
+				)
+			},
+		).map(|_| (
+			unsafe { co::PIPE::from_raw(flags) },
+			out_sz,
+			in_sz,
+			if max_inst == PIPE_UNLIMITED_INSTANCES { None } else { Some(max_inst) },
+		))
+	}
+
+	/// [`GetNamedPipeServerProcessId`](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getnamedpipeserverprocessid)
+	/// function.
+	#[must_use]
+	fn GetNamedPipeServerProcessId(&self) -> SysResult<u32> {
+		let mut pid = u32::default();
+		bool_to_sysresult(
+			unsafe { ffi::GetNamedPipeServerProcessId(self.ptr(), &mut pid) },
+		).map(|_| pid)
+	}
+
+	/// [`PeekNamedPipe`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-peeknamedpipe)
+	/// function.
+	///
+	/// Copies data into `buffer`, if any, without removing it from the pipe.
+	/// Returns the number of bytes copied, the total number of bytes available
+	/// and, for message-mode pipes, the number of bytes left in the current
+	/// message.
+	///
+	/// Unlike [`ReadFile`](crate::prelude::kernel_Hpipe::ReadFile), this
+	/// function never blocks.
+	#[must_use]
+	fn PeekNamedPipe(&self,
+		buffer: Option<&mut [u8]>,
+	) -> SysResult<(u32, u32, u32)>
+	{
+		let (mut num_read, mut total_avail, mut left_this_msg) =
+			(u32::default(), u32::default(), u32::default());
+		let (buf_ptr, buf_len) = buffer
+			.map_or((std::ptr::null_mut(), 0), |buf| (buf.as_mut_ptr(), buf.len()));
+		bool_to_sysresult(
+			unsafe {
+				ffi::PeekNamedPipe(
+					self.ptr(),
+					buf_ptr as _,
+					buf_len as _,
+					&mut num_read,
+					&mut total_avail,
+					&mut left_this_msg,
+				)
+			},
+		).map(|_| (num_read, total_avail, left_this_msg))
+	}
+
 	/// [`ReadFile`](https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-readfile)
 	/// function.
 	///
 	/// Returns the number of bytes read.
 	///
+	/// When reading a message-mode pipe with a buffer smaller than the message,
+	/// the buffer is filled and the function fails with
+	/// [`co::ERROR::MORE_DATA`](crate::co::ERROR::MORE_DATA); the rest of the
+	/// message is returned by the next calls. The size of the message can be
+	/// retrieved beforehand with
+	/// [`HPIPE::PeekNamedPipe`](crate::prelude::kernel_Hpipe::PeekNamedPipe).
+	///
 	/// Note that asynchronous reading – which use the
 	/// [`OVERLAPPED`](crate::OVERLAPPED) struct – is not currently supported by
 	/// this method, because the buffer must remain untouched until the async
@@ -59,6 +257,78 @@ pub trait kernel_Hpipe: Handle {
 			.ReadFile(buffer)
 	}
 
+	/// [`SetHandleInformation`](https://learn.microsoft.com/en-us/windows/win32/api/handleapi/nf-handleapi-sethandleinformation)
+	/// function.
+	///
+	/// # Examples
+	///
+	/// Making the read end of a pipe inheritable by a child process:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let (hread, hwrite) = w::HPIPE::CreatePipe(None, 0)?;
+	/// hread.SetHandleInformation(co::HANDLE_FLAG::INHERIT, co::HANDLE_FLAG::INHERIT)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn SetHandleInformation(&self,
+		mask: co::HANDLE_FLAG,
+		flags: co::HANDLE_FLAG,
+	) -> SysResult<()>
+	{
+		bool_to_sysresult(
+			unsafe { ffi::SetHandleInformation(self.ptr(), mask.raw(), flags.raw()) },
+		)
+	}
+
+	/// [`SetNamedPipeHandleState`](https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-setnamedpipehandlestate)
+	/// function.
+	///
+	/// Usually called by a client to read a message-mode pipe in
+	/// [`co::PIPE::READMODE_MESSAGE`](crate::co::PIPE::READMODE_MESSAGE).
+	/// Parameters which are `None` are left unchanged.
+	fn SetNamedPipeHandleState(&self,
+		mode: Option<co::PIPE>,
+		max_collection_count: Option<u32>,
+		collect_data_timeout: Option<u32>,
+	) -> SysResult<()>
+	{
+		let mut mode = mode.map(|m| m.raw());
+		let mut max_collection_count = max_collection_count;
+		let mut collect_data_timeout = collect_data_timeout;
+		bool_to_sysresult(
+			unsafe {
+				ffi::SetNamedPipeHandleState(
+					self.ptr(),
+					mode.as_mut().map_or(std::ptr::null_mut(), |m| m),
+					max_collection_count.as_mut().map_or(std::ptr::null_mut(), |c| c),
+					collect_data_timeout.as_mut().map_or(std::ptr::null_mut(), |t| t),
+				)
+			},
+		)
+	}
+
+	/// [`WaitNamedPipe`](https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-waitnamedpipew)
+	/// function.
+	///
+	/// Waits until an instance of the pipe is available for connection. If
+	/// `milliseconds` is `None`, waits indefinitely.
+	///
+	/// Fails with [`co::ERROR::SEM_TIMEOUT`](crate::co::ERROR::SEM_TIMEOUT) if
+	/// the timeout elapses, and with
+	/// [`co::ERROR::FILE_NOT_FOUND`](crate::co::ERROR::FILE_NOT_FOUND) if the
+	/// pipe doesn't exist.
+	fn WaitNamedPipe(name: &str, milliseconds: Option<u32>) -> SysResult<()> {
+		bool_to_sysresult(
+			unsafe {
+				ffi::WaitNamedPipeW(
+					WString::from_str(name).as_ptr(),
+					milliseconds.unwrap_or(NMPWAIT_WAIT_FOREVER),
+				)
+			},
+		)
+	}
+
 	/// [`WriteFile`](https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-writefile)
 	/// function.
 	///
@@ -35,6 +35,12 @@ pub trait kernel_Hprocess: Handle {
 
 	/// [`CreateProcess`](https://learn.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-createprocessw)
 	/// function.
+	///
+	/// If `environment` is given,
+	/// [`co::CREATE::UNICODE_ENVIRONMENT`](crate::co::CREATE::UNICODE_ENVIRONMENT)
+	/// is automatically added to `creation_flags`.
+	///
+	/// For an example, see [`EnvBlock`](crate::EnvBlock).
 	#[must_use]
 	fn CreateProcess(
 		application_name: Option<&str>,
@@ -43,12 +49,18 @@ pub trait kernel_Hprocess: Handle {
 		thread_attrs: Option<&mut SECURITY_ATTRIBUTES>,
 		inherit_handles: bool,
 		creation_flags: co::CREATE,
-		environment: Option<Vec<(&str, &str)>>,
+		environment: Option<&EnvBlock>,
 		current_dir: Option<&str>,
 		si: &mut STARTUPINFO,
 	) -> SysResult<CloseHandlePiGuard>
 	{
 		let mut buf_cmd_line = WString::from_opt_str(command_line);
+		let mut buf_env = environment.map(|env| env.to_block()); // must outlive the call
+		let creation_flags = if buf_env.is_some() {
+			creation_flags | co::CREATE::UNICODE_ENVIRONMENT
+		} else {
+			creation_flags
+		};
 		let mut pi = PROCESS_INFORMATION::default();
 
 		unsafe {
@@ -60,13 +72,7 @@ pub trait kernel_Hprocess: Handle {
 					thread_attrs.map_or(std::ptr::null_mut(), |lp| lp as *mut _ as _),
 					inherit_handles as _,
 					creation_flags.raw(),
-					environment.map_or(std::ptr::null_mut(), |environment| {
-						WString::from_str_vec(
-							&environment.iter()
-								.map(|(name, val)| format!("{}={}", name, val))
-								.collect::<Vec<_>>()
-						).as_ptr() as _
-					}),
+					buf_env.as_mut().map_or(std::ptr::null_mut(), |buf| buf.as_mut_ptr() as _),
 					WString::from_opt_str(current_dir).as_ptr(),
 					si as *mut _ as _,
 					&mut pi as *mut _ as _,
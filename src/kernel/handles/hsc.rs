@@ -2,7 +2,7 @@
 
 use crate::co;
 use crate::decl::*;
-use crate::kernel::{ffi, guard::*, privs::*};
+use crate::kernel::{ffi, ffi_types::*, guard::*, privs::*};
 use crate::prelude::*;
 
 impl_handle! { HSC;
@@ -22,6 +22,69 @@ impl kernel_Hsc for HSC {}
 /// use winsafe::prelude::*;
 /// ```
 pub trait kernel_Hsc: Handle {
+	/// Installs the current executable as a service which runs in its own
+	/// process, under the `LocalSystem` account, by calling
+	/// [`HSC::CreateService`](crate::prelude::kernel_Hsc::CreateService).
+	///
+	/// The `args` are appended to the quoted executable path, quoted with
+	/// [`cmd_line::quote`](crate::cmd_line::quote).
+	///
+	/// The executable should run a [`ServiceHost`](crate::ServiceHost) with a
+	/// service of the same name.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::CREATE_SERVICE)?;
+	/// hsc.install_service(
+	///     "MyService",
+	///     "My Service",
+	///     &["--service"],
+	///     co::SERVICE_START::AUTO_START,
+	/// )?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn install_service(&self,
+		service_name: &str,
+		display_name: &str,
+		args: &[impl AsRef<str>],
+		start_type: co::SERVICE_START,
+	) -> SysResult<CloseServiceHandleSvcGuard>
+	{
+		let mut cmd_line = format!("\"{}\"", HINSTANCE::NULL.GetModuleFileName()?);
+		for arg in args.iter() {
+			cmd_line.push(' ');
+			cmd_line.push_str(&cmd_line::quote(arg.as_ref()));
+		}
+
+		self.CreateService(
+			service_name,
+			Some(display_name),
+			co::SERVICE::ALL_ACCESS,
+			co::SERVICE_TYPE::WIN32_OWN_PROCESS,
+			start_type,
+			co::SERVICE_ERROR::NORMAL,
+			Some(&cmd_line),
+			None::<&[&str]>,
+			None,
+			None::<&[&str]>,
+			None,
+			None,
+		)
+	}
+
+	/// Opens the service and marks it for deletion, by calling
+	/// [`HSC::OpenService`](crate::prelude::kernel_Hsc::OpenService) and
+	/// [`HSERVICE::DeleteService`](crate::prelude::kernel_Hservice::DeleteService).
+	///
+	/// If the service is running, it's removed only after it stops.
+	fn uninstall_service(&self, service_name: &str) -> SysResult<()> {
+		self.OpenService(service_name, co::SERVICE::DELETE)?
+			.DeleteService()
+	}
+
 	/// [`CreateService`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-createservicew)
 	/// function.
 	#[must_use]
@@ -66,6 +129,93 @@ pub trait kernel_Hsc: Handle {
 		}
 	}
 
+	/// [`EnumServicesStatusEx`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-enumservicesstatusexw)
+	/// function.
+	///
+	/// Returns the name, the display name and the status of each service. If
+	/// `group_name` is `None`, services are enumerated regardless of their
+	/// load ordering group.
+	///
+	/// # Examples
+	///
+	/// Listing the running services:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::ENUMERATE_SERVICE)?;
+	///
+	/// for (name, display_name, status) in hsc.EnumServicesStatusEx(
+	///     co::SERVICE_TYPE::WIN32,
+	///     co::SERVICE_ENUM_STATE::ACTIVE,
+	///     None,
+	/// )? {
+	///     println!("{} ({}), PID {}", name, display_name, status.dwProcessId);
+	/// }
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	fn EnumServicesStatusEx(&self,
+		service_type: co::SERVICE_TYPE,
+		service_state: co::SERVICE_ENUM_STATE,
+		group_name: Option<&str>,
+	) -> SysResult<Vec<(String, String, SERVICE_STATUS_PROCESS)>>
+	{
+		let wgroup_name = WString::from_opt_str(group_name);
+		let mut buf = Vec::<u64>::default(); // aligned for the pointers
+		let mut resume_handle = u32::default();
+		let mut services = Vec::default();
+
+		loop {
+			let mut bytes_needed = u32::default();
+			let mut num_returned = u32::default();
+
+			let ret = bool_to_sysresult(
+				unsafe {
+					ffi::EnumServicesStatusExW(
+						self.ptr(),
+						SC_ENUM_PROCESS_INFO,
+						service_type.raw(),
+						service_state.raw(),
+						if buf.is_empty() { std::ptr::null_mut() } else { buf.as_mut_ptr() as _ },
+						(buf.len() * 8) as _,
+						&mut bytes_needed,
+						&mut num_returned,
+						&mut resume_handle,
+						wgroup_name.as_ptr(),
+					)
+				},
+			);
+
+			if num_returned > 0 {
+				let entries = unsafe {
+					std::slice::from_raw_parts(
+						buf.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESS,
+						num_returned as _,
+					)
+				};
+				services.extend(entries.iter().map(|entry| unsafe {
+					(
+						WString::from_wchars_nullt(entry.lpServiceName).to_string(),
+						WString::from_wchars_nullt(entry.lpDisplayName).to_string(),
+						entry.ServiceStatusProcess,
+					)
+				}));
+			}
+
+			match ret {
+				Ok(_) => return Ok(services),
+				Err(co::ERROR::MORE_DATA) => { // remaining entries will be fetched from resume_handle
+					let bytes_needed = bytes_needed as usize;
+					if bytes_needed > buf.len() * 8 {
+						buf.resize(bytes_needed.div_ceil(8), 0);
+					}
+				},
+				Err(err) => return Err(err),
+			}
+		}
+	}
+
 	/// [`OpenSCManager`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-openscmanagerw)
 	/// function.
 	#[must_use]
@@ -104,3 +254,12 @@ pub trait kernel_Hsc: Handle {
 		}
 	}
 }
+
+/// [`ENUM_SERVICE_STATUS_PROCESS`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-enum_service_status_processw)
+/// struct.
+#[repr(C)]
+struct ENUM_SERVICE_STATUS_PROCESS {
+	lpServiceName: PSTR,
+	lpDisplayName: PSTR,
+	ServiceStatusProcess: SERVICE_STATUS_PROCESS,
+}
@@ -1,7 +1,10 @@
 #![allow(non_camel_case_types, non_snake_case)]
 
+use std::time::{Duration, Instant};
+
+use crate::co;
 use crate::decl::*;
-use crate::kernel::{ffi, privs::*};
+use crate::kernel::{ffi, ffi_types::*, privs::*};
 use crate::prelude::*;
 
 impl_handle! { HSERVICE;
@@ -21,9 +24,384 @@ impl kernel_Hservice for HSERVICE {}
 /// use winsafe::prelude::*;
 /// ```
 pub trait kernel_Hservice: Handle {
+	/// Blocks until the service reaches the given state, by polling
+	/// [`HSERVICE::QueryServiceStatusEx`](crate::prelude::kernel_Hservice::QueryServiceStatusEx),
+	/// returning its final status.
+	///
+	/// The polling interval is a tenth of the wait hint reported by the
+	/// service, between 100 milliseconds and 1 second.
+	///
+	/// Fails with [`co::ERROR::TIMEOUT`](crate::co::ERROR::TIMEOUT) if the
+	/// state is not reached within `milliseconds`. If the service stops while
+	/// another state is awaited – for example, because it failed to start –
+	/// fails right away with the exit code of the service, or with
+	/// [`co::ERROR::SERVICE_NOT_ACTIVE`](crate::co::ERROR::SERVICE_NOT_ACTIVE)
+	/// if there's none.
+	///
+	/// # Examples
+	///
+	/// Restarting a service:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::CONNECT)?;
+	/// let hsvc = hsc.OpenService(
+	///     "MyService",
+	///     co::SERVICE::START | co::SERVICE::STOP | co::SERVICE::QUERY_STATUS,
+	/// )?;
+	///
+	/// hsvc.ControlService(co::SERVICE_CONTROL::STOP)?;
+	/// hsvc.wait_for_state(co::SERVICE_STATE::STOPPED, 30_000)?;
+	///
+	/// hsvc.StartService(&[] as &[&str])?;
+	/// hsvc.wait_for_state(co::SERVICE_STATE::RUNNING, 30_000)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn wait_for_state(&self,
+		state: co::SERVICE_STATE,
+		milliseconds: u32,
+	) -> SysResult<SERVICE_STATUS_PROCESS>
+	{
+		let timeout = Duration::from_millis(milliseconds as _);
+		let start = Instant::now();
+
+		loop {
+			let status = self.QueryServiceStatusEx()?;
+			if status.dwCurrentState == state {
+				return Ok(status);
+			} else if status.dwCurrentState == co::SERVICE_STATE::STOPPED {
+				return Err(match unsafe { co::ERROR::from_raw(status.dwWin32ExitCode) } {
+					co::ERROR::SUCCESS => co::ERROR::SERVICE_NOT_ACTIVE,
+					err => err,
+				});
+			}
+
+			let elapsed = start.elapsed();
+			if elapsed >= timeout {
+				return Err(co::ERROR::TIMEOUT);
+			}
+			let interval = Duration::from_millis((status.dwWaitHint / 10).clamp(100, 1000) as _);
+			std::thread::sleep(interval.min(timeout - elapsed));
+		}
+	}
+
+	/// [`ChangeServiceConfig`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-changeserviceconfigw)
+	/// function.
+	///
+	/// Parameters which are `None` are left unchanged. Unlike
+	/// [`HSC::CreateService`](crate::prelude::kernel_Hsc::CreateService),
+	/// `binary_path_name` is not automatically quoted.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::CONNECT)?;
+	/// let hsvc = hsc.OpenService("MyService", co::SERVICE::CHANGE_CONFIG)?;
+	///
+	/// hsvc.ChangeServiceConfig(
+	///     None,
+	///     Some(co::SERVICE_START::DEMAND_START),
+	///     None,
+	///     None,
+	///     None,
+	///     None,
+	///     None::<&[&str]>,
+	///     None,
+	///     None,
+	///     None,
+	/// )?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn ChangeServiceConfig(&self,
+		service_type: Option<co::SERVICE_TYPE>,
+		start_type: Option<co::SERVICE_START>,
+		error_control: Option<co::SERVICE_ERROR>,
+		binary_path_name: Option<&str>,
+		load_order_group: Option<&str>,
+		tag_id: Option<&mut u32>,
+		dependencies: Option<&[impl AsRef<str>]>,
+		service_start_name: Option<&str>,
+		password: Option<&str>,
+		display_name: Option<&str>,
+	) -> SysResult<()>
+	{
+		let wdependencies = dependencies.map(WString::from_str_vec);
+		bool_to_sysresult(
+			unsafe {
+				ffi::ChangeServiceConfigW(
+					self.ptr(),
+					service_type.map_or(SERVICE_NO_CHANGE, |t| t.raw()),
+					start_type.map_or(SERVICE_NO_CHANGE, |t| t.raw()),
+					error_control.map_or(SERVICE_NO_CHANGE, |e| e.raw()),
+					WString::from_opt_str(binary_path_name).as_ptr(),
+					WString::from_opt_str(load_order_group).as_ptr(),
+					tag_id.map_or(std::ptr::null_mut(), |n| n),
+					wdependencies.as_ref().map_or(std::ptr::null(), |w| w.as_ptr()),
+					WString::from_opt_str(service_start_name).as_ptr(),
+					WString::from_opt_str(password).as_ptr(),
+					WString::from_opt_str(display_name).as_ptr(),
+				)
+			},
+		)
+	}
+
+	/// [`ChangeServiceConfig2`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-changeserviceconfig2w)
+	/// function.
+	///
+	/// Failure actions which restart the service require the handle to have
+	/// the [`co::SERVICE::START`](crate::co::SERVICE::START) access right.
+	///
+	/// # Examples
+	///
+	/// Setting the description, and restarting the service 5 seconds after
+	/// each failure:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::CONNECT)?;
+	/// let hsvc = hsc.OpenService(
+	///     "MyService",
+	///     co::SERVICE::CHANGE_CONFIG | co::SERVICE::START,
+	/// )?;
+	///
+	/// hsvc.ChangeServiceConfig2(
+	///     w::ServiceConfigInfo::Description("Does important things."))?;
+	///
+	/// hsvc.ChangeServiceConfig2(w::ServiceConfigInfo::FailureActions {
+	///     reset_period: Some(24 * 60 * 60),
+	///     reboot_msg: None,
+	///     command: None,
+	///     actions: &[w::SC_ACTION {
+	///         Type: co::SC_ACTION_TYPE::RESTART,
+	///         Delay: 5_000,
+	///     }],
+	/// })?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	fn ChangeServiceConfig2(&self, info: ServiceConfigInfo) -> SysResult<()> {
+		let change = |info_level: u32, info_ptr: PVOID| bool_to_sysresult(
+			unsafe { ffi::ChangeServiceConfig2W(self.ptr(), info_level, info_ptr) },
+		);
+
+		match info {
+			ServiceConfigInfo::DelayedAutoStart(delayed) => {
+				let mut raw = SERVICE_DELAYED_AUTO_START_INFO { fDelayedAutostart: delayed as _ };
+				change(SERVICE_CONFIG_DELAYED_AUTO_START_INFO, &mut raw as *mut _ as _)
+			},
+			ServiceConfigInfo::Description(description) => {
+				let wdescription = service_config_wstr(Some(description));
+				let mut raw = SERVICE_DESCRIPTION { lpDescription: wdescription.as_ptr() };
+				change(SERVICE_CONFIG_DESCRIPTION, &mut raw as *mut _ as _)
+			},
+			ServiceConfigInfo::FailureActions { reset_period, reboot_msg, command, actions } => {
+				let wreboot_msg = service_config_wstr(reboot_msg);
+				let wcommand = service_config_wstr(command);
+				let mut raw = SERVICE_FAILURE_ACTIONS {
+					dwResetPeriod: reset_period.unwrap_or(INFINITE),
+					lpRebootMsg: wreboot_msg.as_ptr(),
+					lpCommand: wcommand.as_ptr(),
+					cActions: actions.len() as _,
+					lpsaActions: actions.as_ptr(), // not null even if empty, so actions are deleted
+				};
+				change(SERVICE_CONFIG_FAILURE_ACTIONS, &mut raw as *mut _ as _)
+			},
+			ServiceConfigInfo::FailureActionsOnNonCrashFailures(enabled) => {
+				let mut raw = SERVICE_FAILURE_ACTIONS_FLAG {
+					fFailureActionsOnNonCrashFailures: enabled as _,
+				};
+				change(SERVICE_CONFIG_FAILURE_ACTIONS_FLAG, &mut raw as *mut _ as _)
+			},
+		}
+	}
+
+	/// [`ControlService`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-controlservice)
+	/// function.
+	///
+	/// Returns the latest status of the service. Note that the function returns
+	/// as soon as the control is delivered; to wait until the service stops or
+	/// pauses, use
+	/// [`HSERVICE::wait_for_state`](crate::prelude::kernel_Hservice::wait_for_state).
+	fn ControlService(&self,
+		control: co::SERVICE_CONTROL,
+	) -> SysResult<SERVICE_STATUS>
+	{
+		let mut status = SERVICE_STATUS::default();
+		bool_to_sysresult(
+			unsafe {
+				ffi::ControlService(self.ptr(), control.raw(), &mut status as *mut _ as _)
+			},
+		).map(|_| status)
+	}
+
 	/// [`DeleteService`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-deleteservice)
 	/// function.
 	fn DeleteService(&self) -> SysResult<()> {
 		bool_to_sysresult(unsafe { ffi::DeleteService(self.ptr()) })
 	}
+
+	/// [`QueryServiceConfig`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-queryserviceconfigw)
+	/// function.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hsc = w::HSC::OpenSCManager(None, co::SC_MANAGER::CONNECT)?;
+	/// let hsvc = hsc.OpenService("MyService", co::SERVICE::QUERY_CONFIG)?;
+	///
+	/// let config = hsvc.QueryServiceConfig()?;
+	/// println!("{} runs {}", config.display_name, config.binary_path_name);
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	fn QueryServiceConfig(&self) -> SysResult<ServiceConfig> {
+		let mut bytes_needed = u32::default();
+		match bool_to_sysresult(
+			unsafe {
+				ffi::QueryServiceConfigW(self.ptr(), std::ptr::null_mut(), 0, &mut bytes_needed)
+			},
+		) {
+			Err(co::ERROR::INSUFFICIENT_BUFFER) => {},
+			Err(err) => return Err(err),
+			Ok(_) => return Err(co::ERROR::INVALID_DATA), // a zero-sized config is not possible
+		}
+
+		let mut buf = vec![0u64; (bytes_needed as usize).div_ceil(8)]; // aligned for the pointers
+		bool_to_sysresult(
+			unsafe {
+				ffi::QueryServiceConfigW(
+					self.ptr(),
+					buf.as_mut_ptr() as _,
+					(buf.len() * 8) as _,
+					&mut bytes_needed,
+				)
+			},
+		)?;
+
+		let raw = unsafe { &*(buf.as_ptr() as *const QUERY_SERVICE_CONFIG) };
+		Ok(ServiceConfig {
+			service_type: unsafe { co::SERVICE_TYPE::from_raw(raw.dwServiceType) },
+			start_type: unsafe { co::SERVICE_START::from_raw(raw.dwStartType) },
+			error_control: unsafe { co::SERVICE_ERROR::from_raw(raw.dwErrorControl) },
+			binary_path_name: ptr_to_string(raw.lpBinaryPathName),
+			load_order_group: ptr_to_string(raw.lpLoadOrderGroup),
+			tag_id: raw.dwTagId,
+			dependencies: if raw.lpDependencies.is_null() {
+				Vec::default()
+			} else {
+				parse_multi_z_str(raw.lpDependencies)
+			},
+			service_start_name: ptr_to_string(raw.lpServiceStartName),
+			display_name: ptr_to_string(raw.lpDisplayName),
+		})
+	}
+
+	/// [`QueryServiceStatusEx`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-queryservicestatusex)
+	/// function.
+	#[must_use]
+	fn QueryServiceStatusEx(&self) -> SysResult<SERVICE_STATUS_PROCESS> {
+		let mut status = SERVICE_STATUS_PROCESS::default();
+		let mut bytes_needed = u32::default();
+		bool_to_sysresult(
+			unsafe {
+				ffi::QueryServiceStatusEx(
+					self.ptr(),
+					SC_STATUS_PROCESS_INFO,
+					&mut status as *mut _ as _,
+					std::mem::size_of::<SERVICE_STATUS_PROCESS>() as _,
+					&mut bytes_needed,
+				)
+			},
+		).map(|_| status)
+	}
+
+	/// [`StartService`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-startservicew)
+	/// function.
+	///
+	/// The `args` are passed to the service entry point, after the service
+	/// name. Note that the function returns as soon as the service process
+	/// starts; to wait until the service is running, use
+	/// [`HSERVICE::wait_for_state`](crate::prelude::kernel_Hservice::wait_for_state).
+	fn StartService(&self, args: &[impl AsRef<str>]) -> SysResult<()> {
+		let (_wargs, pargs) = create_wstr_ptr_vecs(Some(args));
+		bool_to_sysresult(
+			unsafe {
+				ffi::StartServiceW(self.ptr(), pargs.len() as _, vec_ptr(&pargs))
+			},
+		)
+	}
+}
+
+/// Copies a string which may be null.
+#[must_use]
+fn ptr_to_string(src: PCSTR) -> String {
+	if src.is_null() {
+		String::default()
+	} else {
+		unsafe { WString::from_wchars_nullt(src) }.to_string()
+	}
+}
+
+/// [`QUERY_SERVICE_CONFIG`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-query_service_configw)
+/// struct, returned as [`ServiceConfig`](crate::ServiceConfig).
+#[repr(C)]
+struct QUERY_SERVICE_CONFIG {
+	dwServiceType: u32,
+	dwStartType: u32,
+	dwErrorControl: u32,
+	lpBinaryPathName: PCSTR,
+	lpLoadOrderGroup: PCSTR,
+	dwTagId: u32,
+	lpDependencies: PCSTR,
+	lpServiceStartName: PCSTR,
+	lpDisplayName: PCSTR,
+}
+
+/// Converts a string for
+/// [`HSERVICE::ChangeServiceConfig2`](crate::prelude::kernel_Hservice::ChangeServiceConfig2),
+/// where a null pointer leaves the setting unchanged: an empty string is still
+/// allocated, so it clears the setting.
+#[must_use]
+fn service_config_wstr(s: Option<&str>) -> WString {
+	match s {
+		Some("") => WString::new_alloc_buf(1), // just the terminating null
+		s => WString::from_opt_str(s),
+	}
+}
+
+/// [`SERVICE_DELAYED_AUTO_START_INFO`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_delayed_auto_start_info)
+/// struct.
+#[repr(C)]
+struct SERVICE_DELAYED_AUTO_START_INFO {
+	fDelayedAutostart: BOOL,
+}
+
+/// [`SERVICE_DESCRIPTION`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_descriptionw)
+/// struct.
+#[repr(C)]
+struct SERVICE_DESCRIPTION {
+	lpDescription: PCSTR,
+}
+
+/// [`SERVICE_FAILURE_ACTIONS`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_failure_actionsw)
+/// struct.
+#[repr(C)]
+struct SERVICE_FAILURE_ACTIONS {
+	dwResetPeriod: u32,
+	lpRebootMsg: PCSTR,
+	lpCommand: PCSTR,
+	cActions: u32,
+	lpsaActions: *const SC_ACTION,
+}
+
+/// [`SERVICE_FAILURE_ACTIONS_FLAG`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_failure_actions_flag)
+/// struct.
+#[repr(C)]
+struct SERVICE_FAILURE_ACTIONS_FLAG {
+	fFailureActionsOnNonCrashFailures: BOOL,
 }
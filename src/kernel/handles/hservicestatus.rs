@@ -23,26 +23,37 @@ impl kernel_Hservicestatus for HSERVICESTATUS {}
 pub trait kernel_Hservicestatus: Handle {
 	/// [`RegisterServiceCtrlHandlerEx`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-registerservicectrlhandlerexw)
 	/// function.
+	///
+	/// The handler is called by the thread which called
+	/// [`StartServiceCtrlDispatcher`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-startservicectrldispatcherw),
+	/// and it's kept alive until the process ends, since there is no way to
+	/// unregister it.
+	///
+	/// Unless you need something specific, consider using the
+	/// [`ServiceHost`](crate::ServiceHost) high-level abstraction.
 	fn RegisterServiceCtrlHandlerEx<F>(
 		service_name: &str,
 		handler_proc: F,
 	) -> SysResult<HSERVICESTATUS>
-		where F: FnMut(SvcCtl) -> u32,
+		where F: FnMut(SvcCtl) -> u32 + Send + 'static,
 	{
+		let handler_ptr = Box::into_raw(Box::new(handler_proc));
 		ptr_to_sysresult_handle(
 			unsafe {
 				ffi::RegisterServiceCtrlHandlerExW(
 					WString::from_str(service_name).as_ptr(),
 					proc::hservicestatus_register_service_ctrl_handler_ex::<F> as _,
-					&handler_proc as *const _ as _,
+					handler_ptr as _,
 				)
 			},
-		)
+		).inspect_err(|_| {
+			drop(unsafe { Box::from_raw(handler_ptr) }); // handler won't be called
+		})
 	}
 
-  /// [`SetServiceStatus`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-setservicestatus)
-  /// function.
-  fn SetServiceStatus(&self, status: &mut SERVICE_STATUS) -> SysResult<()> {
+	/// [`SetServiceStatus`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-setservicestatus)
+	/// function.
+	fn SetServiceStatus(&self, status: &mut SERVICE_STATUS) -> SysResult<()> {
 		bool_to_sysresult(
 			unsafe {
 				ffi::SetServiceStatus(self.ptr(), status as *mut _ as _)
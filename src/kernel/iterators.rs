@@ -582,3 +582,44 @@ impl<'a, H> HprocesslistThreadIter<'a, H>
 		}
 	}
 }
+
+//------------------------------------------------------------------------------
+
+pub(in crate::kernel) struct TextReaderLinesIter<'a, R>
+	where R: std::io::Read,
+{
+	reader: &'a mut TextReader<R>,
+	has_more: bool,
+}
+
+impl<'a, R> Iterator for TextReaderLinesIter<'a, R>
+	where R: std::io::Read,
+{
+	type Item = std::io::Result<String>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if !self.has_more {
+			return None;
+		}
+
+		match self.reader.read_line() {
+			Err(e) => {
+				self.has_more = false; // no further iterations
+				Some(Err(e))
+			},
+			Ok(line) => {
+				self.has_more = line.is_some();
+				line.map(Ok)
+			},
+		}
+	}
+}
+
+impl<'a, R> TextReaderLinesIter<'a, R>
+	where R: std::io::Read,
+{
+	#[must_use]
+	pub(in crate::kernel) fn new(reader: &'a mut TextReader<R>) -> Self {
+		Self { reader, has_more: true }
+	}
+}
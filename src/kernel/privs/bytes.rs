@@ -0,0 +1,37 @@
+//! Bounds-checked readers of little-endian binary data, and alignment helpers,
+//! used by the parsers and writers of resource and file formats.
+
+use crate::co;
+use crate::decl::*;
+
+/// Returns a slice of the data, failing with
+/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA) if out of
+/// bounds.
+pub(crate) fn slice_at(data: &[u8], offset: usize, len: usize) -> SysResult<&[u8]> {
+	offset.checked_add(len)
+		.and_then(|end| data.get(offset..end))
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> SysResult<u16> {
+	slice_at(data, offset, 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> SysResult<u32> {
+	slice_at(data, offset, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+pub(crate) fn read_u32_be(data: &[u8], offset: usize) -> SysResult<u32> {
+	slice_at(data, offset, 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Rounds the position up to a `DWORD` boundary.
+#[must_use]
+pub(crate) const fn align4(pos: usize) -> usize {
+	(pos + 3) & !3
+}
+
+/// Appends zeros until the buffer length is at a `DWORD` boundary.
+pub(crate) fn pad4(buf: &mut Vec<u8>) {
+	buf.resize(align4(buf.len()), 0x00);
+}
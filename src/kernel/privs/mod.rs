@@ -1,5 +1,7 @@
 #![allow(dead_code, non_snake_case)]
 
+pub(crate) mod bytes;
+
 use crate::co;
 use crate::decl::*;
 use crate::kernel::ffi_types::*;
@@ -12,8 +14,17 @@ pub(crate) const LMEM_INVALID_HANDLE: u32 = 0x8000;
 pub(crate) const MAX_COMPUTERNAME_LENGTH: usize = 15;
 pub(crate) const MAX_MODULE_NAME32: usize = 255;
 pub(crate) const MAX_PATH: usize = 260;
+pub(crate) const NMPWAIT_WAIT_FOREVER: u32 = 0xffff_ffff;
+pub(crate) const PIPE_UNLIMITED_INSTANCES: u32 = 255;
+pub(crate) const SC_ENUM_PROCESS_INFO: u32 = 0;
+pub(crate) const SC_STATUS_PROCESS_INFO: u32 = 0;
 pub(crate) const SECURITY_DESCRIPTOR_REVISION: u32 = 1;
 pub(crate) const SECURITY_SQOS_PRESENT: u32 = 0x0010_0000;
+pub(crate) const SERVICE_CONFIG_DELAYED_AUTO_START_INFO: u32 = 3;
+pub(crate) const SERVICE_CONFIG_DESCRIPTION: u32 = 1;
+pub(crate) const SERVICE_CONFIG_FAILURE_ACTIONS: u32 = 2;
+pub(crate) const SERVICE_CONFIG_FAILURE_ACTIONS_FLAG: u32 = 4;
+pub(crate) const SERVICE_NO_CHANGE: u32 = 0xffff_ffff;
 pub(crate) const SID_HASH_SIZE: usize = 32;
 pub(crate) const SSO_LEN: usize = 20; // defines WString SSO stack buffer size
 pub(crate) const TOKEN_SOURCE_LENGTH: usize = 8;
@@ -1,9 +1,33 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::ffi;
+
 /// [`GUID`](https://learn.microsoft.com/en-us/windows/win32/api/guiddef/ns-guiddef-guid)
 /// struct.
 ///
 /// The [`Default`](std::default::Default) implementation returns `GUID::NULL`
 /// (all zeros). To create a new random `GUID`, use
-/// [`CoCreateGuid`](crate::CoCreateGuid).
+/// [`CoCreateGuid`](crate::CoCreateGuid) or
+/// [`GUID::new_v4`](crate::GUID::new_v4); to create a name-based `GUID`, use
+/// [`GUID::new_v5`](crate::GUID::new_v5).
+///
+/// Implements [`FromStr`](std::str::FromStr) and
+/// [`TryFrom<&str>`](std::convert::TryFrom), which accept the braced registry
+/// form `{43826d1e-e718-42ee-bc55-a1e261c37bfe}`, the bare form
+/// `43826d1e-e718-42ee-bc55-a1e261c37bfe` and the 32 hex digits form
+/// `43826d1ee71842eebc55a1e261c37bfe`, failing with
+/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA) if the string
+/// is malformed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let g: w::GUID = "{43826D1E-E718-42EE-BC55-A1E261C37BFE}".parse()?;
+/// let g = w::GUID::try_from("43826d1ee71842eebc55a1e261c37bfe")?;
+/// # w::SysResult::Ok(())
+/// ```
 #[repr(C)]
 #[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GUID {
@@ -28,13 +52,59 @@ impl std::fmt::Debug for GUID {
 	}
 }
 
+impl std::str::FromStr for GUID {
+	type Err = co::ERROR;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::try_from(s)
+	}
+}
+
+impl TryFrom<&str> for GUID {
+	type Error = co::ERROR;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let bare = match value.strip_prefix('{') {
+			Some(rest) => rest.strip_suffix('}').ok_or(co::ERROR::INVALID_DATA)?,
+			None => value,
+		};
+
+		let hex = match bare.len() {
+			32 => bare.to_owned(),
+			36 => {
+				let chs = bare.as_bytes();
+				if [8, 13, 18, 23].iter().any(|idx| chs[*idx] != b'-') {
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				bare.replace('-', "")
+			},
+			_ => return Err(co::ERROR::INVALID_DATA),
+		};
+
+		if hex.len() != 32 || !hex.bytes().all(|ch| ch.is_ascii_hexdigit()) {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let num = u128::from_str_radix(&hex, 16)
+			.map_err(|_| co::ERROR::INVALID_DATA)?;
+
+		Ok(Self {
+			data1: (num >> 96) as _,
+			data2: (num >> 80) as _,
+			data3: (num >> 64) as _,
+			data4: (num as u64).swap_bytes(),
+		})
+	}
+}
+
 impl GUID {
 	/// Creates a new `GUID` from a representative hex string, which can be
 	/// copied straight from standard `GUID` declarations.
 	///
 	/// # Panics
 	///
-	/// Panics if the string has an invalid format.
+	/// Panics if the string has an invalid format. To parse strings which are
+	/// not known at compile time, use [`FromStr`](std::str::FromStr) or
+	/// [`TryFrom<&str>`](std::convert::TryFrom) instead.
 	///
 	/// # Examples
 	///
@@ -66,6 +136,228 @@ impl GUID {
 		}
 	}
 
+	/// Creates a new random `GUID`, as defined by
+	/// [RFC 4122](https://www.rfc-editor.org/rfc/rfc4122#section-4.4) version
+	/// 4.
+	///
+	/// The random bits come from
+	/// [`RtlGenRandom`](https://learn.microsoft.com/en-us/windows/win32/api/ntsecapi/nf-ntsecapi-rtlgenrandom),
+	/// the system cryptographically secure random number generator.
+	///
+	/// # Panics
+	///
+	/// Panics if the system random number generator fails.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let g = w::GUID::new_v4();
+	/// assert_eq!(g.version(), 4);
+	/// ```
+	#[must_use]
+	pub fn new_v4() -> Self {
+		let mut bytes = [0u8; 16];
+		if unsafe { ffi::SystemFunction036(bytes.as_mut_ptr() as _, bytes.len() as _) } == 0 {
+			panic!("RtlGenRandom failed.");
+		}
+		Self::from_bytes_be_with_version(bytes, 4)
+	}
+
+	/// Creates a new name-based `GUID`, as defined by
+	/// [RFC 4122](https://www.rfc-editor.org/rfc/rfc4122#section-4.3) version
+	/// 5, which uses SHA-1 hashing. The same namespace and name always produce
+	/// the same `GUID`.
+	///
+	/// The predefined namespaces are available in
+	/// [`co::NAMESPACE`](crate::co::NAMESPACE), but any `GUID` can be used.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let g = w::GUID::new_v5(co::NAMESPACE::DNS.as_ref(), b"www.example.com");
+	/// assert_eq!(g.to_string(), "2ed6657d-e927-568b-95e1-2665a8aea6a2");
+	/// ```
+	#[must_use]
+	pub fn new_v5(namespace: &GUID, name: &[u8]) -> Self {
+		let mut data = Vec::with_capacity(16 + name.len());
+		data.extend_from_slice(&namespace.to_bytes_be());
+		data.extend_from_slice(name);
+
+		let mut bytes = [0u8; 16];
+		bytes.copy_from_slice(&Self::sha1(&data)[..16]);
+		Self::from_bytes_be_with_version(bytes, 5)
+	}
+
+	/// Returns the version of the `GUID`, which is stored in the 4 most
+	/// significant bits of `Data3`. This value is meaningful only if
+	/// [`GUID::variant`](crate::GUID::variant) is
+	/// [`GuidVariant::Rfc4122`](crate::GuidVariant::Rfc4122).
+	///
+	/// For example, random `GUID`s have version 4, and SHA-1 name-based ones
+	/// have version 5.
+	#[must_use]
+	pub const fn version(&self) -> u8 {
+		(self.data3 >> 12) as _
+	}
+
+	/// Returns the variant of the `GUID`, which is stored in the most
+	/// significant bits of the first byte of `Data4`.
+	#[must_use]
+	pub const fn variant(&self) -> GuidVariant {
+		let b = self.data4()[0];
+		if b & 0b1000_0000 == 0 {
+			GuidVariant::Ncs
+		} else if b & 0b0100_0000 == 0 {
+			GuidVariant::Rfc4122
+		} else if b & 0b0010_0000 == 0 {
+			GuidVariant::Microsoft
+		} else {
+			GuidVariant::Future
+		}
+	}
+
+	/// Creates a new `GUID` from its 16 bytes, in the same mixed-endian layout
+	/// the struct has in memory: `data1`, `data2` and `data3` as little-endian,
+	/// followed by the 8 bytes of `data4`.
+	///
+	/// This is the layout used when a `GUID` is stored in binary blobs, like
+	/// registry values.
+	#[must_use]
+	pub const fn from_bytes_le(bytes: &[u8; 16]) -> Self {
+		Self {
+			data1: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+			data2: u16::from_le_bytes([bytes[4], bytes[5]]),
+			data3: u16::from_le_bytes([bytes[6], bytes[7]]),
+			data4: u64::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11],
+				bytes[12], bytes[13], bytes[14], bytes[15]]),
+		}
+	}
+
+	/// Returns the 16 bytes of the `GUID`, in the same mixed-endian layout the
+	/// struct has in memory. This is the inverse of
+	/// [`GUID::from_bytes_le`](crate::GUID::from_bytes_le).
+	#[must_use]
+	pub const fn to_bytes_le(&self) -> [u8; 16] {
+		let d1 = self.data1.to_le_bytes();
+		let d2 = self.data2.to_le_bytes();
+		let d3 = self.data3.to_le_bytes();
+		let d4 = self.data4();
+		[d1[0], d1[1], d1[2], d1[3], d2[0], d2[1], d3[0], d3[1],
+			d4[0], d4[1], d4[2], d4[3], d4[4], d4[5], d4[6], d4[7]]
+	}
+
+	/// Returns the `Data1` field, which corresponds to the first group of 8 hex
+	/// digits.
+	#[must_use]
+	pub const fn data1(&self) -> u32 {
+		self.data1
+	}
+
+	/// Returns the `Data2` field, which corresponds to the second group of 4
+	/// hex digits.
+	#[must_use]
+	pub const fn data2(&self) -> u16 {
+		self.data2
+	}
+
+	/// Returns the `Data3` field, which corresponds to the third group of 4 hex
+	/// digits.
+	#[must_use]
+	pub const fn data3(&self) -> u16 {
+		self.data3
+	}
+
+	/// Returns the `Data4` field, whose bytes correspond to the last 4 and 12
+	/// hex digits groups.
+	#[must_use]
+	pub const fn data4(&self) -> [u8; 8] {
+		self.data4.to_le_bytes()
+	}
+
+	/// Returns the 16 bytes in network byte order, as used by RFC 4122.
+	#[must_use]
+	const fn to_bytes_be(self) -> [u8; 16] {
+		let d1 = self.data1.to_be_bytes();
+		let d2 = self.data2.to_be_bytes();
+		let d3 = self.data3.to_be_bytes();
+		let d4 = self.data4();
+		[d1[0], d1[1], d1[2], d1[3], d2[0], d2[1], d3[0], d3[1],
+			d4[0], d4[1], d4[2], d4[3], d4[4], d4[5], d4[6], d4[7]]
+	}
+
+	/// Creates a `GUID` from bytes in network byte order, stamping the given
+	/// version and the RFC 4122 variant.
+	#[must_use]
+	fn from_bytes_be_with_version(mut bytes: [u8; 16], version: u8) -> Self {
+		bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+		bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+		let mut le = bytes;
+		le[..4].reverse();
+		le[4..6].reverse();
+		le[6..8].reverse();
+		Self::from_bytes_le(&le)
+	}
+
+	/// Computes the [SHA-1](https://en.wikipedia.org/wiki/SHA-1) digest.
+	#[must_use]
+	fn sha1(data: &[u8]) -> [u8; 20] {
+		let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+
+		let mut msg = data.to_vec();
+		msg.push(0x80);
+		while msg.len() % 64 != 56 {
+			msg.push(0x00);
+		}
+		msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+		for block in msg.chunks(64) {
+			let mut w = [0u32; 80];
+			for (i, word) in block.chunks(4).enumerate() {
+				w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+			}
+			for i in 16..80 {
+				w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+			}
+
+			let [mut a, mut b, mut c, mut d, mut e] = h;
+			for (i, wi) in w.iter().enumerate() {
+				let (f, k) = match i {
+					0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+					20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+					40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+					_ => (b ^ c ^ d, 0xca62_c1d6),
+				};
+				let temp = a.rotate_left(5)
+					.wrapping_add(f)
+					.wrapping_add(e)
+					.wrapping_add(k)
+					.wrapping_add(*wi);
+				e = d;
+				d = c;
+				c = b.rotate_left(30);
+				b = a;
+				a = temp;
+			}
+
+			h[0] = h[0].wrapping_add(a);
+			h[1] = h[1].wrapping_add(b);
+			h[2] = h[2].wrapping_add(c);
+			h[3] = h[3].wrapping_add(d);
+			h[4] = h[4].wrapping_add(e);
+		}
+
+		let mut digest = [0u8; 20];
+		for (i, hi) in h.iter().enumerate() {
+			digest[i * 4..i * 4 + 4].copy_from_slice(&hi.to_be_bytes());
+		}
+		digest
+	}
+
 	#[must_use]
 	const fn parse_block<const N: usize>(chars: [u8; N]) -> u64 {
 		let mut res: u64 = 0;
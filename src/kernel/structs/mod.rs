@@ -1,7 +1,9 @@
 mod dev_broadcast;
 mod guid;
 mod structs_other;
+mod time;
 
 pub use dev_broadcast::*;
 pub use guid::GUID;
 pub use structs_other::*;
+pub use time::{FILETIME, SYSTEMTIME};
@@ -205,18 +205,6 @@ pub struct DISK_SPACE_INFORMATION {
 	pub BytesPerSector: u32,
 }
 
-/// [`FILETIME`](https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-filetime)
-/// struct.
-///
-/// Can be converted to [`SYSTEMTIME`](crate::SYSTEMTIME) with
-/// [`FileTimeToSystemTime`](crate::FileTimeToSystemTime) function.
-#[repr(C)]
-#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct FILETIME {
-	pub dwLowDateTime: u32,
-	pub dwHighDateTime: u32,
-}
-
 /// [`HEAPLIST32`](https://learn.microsoft.com/en-us/windows/win32/api/tlhelp32/ns-tlhelp32-heaplist32)
 /// struct.
 #[repr(C)]
@@ -647,6 +635,15 @@ pub struct PROCESSOR_NUMBER {
 	Reserved: u8,
 }
 
+/// [`SC_ACTION`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-sc_action)
+/// struct.
+#[repr(C)]
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct SC_ACTION {
+	pub Type: co::SC_ACTION_TYPE,
+	pub Delay: u32,
+}
+
 /// [`SECURITY_ATTRIBUTES`](https://learn.microsoft.com/en-us/previous-versions/windows/desktop/legacy/aa379560(v=vs.85))
 /// struct.
 #[repr(C)]
@@ -737,6 +734,22 @@ pub struct SERVICE_STATUS {
 	pub dwWaitPoint: u32,
 }
 
+/// [`SERVICE_STATUS_PROCESS`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_status_process)
+/// struct.
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+pub struct SERVICE_STATUS_PROCESS {
+	pub dwServiceType: co::SERVICE_TYPE,
+	pub dwCurrentState: co::SERVICE_STATE,
+	pub dwControlsAccepted: co::SERVICE_ACCEPT,
+	pub dwWin32ExitCode: u32,
+	pub dwServiceSpecificExitCode: u32,
+	pub dwCheckPoint: u32,
+	pub dwWaitHint: u32,
+	pub dwProcessId: u32,
+	pub dwServiceFlags: u32,
+}
+
 /// [`SID`](https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-sid)
 /// struct.
 ///
@@ -752,6 +765,9 @@ pub struct SERVICE_STATUS {
 /// * [`CreateWellKnownSid`](crate::CreateWellKnownSid) as [`SidGuard`](crate::guard::SidGuard);
 /// * [`GetWindowsAccountDomainSid`](crate::GetWindowsAccountDomainSid) as [`SidGuard`](crate::guard::SidGuard);
 /// * [`LookupAccountName`](crate::LookupAccountName) as [`SidGuard`](crate::guard::SidGuard).
+///
+/// To keep an owned copy which can be stored, hashed and sorted, convert it
+/// into a [`Sid`](crate::Sid).
 #[repr(C)]
 pub struct SID {
 	pub Revision: u8,
@@ -764,16 +780,15 @@ impl VariableSized for SID {}
 
 impl std::fmt::Display for SID {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match ConvertSidToStringSid(self) {
-			Ok(name) => write!(f, "{}", name),
-			Err(err) => write!(f, "{}", err),
-		}
+		std::fmt::Display::fmt(&Sid::from(self), f) // delegate to the owned Sid
 	}
 }
 
 impl PartialEq<SID> for SID {
 	fn eq(&self, other: &SID) -> bool {
-		EqualSid(self, other).unwrap() // assumes valid references
+		self.Revision == other.Revision
+			&& self.IdentifierAuthority == other.IdentifierAuthority
+			&& self.SubAuthority() == other.SubAuthority()
 	}
 }
 
@@ -831,7 +846,7 @@ impl<'a> SID_AND_ATTRIBUTES_HASH<'a> {
 /// [`SID_IDENTIFIER_AUTHORITY`](https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-sid_identifier_authority)
 /// struct.
 #[repr(C)]
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SID_IDENTIFIER_AUTHORITY {
 	pub Value: [u8; 6],
 }
@@ -929,24 +944,6 @@ pub struct SYSTEM_INFO {
 
 impl_default!(SYSTEM_INFO);
 
-/// [`SYSTEMTIME`](https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-systemtime)
-/// struct.
-///
-/// Can be converted to [`FILETIME`](crate::FILETIME) with
-/// [`SystemTimeToFileTime`](crate::SystemTimeToFileTime) function.
-#[repr(C)]
-#[derive(Default, Clone, PartialEq, Eq)]
-pub struct SYSTEMTIME {
-	pub wYear: u16,
-	pub wMonth: u16,
-	pub wDayOfWeek: u16,
-	pub wDay: u16,
-	pub wHour: u16,
-	pub wMinute: u16,
-	pub wSecond: u16,
-	pub wMilliseconds: u16,
-}
-
 /// [`THREADENTRY32`](https://learn.microsoft.com/en-us/windows/win32/api/tlhelp32/ns-tlhelp32-threadentry32)
 /// struct.
 #[repr(C)]
@@ -0,0 +1,522 @@
+#![allow(non_snake_case)]
+
+use std::cmp::Ordering;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::co;
+use crate::decl::*;
+
+/// Number of 100-nanosecond intervals in a second.
+const TICKS_PER_SEC: u64 = 10_000_000;
+
+/// Number of 100-nanosecond intervals in a millisecond.
+const TICKS_PER_MS: u64 = 10_000;
+
+/// Number of days between 1601-01-01 and 1970-01-01.
+const DAYS_1601_TO_1970: i64 = 134_774;
+
+/// Range of years supported by [`SYSTEMTIME`](crate::SYSTEMTIME).
+const MIN_YEAR: u16 = 1601;
+const MAX_YEAR: u16 = 30827;
+
+/// [`FILETIME`](https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-filetime)
+/// struct.
+///
+/// Stores the number of 100-nanosecond intervals – the ticks – since January
+/// 1, 1601 (UTC). The ordering compares these ticks.
+///
+/// Can be converted to [`SYSTEMTIME`](crate::SYSTEMTIME) with
+/// [`FileTimeToSystemTime`](crate::FileTimeToSystemTime) function, or with
+/// [`FILETIME::to_systemtime`](crate::FILETIME::to_systemtime), which doesn't
+/// call any native function.
+///
+/// Can also be converted to and from
+/// [`std::time::SystemTime`](std::time::SystemTime) with
+/// [`TryFrom`](std::convert::TryFrom), failing with
+/// [`co::ERROR::ARITHMETIC_OVERFLOW`](crate::co::ERROR::ARITHMETIC_OVERFLOW)
+/// if the value is out of range.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let ft = w::FILETIME::try_from(std::time::SystemTime::now())?;
+/// let st = ft.to_systemtime()?;
+/// println!("{}", st.to_iso8601());
+///
+/// let back = std::time::SystemTime::try_from(ft)?;
+/// # w::SysResult::Ok(())
+/// ```
+#[repr(C)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FILETIME {
+	pub dwLowDateTime: u32,
+	pub dwHighDateTime: u32,
+}
+
+impl PartialOrd for FILETIME {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for FILETIME {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.ticks().cmp(&other.ticks())
+	}
+}
+
+impl TryFrom<SystemTime> for FILETIME {
+	type Error = co::ERROR;
+
+	fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+		let ticks = match value.duration_since(UNIX_EPOCH) {
+			Ok(after) => u64::try_from(after.as_nanos() / 100).ok()
+				.and_then(|t| t.checked_add(FILETIME::UNIX_EPOCH.ticks())),
+			Err(e) => u64::try_from(e.duration().as_nanos().div_ceil(100)).ok() // round down
+				.and_then(|t| FILETIME::UNIX_EPOCH.ticks().checked_sub(t)),
+		};
+		ticks.map(Self::from_ticks)
+			.ok_or(co::ERROR::ARITHMETIC_OVERFLOW)
+	}
+}
+
+impl TryFrom<FILETIME> for SystemTime {
+	type Error = co::ERROR;
+
+	fn try_from(value: FILETIME) -> Result<Self, Self::Error> {
+		let ticks = value.ticks();
+		let epoch = FILETIME::UNIX_EPOCH.ticks();
+		if ticks >= epoch {
+			UNIX_EPOCH.checked_add(FILETIME::ticks_to_duration(ticks - epoch))
+		} else {
+			UNIX_EPOCH.checked_sub(FILETIME::ticks_to_duration(epoch - ticks))
+		}.ok_or(co::ERROR::ARITHMETIC_OVERFLOW)
+	}
+}
+
+impl FILETIME {
+	/// The Unix epoch, January 1, 1970 (UTC).
+	pub const UNIX_EPOCH: Self = Self::from_ticks(DAYS_1601_TO_1970 as u64 * 86_400 * TICKS_PER_SEC);
+
+	/// Creates a new `FILETIME` from the number of 100-nanosecond intervals
+	/// since January 1, 1601 (UTC).
+	#[must_use]
+	pub const fn from_ticks(ticks: u64) -> Self {
+		Self {
+			dwLowDateTime: ticks as _,
+			dwHighDateTime: (ticks >> 32) as _,
+		}
+	}
+
+	/// Returns the number of 100-nanosecond intervals since January 1, 1601
+	/// (UTC).
+	#[must_use]
+	pub const fn ticks(&self) -> u64 {
+		((self.dwHighDateTime as u64) << 32) | self.dwLowDateTime as u64
+	}
+
+	/// Creates a new `FILETIME` from a
+	/// [Unix timestamp](https://en.wikipedia.org/wiki/Unix_time), which is the
+	/// number of seconds since January 1, 1970 (UTC), possibly negative.
+	///
+	/// Fails with
+	/// [`co::ERROR::ARITHMETIC_OVERFLOW`](crate::co::ERROR::ARITHMETIC_OVERFLOW)
+	/// if the time is before January 1, 1601.
+	pub fn from_unix_timestamp(secs: i64) -> SysResult<Self> {
+		(secs as i128)
+			.checked_mul(TICKS_PER_SEC as _)
+			.and_then(|t| t.checked_add(Self::UNIX_EPOCH.ticks() as _))
+			.and_then(|t| u64::try_from(t).ok())
+			.map(Self::from_ticks)
+			.ok_or(co::ERROR::ARITHMETIC_OVERFLOW)
+	}
+
+	/// Returns the
+	/// [Unix timestamp](https://en.wikipedia.org/wiki/Unix_time), which is the
+	/// number of seconds since January 1, 1970 (UTC). Fractions of second are
+	/// rounded down.
+	#[must_use]
+	pub const fn unix_timestamp(&self) -> i64 {
+		(self.ticks() as i128 - Self::UNIX_EPOCH.ticks() as i128)
+			.div_euclid(TICKS_PER_SEC as _) as _
+	}
+
+	/// Returns a new `FILETIME` moved forward by the given duration, or `None`
+	/// on overflow. The duration is truncated to 100-nanosecond intervals.
+	#[must_use]
+	pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+		Self::duration_to_ticks(duration)
+			.and_then(|t| self.ticks().checked_add(t))
+			.map(Self::from_ticks)
+	}
+
+	/// Returns a new `FILETIME` moved backwards by the given duration, or
+	/// `None` on overflow. The duration is truncated to 100-nanosecond
+	/// intervals.
+	#[must_use]
+	pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+		Self::duration_to_ticks(duration)
+			.and_then(|t| self.ticks().checked_sub(t))
+			.map(Self::from_ticks)
+	}
+
+	/// Returns the amount of time elapsed from `earlier` to `self`, or `None`
+	/// if `earlier` is later than `self`.
+	#[must_use]
+	pub fn duration_since(&self, earlier: &FILETIME) -> Option<Duration> {
+		self.ticks().checked_sub(earlier.ticks())
+			.map(Self::ticks_to_duration)
+	}
+
+	/// Converts the `FILETIME` into a [`SYSTEMTIME`](crate::SYSTEMTIME) in
+	/// pure Rust, producing the same result of
+	/// [`FileTimeToSystemTime`](crate::FileTimeToSystemTime).
+	///
+	/// Fails with [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if the year would be after 30827.
+	pub fn to_systemtime(&self) -> SysResult<SYSTEMTIME> {
+		let ticks = self.ticks();
+		let days = ticks / (86_400 * TICKS_PER_SEC);
+		let day_ticks = ticks % (86_400 * TICKS_PER_SEC);
+
+		let days_1970 = days as i64 - DAYS_1601_TO_1970;
+		let (year, month, day) = civil_from_days(days_1970);
+		if year > MAX_YEAR as i64 {
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		Ok(SYSTEMTIME {
+			wYear: year as _,
+			wMonth: month as _,
+			wDayOfWeek: day_of_week(days_1970),
+			wDay: day as _,
+			wHour: (day_ticks / (3_600 * TICKS_PER_SEC)) as _,
+			wMinute: (day_ticks / (60 * TICKS_PER_SEC) % 60) as _,
+			wSecond: (day_ticks / TICKS_PER_SEC % 60) as _,
+			wMilliseconds: (day_ticks / TICKS_PER_MS % 1_000) as _,
+		})
+	}
+
+	#[must_use]
+	const fn ticks_to_duration(ticks: u64) -> Duration {
+		Duration::new(ticks / TICKS_PER_SEC, (ticks % TICKS_PER_SEC * 100) as _)
+	}
+
+	#[must_use]
+	fn duration_to_ticks(duration: Duration) -> Option<u64> {
+		u64::try_from(duration.as_nanos() / 100).ok()
+	}
+}
+
+/// [`SYSTEMTIME`](https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-systemtime)
+/// struct.
+///
+/// Can be converted to [`FILETIME`](crate::FILETIME) with
+/// [`SystemTimeToFileTime`](crate::SystemTimeToFileTime) function, or with
+/// [`SYSTEMTIME::to_filetime`](crate::SYSTEMTIME::to_filetime), which doesn't
+/// call any native function.
+///
+/// Note that a `SYSTEMTIME` doesn't carry any time zone information: the
+/// conversions to and from [`std::time::SystemTime`](std::time::SystemTime)
+/// assume it's in UTC.
+///
+/// # Examples
+///
+/// Parsing and formatting an
+/// [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) date:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let st = w::SYSTEMTIME::from_iso8601("2024-02-29T13:45:10.250-03:00")?;
+/// assert_eq!(st.to_iso8601(), "2024-02-29T16:45:10.250"); // converted to UTC
+/// assert_eq!(st.day_of_week(), 4); // Thursday
+///
+/// let next_day = st.checked_add(std::time::Duration::from_secs(86_400)).unwrap();
+/// assert_eq!(next_day.wMonth, 3);
+/// # w::SysResult::Ok(())
+/// ```
+#[repr(C)]
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct SYSTEMTIME {
+	pub wYear: u16,
+	pub wMonth: u16,
+	pub wDayOfWeek: u16,
+	pub wDay: u16,
+	pub wHour: u16,
+	pub wMinute: u16,
+	pub wSecond: u16,
+	pub wMilliseconds: u16,
+}
+
+impl TryFrom<SystemTime> for SYSTEMTIME {
+	type Error = co::ERROR;
+
+	fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+		FILETIME::try_from(value)?.to_systemtime()
+	}
+}
+
+impl TryFrom<&SYSTEMTIME> for SystemTime {
+	type Error = co::ERROR;
+
+	fn try_from(value: &SYSTEMTIME) -> Result<Self, Self::Error> {
+		SystemTime::try_from(value.to_filetime()?)
+	}
+}
+
+impl SYSTEMTIME {
+	/// Tells whether all the fields, except `wDayOfWeek`, hold a valid date and
+	/// time between years 1601 and 30827, as accepted by
+	/// [`SystemTimeToFileTime`](crate::SystemTimeToFileTime).
+	#[must_use]
+	pub const fn is_valid(&self) -> bool {
+		self.wYear >= MIN_YEAR && self.wYear <= MAX_YEAR
+			&& self.wMonth >= 1 && self.wMonth <= 12
+			&& self.wDay >= 1 && self.wDay <= days_in_month(self.wYear, self.wMonth)
+			&& self.wHour < 24
+			&& self.wMinute < 60
+			&& self.wSecond < 60
+			&& self.wMilliseconds < 1_000
+	}
+
+	/// Computes the day of the week from the date fields, ignoring the
+	/// `wDayOfWeek` field itself. Sunday is 0, Monday is 1, and so on.
+	///
+	/// The date is assumed to be valid; see
+	/// [`SYSTEMTIME::is_valid`](crate::SYSTEMTIME::is_valid).
+	#[must_use]
+	pub const fn day_of_week(&self) -> u16 {
+		day_of_week(days_from_civil(self.wYear as _, self.wMonth as _, self.wDay as _))
+	}
+
+	/// Converts the `SYSTEMTIME` into a [`FILETIME`](crate::FILETIME) in
+	/// pure Rust, producing the same result of
+	/// [`SystemTimeToFileTime`](crate::SystemTimeToFileTime). The `wDayOfWeek`
+	/// field is ignored.
+	///
+	/// Fails with [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if the date is not valid.
+	pub fn to_filetime(&self) -> SysResult<FILETIME> {
+		if !self.is_valid() {
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		let days = days_from_civil(self.wYear as _, self.wMonth as _, self.wDay as _)
+			+ DAYS_1601_TO_1970;
+		let secs = days as u64 * 86_400
+			+ self.wHour as u64 * 3_600
+			+ self.wMinute as u64 * 60
+			+ self.wSecond as u64;
+		Ok(FILETIME::from_ticks(
+			secs * TICKS_PER_SEC + self.wMilliseconds as u64 * TICKS_PER_MS,
+		))
+	}
+
+	/// Returns a new `SYSTEMTIME` moved forward by the given duration, or
+	/// `None` if the date is not valid or the result is out of range. The
+	/// result is truncated to milliseconds.
+	#[must_use]
+	pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+		self.to_filetime().ok()
+			.and_then(|ft| ft.checked_add(duration))
+			.and_then(|ft| ft.to_systemtime().ok())
+	}
+
+	/// Returns a new `SYSTEMTIME` moved backwards by the given duration, or
+	/// `None` if the date is not valid or the result is out of range. The
+	/// result is truncated to milliseconds.
+	#[must_use]
+	pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+		self.to_filetime().ok()
+			.and_then(|ft| ft.checked_sub(duration))
+			.and_then(|ft| ft.to_systemtime().ok())
+	}
+
+	/// Returns the amount of time elapsed from `earlier` to `self`, or `None`
+	/// if any of the dates is not valid, or if `earlier` is later than `self`.
+	#[must_use]
+	pub fn duration_since(&self, earlier: &SYSTEMTIME) -> Option<Duration> {
+		let (ft_self, ft_earlier) = (self.to_filetime().ok()?, earlier.to_filetime().ok()?);
+		ft_self.duration_since(&ft_earlier)
+	}
+
+	/// Formats the date and time as
+	/// [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601), in the
+	/// `YYYY-MM-DDThh:mm:ss.sss` format. No time zone designator is appended.
+	#[must_use]
+	pub fn to_iso8601(&self) -> String {
+		format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}",
+			self.wYear, self.wMonth, self.wDay,
+			self.wHour, self.wMinute, self.wSecond, self.wMilliseconds)
+	}
+
+	/// Parses an [ISO 8601](https://en.wikipedia.org/wiki/ISO_8601) date, in
+	/// the extended format, like `2024-02-29`, `2024-02-29T13:45`,
+	/// `2024-02-29T13:45:10.25Z` or `2024-02-29 13:45:10+05:30`. Fractions of
+	/// second are truncated to milliseconds.
+	///
+	/// If the string has a time zone offset, the result is converted to UTC;
+	/// otherwise, the time is returned as it is. The `wDayOfWeek` field is
+	/// computed.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the string is malformed or the date is not valid.
+	pub fn from_iso8601(s: &str) -> SysResult<Self> {
+		let mut p = IsoParser { s: s.as_bytes(), pos: 0 };
+		let year = p.number(4)?;
+		p.expect(b'-')?;
+		let month = p.number(2)?;
+		p.expect(b'-')?;
+		let day = p.number(2)?;
+
+		let mut st = SYSTEMTIME {
+			wYear: year,
+			wMonth: month,
+			wDay: day,
+			..Default::default()
+		};
+
+		let mut offset_mins = 0i64;
+		if p.accept(b'T') || p.accept(b't') || p.accept(b' ') {
+			st.wHour = p.number(2)?;
+			p.expect(b':')?;
+			st.wMinute = p.number(2)?;
+
+			if p.accept(b':') {
+				st.wSecond = p.number(2)?;
+				if p.accept(b'.') || p.accept(b',') {
+					let frac = p.digits()?;
+					st.wMilliseconds = frac.iter()
+						.chain([b'0'; 3].iter())
+						.take(3)
+						.fold(0, |acc, ch| acc * 10 + (ch - b'0') as u16);
+				}
+			}
+
+			if p.accept(b'Z') || p.accept(b'z') {
+				// UTC, nothing to do
+			} else if let Some(sign) = p.accept_sign() {
+				let hours = p.number(2)? as i64;
+				p.accept(b':');
+				let mins = p.number(2)? as i64;
+				if hours > 23 || mins > 59 {
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				offset_mins = sign * (hours * 60 + mins);
+			}
+		}
+
+		if p.pos != p.s.len() || !st.is_valid() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let offset = Duration::from_secs(offset_mins.unsigned_abs() * 60);
+		let utc = if offset_mins >= 0 {
+			st.checked_sub(offset) // east of UTC, go back
+		} else {
+			st.checked_add(offset)
+		};
+		utc.ok_or(co::ERROR::INVALID_DATA)
+	}
+}
+
+/// Simple cursor over the bytes of an ISO 8601 string.
+struct IsoParser<'a> {
+	s: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> IsoParser<'a> {
+	fn accept(&mut self, ch: u8) -> bool {
+		if self.s.get(self.pos) == Some(&ch) {
+			self.pos += 1;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn accept_sign(&mut self) -> Option<i64> {
+		if self.accept(b'+') {
+			Some(1)
+		} else if self.accept(b'-') {
+			Some(-1)
+		} else {
+			None
+		}
+	}
+
+	fn expect(&mut self, ch: u8) -> SysResult<()> {
+		if self.accept(ch) { Ok(()) } else { Err(co::ERROR::INVALID_DATA) }
+	}
+
+	fn number(&mut self, len: usize) -> SysResult<u16> {
+		let digits = self.s.get(self.pos..self.pos + len)
+			.filter(|digits| digits.iter().all(|ch| ch.is_ascii_digit()))
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		self.pos += len;
+		Ok(digits.iter().fold(0, |acc, ch| acc * 10 + (ch - b'0') as u16))
+	}
+
+	fn digits(&mut self) -> SysResult<&'a [u8]> {
+		let len = self.s[self.pos..].iter()
+			.take_while(|ch| ch.is_ascii_digit())
+			.count();
+		if len == 0 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		self.pos += len;
+		Ok(&self.s[self.pos - len..self.pos])
+	}
+}
+
+#[must_use]
+const fn is_leap_year(year: u16) -> bool {
+	(year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+#[must_use]
+const fn days_in_month(year: u16, month: u16) -> u16 {
+	match month {
+		2 => if is_leap_year(year) { 29 } else { 28 },
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+/// Sunday is 0, given the number of days since 1970-01-01, a Thursday.
+#[must_use]
+const fn day_of_week(days_1970: i64) -> u16 {
+	(days_1970 + 4).rem_euclid(7) as _
+}
+
+/// Number of days since 1970-01-01 of the given date in the proleptic
+/// Gregorian calendar. See
+/// [Howard Hinnant's algorithms](https://howardhinnant.github.io/date_algorithms.html).
+#[must_use]
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+	let y = if month <= 2 { year - 1 } else { year };
+	let era = y.div_euclid(400);
+	let yoe = y - era * 400;
+	let mp = (month + 9) % 12; // March is 0
+	let doy = (153 * mp + 2) / 5 + day - 1;
+	let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+	era * 146_097 + doe - 719_468
+}
+
+/// Inverse of [`days_from_civil`], returns year, month and day.
+#[must_use]
+const fn civil_from_days(days_1970: i64) -> (i64, i64, i64) {
+	let z = days_1970 + 719_468;
+	let era = z.div_euclid(146_097);
+	let doe = z - era * 146_097;
+	let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+	(year, month, day)
+}
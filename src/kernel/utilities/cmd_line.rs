@@ -0,0 +1,216 @@
+//! Command line utilities.
+//!
+//! The functions split and build command lines following the same rules of
+//! [`CommandLineToArgvW`](https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-commandlinetoargvw)
+//! and the Microsoft C runtime, without the need of the `shell` feature.
+
+use crate::co;
+use crate::decl::*;
+
+/// Splits a command line into its arguments, with the same rules of
+/// [`CommandLineToArgvW`](https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-commandlinetoargvw):
+///
+/// * the first argument, the program name, ends at the next space or tab; if
+///   it starts with a double quote, it ends at the next double quote,
+///   regardless of backslashes;
+/// * the other arguments are separated by spaces and tabs, which are kept
+///   when within double quotes;
+/// * `2n` backslashes followed by a double quote produce `n` backslashes, and
+///   the double quote toggles the quoted mode;
+/// * `2n + 1` backslashes followed by a double quote produce `n` backslashes
+///   and a literal double quote;
+/// * backslashes not followed by a double quote are literal;
+/// * within double quotes, three consecutive double quotes produce a literal
+///   double quote.
+///
+/// Unlike `CommandLineToArgvW`, an empty command line returns no arguments,
+/// instead of the path of the current executable.
+///
+/// This is the inverse of [`cmd_line::join`](crate::cmd_line::join).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let args = w::cmd_line::split(r#"C:\Temp\app.exe "C:\Program Files\\" a\\\"b"#);
+/// assert_eq!(args, [r"C:\Temp\app.exe", r"C:\Program Files\", r#"a\"b"#]);
+///
+/// let my_args = w::cmd_line::split(&w::GetCommandLine());
+/// ```
+#[must_use]
+pub fn split(cmd_line: &str) -> Vec<String> {
+	let mut args = Vec::<String>::default();
+	let mut chars = cmd_line.chars().peekable();
+
+	let mut program = String::default();
+	if chars.next_if_eq(&'"').is_some() {
+		for ch in chars.by_ref() {
+			if ch == '"' {
+				break;
+			}
+			program.push(ch);
+		}
+	} else {
+		while let Some(ch) = chars.next_if(|ch| !is_blank(*ch)) {
+			program.push(ch);
+		}
+	}
+	if program.is_empty() && cmd_line.is_empty() {
+		return args;
+	}
+	args.push(program);
+
+	loop {
+		while chars.next_if(|ch| is_blank(*ch)).is_some() {} // skip to next argument
+		if chars.peek().is_none() {
+			break;
+		}
+
+		let mut arg = String::default();
+		let mut quote_count = 0; // odd means within quotes
+		let mut backslash_count = 0;
+
+		while let Some(ch) = chars.next() {
+			match ch {
+				' ' | '\t' if quote_count == 0 => break,
+				'\\' => {
+					backslash_count += 1;
+					continue;
+				},
+				'"' => {
+					(0..backslash_count / 2).for_each(|_| arg.push('\\'));
+					if backslash_count % 2 == 0 {
+						quote_count += 1;
+					} else {
+						arg.push('"');
+					}
+					while chars.next_if_eq(&'"').is_some() {
+						quote_count += 1;
+						if quote_count == 3 {
+							arg.push('"');
+							quote_count = 0;
+						}
+					}
+					if quote_count == 2 {
+						quote_count = 0;
+					}
+				},
+				ch => {
+					(0..backslash_count).for_each(|_| arg.push('\\'));
+					arg.push(ch);
+				},
+			}
+			backslash_count = 0;
+		}
+		(0..backslash_count).for_each(|_| arg.push('\\')); // trailing backslashes
+		args.push(arg);
+	}
+
+	args
+}
+
+/// Quotes a single argument, so it's read back verbatim by
+/// [`cmd_line::split`](crate::cmd_line::split) and by programs which use the
+/// Microsoft C runtime.
+///
+/// The argument is returned unchanged if it's not empty and has no spaces,
+/// tabs, line breaks or double quotes. Otherwise it's enclosed in double
+/// quotes, with the double quotes and the backslashes which precede them
+/// escaped.
+///
+/// Note that this is not suitable for the first argument, the program name,
+/// which is quoted by [`cmd_line::join`](crate::cmd_line::join).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// assert_eq!(w::cmd_line::quote("foo"), "foo");
+/// assert_eq!(w::cmd_line::quote(r"C:\Program Files\"), r#""C:\Program Files\\""#);
+/// assert_eq!(w::cmd_line::quote(r#"say "hi""#), r#""say \"hi\"""#);
+/// ```
+#[must_use]
+pub fn quote(arg: &str) -> String {
+	if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\x0b', '"']) {
+		return arg.to_owned();
+	}
+
+	let mut quoted = String::with_capacity(arg.len() + 2);
+	quoted.push('"');
+	let mut backslash_count = 0;
+	for ch in arg.chars() {
+		match ch {
+			'\\' => backslash_count += 1,
+			'"' => {
+				(0..backslash_count * 2 + 1).for_each(|_| quoted.push('\\'));
+				quoted.push('"');
+				backslash_count = 0;
+			},
+			ch => {
+				(0..backslash_count).for_each(|_| quoted.push('\\'));
+				quoted.push(ch);
+				backslash_count = 0;
+			},
+		}
+	}
+	(0..backslash_count * 2).for_each(|_| quoted.push('\\')); // before the closing quote
+	quoted.push('"');
+	quoted
+}
+
+/// Builds a command line from the program name followed by its arguments,
+/// which can be passed to
+/// [`HPROCESS::CreateProcess`](crate::prelude::kernel_Hprocess::CreateProcess).
+///
+/// The program name is enclosed in double quotes if it has spaces or tabs,
+/// and the other arguments are quoted with
+/// [`cmd_line::quote`](crate::cmd_line::quote).
+///
+/// This is the inverse of [`cmd_line::split`](crate::cmd_line::split).
+///
+/// Fails with
+/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER) if
+/// there are no arguments, or if the program name has a double quote, which
+/// cannot be represented.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let cmd = w::cmd_line::join(&[
+///     r"C:\Program Files\Tool\tool.exe",
+///     "--out",
+///     r"C:\My Output\",
+/// ])?;
+/// assert_eq!(cmd, r#""C:\Program Files\Tool\tool.exe" --out "C:\My Output\\""#);
+/// # w::SysResult::Ok(())
+/// ```
+pub fn join<S>(args: &[S]) -> SysResult<String>
+	where S: AsRef<str>,
+{
+	let (program, rest) = args.split_first()
+		.ok_or(co::ERROR::INVALID_PARAMETER)?;
+	let program = program.as_ref();
+	if program.contains('"') {
+		return Err(co::ERROR::INVALID_PARAMETER);
+	}
+
+	let mut cmd = if program.is_empty() || program.contains([' ', '\t']) {
+		format!("\"{}\"", program)
+	} else {
+		program.to_owned()
+	};
+	for arg in rest.iter() {
+		cmd.push(' ');
+		cmd.push_str(&quote(arg.as_ref()));
+	}
+	Ok(cmd)
+}
+
+#[must_use]
+const fn is_blank(ch: char) -> bool {
+	ch == ' ' || ch == '\t'
+}
@@ -0,0 +1,535 @@
+use std::sync::Mutex;
+
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::prelude::*;
+
+/// Serializes the spawning of processes, so the inheritable handles created
+/// for one child are not inherited by another one spawned at the same time
+/// from a different thread, which would keep its pipes open.
+static SPAWN_LOCK: Mutex<()> = Mutex::new(());
+
+/// How a standard stream of a child process is configured by
+/// [`Command`](crate::Command).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stdio {
+	/// The child uses the same stream of the current process.
+	Inherit,
+	/// A pipe is created, and its other end is available in
+	/// [`Child`](crate::Child).
+	Piped,
+	/// The stream is connected to the `NUL` device.
+	Null,
+}
+
+/// Builder of a child process, which is spawned with
+/// [`HPROCESS::CreateProcess`](crate::prelude::kernel_Hprocess::CreateProcess).
+///
+/// The command line is built with
+/// [`cmd_line::join`](crate::cmd_line::join), and the pipes of the standard
+/// streams are created with
+/// [`HPIPE::CreatePipe`](crate::prelude::kernel_Hpipe::CreatePipe). Only the
+/// child ends of the pipes are inherited, and the ones kept by the current
+/// process are closed when the [`Child`](crate::Child) and its streams are
+/// dropped.
+///
+/// # Examples
+///
+/// Running a tool and capturing its output:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let output = w::Command::new("C:\\Tools\\tool.exe")
+///     .args(["--input", "C:\\My Files\\data.txt"])
+///     .current_dir("C:\\Temp")
+///     .output()?;
+///
+/// println!("Exit code: {}", output.exit_code);
+/// println!("{}", String::from_utf8_lossy(&output.stdout));
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Writing to the standard input of a child:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+/// use std::io::Write;
+///
+/// let mut child = w::Command::new("C:\\Windows\\System32\\sort.exe")
+///     .stdin(w::Stdio::Piped)
+///     .spawn()?;
+///
+/// let mut stdin = child.stdin.take().unwrap();
+/// stdin.write_all(b"banana\r\napple\r\n").map_err(|_| w::co::ERROR::BROKEN_PIPE)?;
+/// drop(stdin); // close the pipe, so the child sees the end of the input
+///
+/// let exit_code = child.wait()?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct Command {
+	program: String,
+	args: Vec<String>,
+	env_clear: bool,
+	env: Vec<(String, Option<String>)>,
+	current_dir: Option<String>,
+	creation_flags: co::CREATE,
+	stdin: Option<Stdio>,
+	stdout: Option<Stdio>,
+	stderr: Option<Stdio>,
+}
+
+impl Command {
+	/// Creates a new builder for the given program, which is also the first
+	/// argument of the command line. If it's not a full path, it's searched
+	/// like `CreateProcess` does with its command line.
+	///
+	/// By default, the child inherits the environment, the current directory
+	/// and the standard streams of the current process.
+	#[must_use]
+	pub fn new(program: &str) -> Self {
+		Self {
+			program: program.to_owned(),
+			args: Vec::default(),
+			env_clear: false,
+			env: Vec::default(),
+			current_dir: None,
+			creation_flags: co::CREATE::default(),
+			stdin: None,
+			stdout: None,
+			stderr: None,
+		}
+	}
+
+	/// Adds an argument, which is quoted with
+	/// [`cmd_line::quote`](crate::cmd_line::quote).
+	pub fn arg(&mut self, arg: &str) -> &mut Self {
+		self.args.push(arg.to_owned());
+		self
+	}
+
+	/// Adds many arguments, which are quoted with
+	/// [`cmd_line::quote`](crate::cmd_line::quote).
+	pub fn args<I, S>(&mut self, args: I) -> &mut Self
+		where I: IntoIterator<Item = S>,
+			S: AsRef<str>,
+	{
+		self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_owned()));
+		self
+	}
+
+	/// Sets an environment variable for the child. Names are
+	/// case-insensitive.
+	pub fn env(&mut self, name: &str, value: &str) -> &mut Self {
+		self.env.push((name.to_owned(), Some(value.to_owned())));
+		self
+	}
+
+	/// Removes an environment variable from the child. Names are
+	/// case-insensitive.
+	pub fn env_remove(&mut self, name: &str) -> &mut Self {
+		self.env.push((name.to_owned(), None));
+		self
+	}
+
+	/// Starts the child with no environment variables, except the ones set with
+	/// [`Command::env`](crate::Command::env).
+	pub fn env_clear(&mut self) -> &mut Self {
+		self.env_clear = true;
+		self.env.clear();
+		self
+	}
+
+	/// Sets the current directory of the child.
+	pub fn current_dir(&mut self, dir: &str) -> &mut Self {
+		self.current_dir = Some(dir.to_owned());
+		self
+	}
+
+	/// Sets the `creation_flags` passed to
+	/// [`HPROCESS::CreateProcess`](crate::prelude::kernel_Hprocess::CreateProcess),
+	/// like [`co::CREATE::NO_WINDOW`](crate::co::CREATE::NO_WINDOW).
+	pub fn creation_flags(&mut self, flags: co::CREATE) -> &mut Self {
+		self.creation_flags = flags;
+		self
+	}
+
+	/// Configures the standard input of the child. Defaults to
+	/// [`Stdio::Inherit`](crate::Stdio::Inherit), except for
+	/// [`Command::output`](crate::Command::output).
+	pub fn stdin(&mut self, cfg: Stdio) -> &mut Self {
+		self.stdin = Some(cfg);
+		self
+	}
+
+	/// Configures the standard output of the child. Defaults to
+	/// [`Stdio::Inherit`](crate::Stdio::Inherit), except for
+	/// [`Command::output`](crate::Command::output).
+	pub fn stdout(&mut self, cfg: Stdio) -> &mut Self {
+		self.stdout = Some(cfg);
+		self
+	}
+
+	/// Configures the standard error of the child. Defaults to
+	/// [`Stdio::Inherit`](crate::Stdio::Inherit), except for
+	/// [`Command::output`](crate::Command::output).
+	pub fn stderr(&mut self, cfg: Stdio) -> &mut Self {
+		self.stderr = Some(cfg);
+		self
+	}
+
+	/// Returns the command line which will be passed to the child, built with
+	/// [`cmd_line::join`](crate::cmd_line::join).
+	#[must_use]
+	pub fn command_line(&self) -> SysResult<String> {
+		cmd_line::join(
+			&std::iter::once(&self.program)
+				.chain(self.args.iter())
+				.collect::<Vec<_>>(),
+		)
+	}
+
+	/// Spawns the child process.
+	///
+	/// The standard streams not configured default to
+	/// [`Stdio::Inherit`](crate::Stdio::Inherit).
+	#[must_use]
+	pub fn spawn(&self) -> SysResult<Child> {
+		self.spawn_with(
+			self.stdin.unwrap_or(Stdio::Inherit),
+			self.stdout.unwrap_or(Stdio::Inherit),
+			self.stderr.unwrap_or(Stdio::Inherit),
+		)
+	}
+
+	/// Spawns the child process, waits for it to finish, and returns its exit
+	/// code.
+	///
+	/// The standard streams not configured default to
+	/// [`Stdio::Inherit`](crate::Stdio::Inherit).
+	pub fn status(&self) -> SysResult<u32> {
+		self.spawn()?.wait()
+	}
+
+	/// Spawns the child process, waits for it to finish, and returns its exit
+	/// code and everything it wrote to its standard output and standard error.
+	///
+	/// The standard output and standard error not configured default to
+	/// [`Stdio::Piped`](crate::Stdio::Piped), and the standard input not
+	/// configured defaults to [`Stdio::Null`](crate::Stdio::Null).
+	pub fn output(&self) -> SysResult<ChildOutput> {
+		self.spawn_with(
+			self.stdin.unwrap_or(Stdio::Null),
+			self.stdout.unwrap_or(Stdio::Piped),
+			self.stderr.unwrap_or(Stdio::Piped),
+		)?.wait_with_output()
+	}
+
+	fn spawn_with(&self,
+		stdin: Stdio,
+		stdout: Stdio,
+		stderr: Stdio,
+	) -> SysResult<Child>
+	{
+		let cmd_line = self.command_line()?;
+		let env = self.build_env()?;
+
+		let _lock = SPAWN_LOCK.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+
+		let (child_in, parent_in) = StdioEnd::new(stdin, co::STD_HANDLE::INPUT)?;
+		let (child_out, parent_out) = StdioEnd::new(stdout, co::STD_HANDLE::OUTPUT)?;
+		let (child_err, parent_err) = StdioEnd::new(stderr, co::STD_HANDLE::ERROR)?;
+
+		let mut si = STARTUPINFO::default();
+		si.dwFlags = co::STARTF::USESTDHANDLES;
+		si.hStdInput = child_in.raw();
+		si.hStdOutput = child_out.raw();
+		si.hStdError = child_err.raw();
+
+		let pi = HPROCESS::CreateProcess(
+			None,
+			Some(&cmd_line),
+			None,
+			None,
+			true,
+			self.creation_flags,
+			env.as_ref(),
+			self.current_dir.as_deref(),
+			&mut si,
+		)?;
+		drop((child_in, child_out, child_err)); // close our copies of the child ends
+
+		Ok(Child {
+			pi,
+			stdin: parent_in.map(|hpipe| ChildStdin { hpipe }),
+			stdout: parent_out.map(|hpipe| ChildStdout { hpipe }),
+			stderr: parent_err.map(|hpipe| ChildStderr { hpipe }),
+		})
+	}
+
+	/// Returns the environment of the child, or `None` if it's the same of the
+	/// current process.
+	fn build_env(&self) -> SysResult<Option<EnvBlock>> {
+		if !self.env_clear && self.env.is_empty() {
+			return Ok(None);
+		}
+
+		let mut env = if self.env_clear {
+			EnvBlock::new()
+		} else {
+			EnvBlock::from_current()?
+		};
+		for (name, val) in self.env.iter() {
+			match val {
+				Some(val) => env.set(name, val),
+				None => { env.remove(name); },
+			}
+		}
+		Ok(Some(env))
+	}
+}
+
+/// The end of a standard stream which is passed to the child.
+enum StdioEnd {
+	/// Handle owned by the current process, which must not be closed.
+	Borrowed(HPIPE),
+	/// Handle created for the child, which is closed after it's spawned.
+	Owned(CloseHandleGuard<HPIPE>),
+}
+
+impl StdioEnd {
+	/// Returns the end which is passed to the child, and the end which is kept
+	/// by the current process, if any.
+	fn new(
+		cfg: Stdio,
+		std_handle: co::STD_HANDLE,
+	) -> SysResult<(Self, Option<CloseHandleGuard<HPIPE>>)>
+	{
+		Ok(match cfg {
+			Stdio::Inherit => {
+				let hstd = HSTD::GetStdHandle(std_handle)?.leak(); // must not be closed
+				(Self::Borrowed(unsafe { HPIPE::from_ptr(hstd.ptr()) }), None)
+			},
+			Stdio::Null => {
+				let mut sa = SECURITY_ATTRIBUTES::default();
+				sa.set_bInheritHandle(true);
+				let (mut hfile, _) = HFILE::CreateFile(
+					"NUL",
+					co::GENERIC::READ | co::GENERIC::WRITE,
+					Some(co::FILE_SHARE::READ | co::FILE_SHARE::WRITE),
+					Some(&mut sa),
+					co::DISPOSITION::OPEN_EXISTING,
+					co::FILE_ATTRIBUTE::NORMAL,
+					None,
+					None,
+					None,
+				)?;
+				let hpipe = unsafe { HPIPE::from_ptr(hfile.leak().ptr()) };
+				(Self::Owned(unsafe { CloseHandleGuard::new(hpipe) }), None)
+			},
+			Stdio::Piped => {
+				let (hread, hwrite) = HPIPE::CreatePipe(None, 0)?;
+				let (child_end, parent_end) = if std_handle == co::STD_HANDLE::INPUT {
+					(hread, hwrite)
+				} else {
+					(hwrite, hread)
+				};
+				child_end.SetHandleInformation(
+					co::HANDLE_FLAG::INHERIT, co::HANDLE_FLAG::INHERIT)?;
+				(Self::Owned(child_end), Some(parent_end))
+			},
+		})
+	}
+
+	#[must_use]
+	fn raw(&self) -> HPIPE {
+		match self {
+			Self::Borrowed(h) => unsafe { h.raw_copy() },
+			Self::Owned(h) => unsafe { h.raw_copy() },
+		}
+	}
+}
+
+/// A child process spawned by [`Command`](crate::Command).
+///
+/// Dropping a `Child` doesn't wait for the process to finish, nor kills it;
+/// it just closes the handles.
+pub struct Child {
+	pi: CloseHandlePiGuard,
+	/// The pipe to the standard input of the child, if it was configured with
+	/// [`Stdio::Piped`](crate::Stdio::Piped).
+	pub stdin: Option<ChildStdin>,
+	/// The pipe from the standard output of the child, if it was configured
+	/// with [`Stdio::Piped`](crate::Stdio::Piped).
+	pub stdout: Option<ChildStdout>,
+	/// The pipe from the standard error of the child, if it was configured
+	/// with [`Stdio::Piped`](crate::Stdio::Piped).
+	pub stderr: Option<ChildStderr>,
+}
+
+/// Exit code and captured output of a child process, returned by
+/// [`Command::output`](crate::Command::output) and
+/// [`Child::wait_with_output`](crate::Child::wait_with_output).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChildOutput {
+	/// The exit code.
+	pub exit_code: u32,
+	/// Everything written to the standard output, if it was piped.
+	pub stdout: Vec<u8>,
+	/// Everything written to the standard error, if it was piped.
+	pub stderr: Vec<u8>,
+}
+
+impl Child {
+	/// Returns the handle to the process.
+	#[must_use]
+	pub fn hprocess(&self) -> &HPROCESS {
+		&self.pi.hProcess
+	}
+
+	/// Returns the process ID.
+	#[must_use]
+	pub fn id(&self) -> u32 {
+		self.pi.dwProcessId
+	}
+
+	/// Returns the exit code, or `None` if the process is still running.
+	#[must_use]
+	pub fn exit_code(&self) -> SysResult<Option<u32>> {
+		self.wait_timeout(0)
+	}
+
+	/// Closes the standard input, if piped, so the child doesn't wait for
+	/// more input, then waits for it to finish and returns its exit code.
+	///
+	/// Note that, if the standard output or standard error are piped and not
+	/// being read, the child may block when their buffers get full, never
+	/// finishing. In this case, use
+	/// [`Child::wait_with_output`](crate::Child::wait_with_output).
+	pub fn wait(&mut self) -> SysResult<u32> {
+		self.stdin = None;
+		self.pi.hProcess.WaitForSingleObject(None)?;
+		self.pi.hProcess.GetExitCodeProcess()
+	}
+
+	/// Waits for the process to finish, up to the given time, returning its
+	/// exit code, or `None` if the time elapsed.
+	pub fn wait_timeout(&self, milliseconds: u32) -> SysResult<Option<u32>> {
+		match self.pi.hProcess.WaitForSingleObject(Some(milliseconds))? {
+			co::WAIT::TIMEOUT => Ok(None),
+			_ => self.pi.hProcess.GetExitCodeProcess().map(Some),
+		}
+	}
+
+	/// Closes the standard input, if piped, then reads the standard output and
+	/// standard error until the child closes them, and waits for it to finish.
+	///
+	/// The standard error is read in a separate thread, so a child which fills
+	/// one of the pipes while the other is being read doesn't block.
+	pub fn wait_with_output(mut self) -> SysResult<ChildOutput> {
+		self.stdin = None;
+
+		let err_reader = self.stderr.take()
+			.map(|mut stderr| std::thread::spawn(move || stderr.read_to_vec()));
+		let stdout = match self.stdout.take() {
+			Some(mut stdout) => stdout.read_to_vec()?,
+			None => Vec::default(),
+		};
+		let stderr = match err_reader {
+			Some(thread) => thread.join()
+				.map_err(|_| co::ERROR::BROKEN_PIPE)??,
+			None => Vec::default(),
+		};
+
+		Ok(ChildOutput { exit_code: self.wait()?, stdout, stderr })
+	}
+
+	/// Forcibly terminates the process with
+	/// [`HPROCESS::TerminateProcess`](crate::prelude::kernel_Hprocess::TerminateProcess),
+	/// with exit code 1. Does nothing if it already finished.
+	pub fn kill(&mut self) -> SysResult<()> {
+		if self.exit_code()?.is_some() {
+			return Ok(());
+		}
+		self.pi.hProcess.TerminateProcess(1)
+	}
+}
+
+/// The pipe to the standard input of a [`Child`](crate::Child), which
+/// implements [`Write`](std::io::Write).
+///
+/// Dropping it closes the pipe, so the child sees the end of the input.
+pub struct ChildStdin {
+	hpipe: CloseHandleGuard<HPIPE>,
+}
+
+impl std::io::Write for ChildStdin {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		let len = buf.len().min(u32::MAX as _); // API limits the writing up to 4 GB
+		self.hpipe.WriteFile(&buf[..len])
+			.map(|bytes_written| bytes_written as _)
+			.map_err(|err| err.into())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(()) // writes are not buffered
+	}
+}
+
+impl ChildStdin {
+	/// Returns the handle to the pipe.
+	#[must_use]
+	pub fn hpipe(&self) -> &HPIPE {
+		&self.hpipe
+	}
+}
+
+/// The pipe from the standard output of a [`Child`](crate::Child), which
+/// implements [`Read`](std::io::Read).
+pub struct ChildStdout {
+	hpipe: CloseHandleGuard<HPIPE>,
+}
+
+/// The pipe from the standard error of a [`Child`](crate::Child), which
+/// implements [`Read`](std::io::Read).
+pub struct ChildStderr {
+	hpipe: CloseHandleGuard<HPIPE>,
+}
+
+macro_rules! impl_child_reader {
+	($name:ident) => {
+		impl std::io::Read for $name {
+			fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+				let len = buf.len().min(u32::MAX as _); // API limits the reading up to 4 GB
+				match self.hpipe.ReadFile(&mut buf[..len]) {
+					Ok(bytes_read) => Ok(bytes_read as _),
+					Err(co::ERROR::BROKEN_PIPE) => Ok(0), // child closed its end
+					Err(err) => Err(err.into()),
+				}
+			}
+		}
+
+		impl $name {
+			/// Returns the handle to the pipe.
+			#[must_use]
+			pub fn hpipe(&self) -> &HPIPE {
+				&self.hpipe
+			}
+
+			/// Reads until the child closes its end of the pipe.
+			fn read_to_vec(&mut self) -> SysResult<Vec<u8>> {
+				let mut buf = Vec::<u8>::default();
+				std::io::Read::read_to_end(self, &mut buf)
+					.map_err(|err| err.raw_os_error()
+						.map_or(co::ERROR::BROKEN_PIPE, |code| unsafe { co::ERROR::from_raw(code as _) }))?;
+				Ok(buf)
+			}
+		}
+	};
+}
+
+impl_child_reader!(ChildStdout);
+impl_child_reader!(ChildStderr);
@@ -1,5 +1,3 @@
-use std::cmp::Ordering;
-
 /// String encodings.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Encoding {
@@ -46,6 +44,27 @@ impl std::fmt::Display for Encoding {
 }
 
 impl Encoding {
+	/// Returns the [BOM](https://en.wikipedia.org/wiki/Byte_order_mark) bytes
+	/// of the encoding, which are the same ones recognized by
+	/// [`Encoding::guess`](crate::Encoding::guess).
+	///
+	/// Returns an empty slice for [`Unknown`](crate::Encoding::Unknown),
+	/// [`Ansi`](crate::Encoding::Ansi) and
+	/// [`Win1252`](crate::Encoding::Win1252), which have no BOM.
+	#[must_use]
+	pub const fn bom(&self) -> &'static [u8] {
+		match self {
+			Self::Unknown | Self::Ansi | Self::Win1252 => &[],
+			Self::Utf8 => &[0xef, 0xbb, 0xbf],
+			Self::Utf16be => &[0xfe, 0xff],
+			Self::Utf16le => &[0xff, 0xfe],
+			Self::Utf32be => &[0x00, 0x00, 0xfe, 0xff],
+			Self::Utf32le => &[0xff, 0xfe, 0x00, 0x00],
+			Self::Scsu => &[0x0e, 0xfe, 0xff],
+			Self::Bocu1 => &[0xfb, 0xee, 0x28],
+		}
+	}
+
 	/// Guesses the encoding of the given raw data, also returning the size of
 	/// its [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), if any.
 	#[must_use]
@@ -68,47 +87,19 @@ impl Encoding {
 
 	#[must_use]
 	fn guess_bom(data: &[u8]) -> Option<(Self, usize)> {
-		let has_bom = |bom_bytes: &[u8]| -> bool {
-			data.len() >= bom_bytes.len()
-				&& data[..bom_bytes.len()].cmp(bom_bytes) == Ordering::Equal
-		};
-
-		const UTF8: [u8; 3] = [0xef, 0xbb, 0xbf];
-		if has_bom(&UTF8) { // UTF-8 BOM
-			return Some((Self::Utf8, UTF8.len()));
-		}
-
-		const UTF16BE: [u8; 2] = [0xfe, 0xff];
-		if has_bom(&UTF16BE) {
-			return Some((Self::Utf16be, UTF16BE.len()));
-		}
-
-		const UTF16LE: [u8; 2] = [0xff, 0xfe];
-		if has_bom(&UTF16LE) {
-			return Some((Self::Utf16le, UTF16LE.len()));
-		}
-
-		const UTF32BE: [u8; 4] = [0x00, 0x00, 0xfe, 0xff];
-		if has_bom(&UTF32BE) {
-			return Some((Self::Utf32be, UTF32BE.len()));
-		}
-
-		const UTF32LE: [u8; 4] = [0xff, 0xfe, 0x00, 0x00];
-		if has_bom(&UTF32LE) {
-			return Some((Self::Utf32le, UTF32LE.len()));
-		}
-
-		const SCSU: [u8; 3] = [0x0e, 0xfe, 0xff];
-		if has_bom(&SCSU) {
-			return Some((Self::Scsu, SCSU.len()));
-		}
-
-		const BOCU1: [u8; 3] = [0xfb, 0xee, 0x28];
-		if has_bom(&BOCU1) {
-			return Some((Self::Bocu1, BOCU1.len()));
-		}
-
-		None // no BOM found
+		// UTF-32 must be checked before UTF-16, since UTF-32 LE BOM starts with
+		// the UTF-16 LE one.
+		[
+			Self::Utf8,
+			Self::Utf32be,
+			Self::Utf32le,
+			Self::Utf16be,
+			Self::Utf16le,
+			Self::Scsu,
+			Self::Bocu1,
+		].iter()
+			.find(|enc| data.starts_with(enc.bom()))
+			.map(|enc| (*enc, enc.bom().len()))
 	}
 
 	#[must_use]
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+
+use crate::decl::*;
+
+/// An environment block, which maps variable names to their values, like the
+/// one retrieved by [`GetEnvironmentStrings`](crate::GetEnvironmentStrings)
+/// and passed to
+/// [`HPROCESS::CreateProcess`](crate::prelude::kernel_Hprocess::CreateProcess).
+///
+/// Names are compared case-insensitively, with the same ordinal uppercase
+/// comparison used by Windows, and the variables are always kept sorted, as
+/// required by `CreateProcess`. Names may start with an equal sign, like the
+/// `=C:` entries which hold the current directory of each drive.
+///
+/// # Examples
+///
+/// Launching a child with a modified `PATH`, without changing the environment
+/// of the current process:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let mut env = w::EnvBlock::from_current()?;
+/// let path = format!("C:\\Tools;{}", env.get("Path").unwrap_or_default());
+/// env.set("PATH", &path);
+///
+/// let mut si = w::STARTUPINFO::default();
+/// let pi = w::HPROCESS::CreateProcess(
+///     None,
+///     Some("tool.exe --version"),
+///     None,
+///     None,
+///     false,
+///     co::CREATE::default(),
+///     Some(&env),
+///     None,
+///     &mut si,
+/// )?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvBlock {
+	vars: Vec<(String, String)>, // always sorted by name
+}
+
+impl EnvBlock {
+	/// Creates a new, empty block.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a block with the variables of the current process, retrieved
+	/// with [`GetEnvironmentStrings`](crate::GetEnvironmentStrings).
+	#[must_use]
+	pub fn from_current() -> SysResult<Self> {
+		let mut block = Self::new();
+		GetEnvironmentStrings()?
+			.into_iter()
+			.for_each(|(name, val)| block.set(&name, &val));
+		Ok(block)
+	}
+
+	/// Parses a raw environment block: a sequence of null-terminated
+	/// `name=value` UTF-16 strings, ended by an empty string. Parsing stops at
+	/// the empty string, or at the end of the slice.
+	///
+	/// If a name appears more than once, the last value is kept.
+	#[must_use]
+	pub fn parse(block: &[u16]) -> Self {
+		let mut env = Self::new();
+		block.split(|ch| *ch == 0x0000)
+			.take_while(|entry| !entry.is_empty())
+			.map(|entry| WString::from_wchars_slice(entry).to_string())
+			.for_each(|entry| {
+				let (name, val) = split_entry(&entry);
+				env.set(name, val);
+			});
+		env
+	}
+
+	/// Serializes the variables into a raw environment block: a sequence of
+	/// null-terminated `name=value` UTF-16 strings, sorted by name, ended by
+	/// an empty string. This is the format expected by `CreateProcess` with
+	/// [`co::CREATE::UNICODE_ENVIRONMENT`](crate::co::CREATE::UNICODE_ENVIRONMENT).
+	#[must_use]
+	pub fn to_block(&self) -> Vec<u16> {
+		let mut buf = Vec::<u16>::default();
+		for (name, val) in self.vars.iter() {
+			buf.extend(name.encode_utf16());
+			buf.push(u16::from(b'='));
+			buf.extend(val.encode_utf16());
+			buf.push(0x0000);
+		}
+		if buf.is_empty() {
+			buf.push(0x0000); // an empty block still has two nulls
+		}
+		buf.push(0x0000);
+		buf
+	}
+
+	/// Returns the value of the variable, if it exists.
+	#[must_use]
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.find(name)
+			.ok()
+			.map(|idx| self.vars[idx].1.as_str())
+	}
+
+	/// Returns the current directory of the given drive letter, stored in the
+	/// hidden variable whose name is the drive followed by a colon and
+	/// preceded by an equal sign, like `=C:`.
+	#[must_use]
+	pub fn drive_current_dir(&self, drive: char) -> Option<&str> {
+		self.get(&format!("={}:", drive))
+	}
+
+	/// Sets the value of the variable, adding it if it doesn't exist. If it
+	/// exists, its name keeps the original case.
+	pub fn set(&mut self, name: &str, value: &str) {
+		match self.find(name) {
+			Ok(idx) => self.vars[idx].1 = value.to_owned(),
+			Err(idx) => self.vars.insert(idx, (name.to_owned(), value.to_owned())),
+		}
+	}
+
+	/// Removes the variable, returning its value, if it existed.
+	pub fn remove(&mut self, name: &str) -> Option<String> {
+		self.find(name)
+			.ok()
+			.map(|idx| self.vars.remove(idx).1)
+	}
+
+	/// Returns the names and values of the variables, sorted by name.
+	#[must_use]
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.vars.iter().map(|(name, val)| (name.as_str(), val.as_str()))
+	}
+
+	/// Returns the number of variables.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.vars.len()
+	}
+
+	/// Tells whether there are no variables.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.vars.is_empty()
+	}
+
+	fn find(&self, name: &str) -> Result<usize, usize> {
+		self.vars.binary_search_by(|(n, _)| cmp_names(n, name))
+	}
+}
+
+/// Splits a `name=value` entry. The first character is always part of the
+/// name, so entries like `=C:=C:\Temp` have the name `=C:`.
+#[must_use]
+pub(in crate::kernel) fn split_entry(entry: &str) -> (&str, &str) {
+	match entry.char_indices().skip(1).find(|(_, ch)| *ch == '=') {
+		Some((idx, _)) => (&entry[..idx], &entry[idx + 1..]),
+		None => (entry, ""),
+	}
+}
+
+/// Compares two names as Windows does: code unit by code unit, after
+/// converting each UTF-16 code unit to uppercase.
+#[must_use]
+fn cmp_names(a: &str, b: &str) -> Ordering {
+	a.encode_utf16().map(upcase)
+		.cmp(b.encode_utf16().map(upcase))
+}
+
+/// Converts a UTF-16 code unit to uppercase, only if its uppercase is also a
+/// single code unit, like the Windows uppercase table.
+#[must_use]
+fn upcase(ch: u16) -> u16 {
+	char::from_u32(ch as _) // surrogates are kept as they are
+		.map(|c| {
+			let mut upper = c.to_uppercase();
+			match (upper.next(), upper.next()) {
+				(Some(u), None) if (u as u32) <= 0xffff => u as u16,
+				_ => ch, // expands to more than one character
+			}
+		})
+		.unwrap_or(ch)
+}
@@ -41,6 +41,11 @@ impl std::fmt::Display for FileAccess {
 /// If you just want to read the file, consider memory-mapping it with
 /// [`FileMapped`](crate::FileMapped), which tends to be faster.
 ///
+/// Implements the standard [`Read`](std::io::Read),
+/// [`Write`](std::io::Write) and [`Seek`](std::io::Seek) traits – also for
+/// `&File` –, so it can be used with [`std::io::BufReader`],
+/// [`std::io::copy`] and other libraries.
+///
 /// # Examples
 ///
 /// Reading the contents as a string:
@@ -70,10 +75,81 @@ impl std::fmt::Display for FileAccess {
 /// f.write("My text".as_bytes())?;
 /// # w::SysResult::Ok(())
 /// ```
+///
+/// Copying the file contents into another file, through the standard traits:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut f_in = w::File::open(
+///     "C:\\Temp\\foo.txt",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+/// let mut f_out = w::File::open(
+///     "C:\\Temp\\bar.txt",
+///     w::FileAccess::CreateRW,
+/// )?;
+/// std::io::copy(&mut f_in, &mut f_out)?;
+/// # std::io::Result::Ok(())
+/// ```
 pub struct File {
 	hfile: CloseHandleGuard<HFILE>,
 }
 
+impl std::io::Read for File {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		std::io::Read::read(&mut &*self, buf)
+	}
+}
+impl std::io::Read for &File {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let len = buf.len().min(u32::MAX as _); // API limits the reading up to 4 GB
+		self.hfile.ReadFile(&mut buf[..len])
+			.map(|bytes_read| bytes_read as _)
+			.map_err(|err| err.into())
+	}
+}
+
+impl std::io::Write for File {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		std::io::Write::write(&mut &*self, buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		std::io::Write::flush(&mut &*self)
+	}
+}
+impl std::io::Write for &File {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		let len = buf.len().min(u32::MAX as _); // API limits the writing up to 4 GB
+		self.hfile.WriteFile(&buf[..len])
+			.map(|bytes_written| bytes_written as _)
+			.map_err(|err| err.into())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(()) // writes are not buffered
+	}
+}
+
+impl std::io::Seek for File {
+	fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+		std::io::Seek::seek(&mut &*self, pos)
+	}
+}
+impl std::io::Seek for &File {
+	fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+		let (distance, move_method) = match pos {
+			std::io::SeekFrom::Start(off) => (off as i64, co::FILE_STARTING_POINT::BEGIN),
+			std::io::SeekFrom::Current(off) => (off, co::FILE_STARTING_POINT::CURRENT),
+			std::io::SeekFrom::End(off) => (off, co::FILE_STARTING_POINT::END),
+		};
+		self.hfile.SetFilePointerEx(distance, move_method)
+			.map(|new_off| new_off as _)
+			.map_err(|err| err.into())
+	}
+}
+
 impl File {
 	/// Opens a file with the desired access.
 	#[must_use]
@@ -175,6 +251,11 @@ impl File {
 
 	/// Returns the creation and last write times of the file, in the current
 	/// time zone.
+	///
+	/// To compare them against [`std::time::SystemTime`](std::time::SystemTime)
+	/// values, which are in UTC, use the [`FILETIME`](crate::FILETIME) values
+	/// returned by [`HFILE::GetFileTime`](crate::prelude::kernel_Hfile::GetFileTime)
+	/// instead, since they can be converted directly.
 	#[must_use]
 	pub fn times(&self) -> SysResult<(SYSTEMTIME, SYSTEMTIME)> {
 		let (ft_creation, _, ft_last_write) = self.hfile.GetFileTime()?;
@@ -23,6 +23,24 @@ use crate::prelude::*;
 /// let text = w::WString::parse(raw_bytes)?.to_string();
 /// # w::SysResult::Ok(())
 /// ```
+///
+/// Reading the mapped memory through the standard
+/// [`Read`](std::io::Read) trait:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+/// use std::io::Read;
+///
+/// let f = w::FileMapped::open(
+///     "C:\\Temp\\foo.bin",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+/// let mut cursor = f.cursor();
+///
+/// let mut header = [0u8; 16];
+/// cursor.read_exact(&mut header)?;
+/// # std::io::Result::Ok(())
+/// ```
 pub struct FileMapped {
 	hview: UnmapViewOfFileGuard, // drop order is important
 	_hmap: CloseHandleGuard<HFILEMAP>,
@@ -72,6 +90,25 @@ impl FileMapped {
 		self.hview.as_slice(self.size as _)
 	}
 
+	/// Returns a [`Cursor`](std::io::Cursor) over the mapped memory, which
+	/// implements the standard [`Read`](std::io::Read),
+	/// [`BufRead`](std::io::BufRead) and [`Seek`](std::io::Seek) traits.
+	#[must_use]
+	pub fn cursor(&self) -> std::io::Cursor<&[u8]> {
+		std::io::Cursor::new(self.as_slice())
+	}
+
+	/// Returns a [`Cursor`](std::io::Cursor) over the mutable mapped memory,
+	/// which implements the standard [`Read`](std::io::Read),
+	/// [`Write`](std::io::Write) and [`Seek`](std::io::Seek) traits.
+	///
+	/// Since the mapped memory has a fixed size, writing past its end will
+	/// fail.
+	#[must_use]
+	pub fn cursor_mut(&mut self) -> std::io::Cursor<&mut [u8]> {
+		std::io::Cursor::new(self.as_mut_slice())
+	}
+
 	/// Returns the underlying file handle.
 	#[must_use]
 	pub fn hfile(&self) -> &HFILE {
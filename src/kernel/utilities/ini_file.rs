@@ -0,0 +1,420 @@
+use crate::co;
+use crate::decl::*;
+
+/// Contents of an `.ini` file, kept in memory as a document which preserves
+/// comments, blank lines and the order of sections and keys, so it can be
+/// written back with minimal changes.
+///
+/// The file is loaded with [`WString::parse`](crate::WString::parse), so its
+/// encoding is guessed, and it's saved back with the same encoding and
+/// [BOM](https://en.wikipedia.org/wiki/Byte_order_mark). Section and key
+/// names are case-insensitive, like in
+/// [`GetPrivateProfileString`](crate::GetPrivateProfileString), and lines
+/// starting with `;` or `#` are comments.
+///
+/// Unlike the profile functions, which open the file at each call, all the
+/// operations are performed in memory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut ini = w::IniFile::load("C:\\Temp\\app.ini")?;
+///
+/// let width: u32 = ini.get_as("Window", "Width")?.unwrap_or(800);
+/// let maximized = ini.get_bool("Window", "Maximized")?.unwrap_or(false);
+///
+/// for (key, val) in ini.entries("Recent") {
+///     println!("{} = {}", key, val);
+/// }
+///
+/// ini.set("Window", "Width", width + 10)?;
+/// ini.save("C:\\Temp\\app.ini")?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct IniFile {
+	encoding: Encoding,
+	bom: bool,
+	line_break: &'static str,
+	final_line_break: bool,
+	preamble: Vec<IniLine>,
+	sections: Vec<IniSection>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct IniSection {
+	name: String,
+	header: String, // the original line
+	lines: Vec<IniLine>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum IniLine {
+	/// Blank line, comment or any other line which is not an entry.
+	Text(String),
+	/// A `key=value` entry, with the original line.
+	Entry { key: String, value: String, line: String },
+}
+
+impl Default for IniFile {
+	fn default() -> Self {
+		Self {
+			encoding: Encoding::Utf8,
+			bom: false,
+			line_break: "\r\n",
+			final_line_break: true,
+			preamble: Vec::default(),
+			sections: Vec::default(),
+		}
+	}
+}
+
+impl std::fmt::Display for IniFile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let lines = self.preamble.iter()
+			.map(|line| line.text())
+			.chain(self.sections.iter()
+				.flat_map(|sec| std::iter::once(sec.header.as_str())
+					.chain(sec.lines.iter().map(|line| line.text()))));
+
+		for (idx, line) in lines.enumerate() {
+			if idx > 0 {
+				f.write_str(self.line_break)?;
+			}
+			f.write_str(line)?;
+		}
+		if self.final_line_break && !(self.preamble.is_empty() && self.sections.is_empty()) {
+			f.write_str(self.line_break)?;
+		}
+		Ok(())
+	}
+}
+
+impl IniLine {
+	#[must_use]
+	fn text(&self) -> &str {
+		match self {
+			Self::Text(line) => line,
+			Self::Entry { line, .. } => line,
+		}
+	}
+}
+
+impl IniFile {
+	/// Creates a new, empty document, which will be saved as UTF-8 without
+	/// BOM, with CRLF line breaks.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Reads the file with [`File::read_all`](crate::File::read_all) and
+	/// parses its contents with [`IniFile::parse`](crate::IniFile::parse).
+	pub fn load(file_path: &str) -> SysResult<Self> {
+		let f = File::open(file_path, FileAccess::ExistingReadOnly)?;
+		Self::parse(&f.read_all()?)
+	}
+
+	/// Parses the raw bytes of an `.ini` file, guessing its encoding with
+	/// [`Encoding::guess`](crate::Encoding::guess) and decoding it with
+	/// [`WString::parse`](crate::WString::parse). The encoding, the BOM and
+	/// the line breaks are kept, and used when serializing. A file guessed as
+	/// [`Encoding::Ansi`](crate::Encoding::Ansi) is saved as
+	/// [`Encoding::Utf8`](crate::Encoding::Utf8), which is a superset of it,
+	/// so non-ASCII values can be set.
+	///
+	/// Lines which are neither sections nor entries are kept as they are.
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let (encoding, sz_bom) = Encoding::guess(data);
+		let text = WString::parse(data)?.to_string();
+
+		let mut ini = Self {
+			encoding: match encoding {
+				Encoding::Unknown | Encoding::Ansi => Encoding::Utf8,
+				enc => enc,
+			},
+			bom: sz_bom > 0,
+			line_break: if text.contains("\r\n") || !text.contains('\n') { "\r\n" } else { "\n" },
+			final_line_break: text.is_empty() || text.ends_with('\n'),
+			..Self::default()
+		};
+
+		if text.is_empty() {
+			return Ok(ini);
+		}
+
+		for line in text.strip_suffix('\n').unwrap_or(&text).split('\n') {
+			let line = line.strip_suffix('\r').unwrap_or(line);
+			let trimmed = line.trim();
+
+			if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+				ini.sections.push(IniSection {
+					name: name.trim().to_owned(),
+					header: line.to_owned(),
+					lines: Vec::default(),
+				});
+				continue;
+			}
+
+			let parsed = match trimmed.split_once('=') {
+				Some((key, value)) if !trimmed.starts_with(';')
+					&& !trimmed.starts_with('#')
+					&& !key.trim().is_empty() => IniLine::Entry {
+						key: key.trim().to_owned(),
+						value: value.trim().to_owned(),
+						line: line.to_owned(),
+					},
+				_ => IniLine::Text(line.to_owned()),
+			};
+
+			match ini.sections.last_mut() {
+				Some(sec) => sec.lines.push(parsed),
+				None => ini.preamble.push(parsed),
+			}
+		}
+
+		Ok(ini)
+	}
+
+	/// Serializes the document into raw bytes, with
+	/// [`WString::serialize`](crate::WString::serialize), using the encoding
+	/// and the BOM of the original file.
+	///
+	/// Fails with
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION)
+	/// if a character cannot be represented in the encoding, like a non-ASCII
+	/// character in an [`Encoding::Ansi`](crate::Encoding::Ansi) file. In this
+	/// case, change the encoding with
+	/// [`IniFile::set_encoding`](crate::IniFile::set_encoding).
+	#[must_use]
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		WString::from_str(self.to_string())
+			.serialize(self.encoding, self.bom)
+	}
+
+	/// Serializes the document with
+	/// [`IniFile::serialize`](crate::IniFile::serialize), and writes it into
+	/// the file, which is created if it doesn't exist, or truncated otherwise.
+	pub fn save(&self, file_path: &str) -> SysResult<()> {
+		let data = self.serialize()?;
+		let f = File::open(file_path, FileAccess::OpenOrCreateRW)?;
+		f.set_size(0)?;
+		f.write(&data)
+	}
+
+	/// Returns the encoding and whether a BOM is written, which are used by
+	/// [`IniFile::serialize`](crate::IniFile::serialize).
+	#[must_use]
+	pub const fn encoding(&self) -> (Encoding, bool) {
+		(self.encoding, self.bom)
+	}
+
+	/// Sets the encoding and whether a BOM is written, which are used by
+	/// [`IniFile::serialize`](crate::IniFile::serialize).
+	pub fn set_encoding(&mut self, encoding: Encoding, bom: bool) {
+		self.encoding = encoding;
+		self.bom = bom;
+	}
+
+	/// Returns the names of the sections, in the order they appear.
+	#[must_use]
+	pub fn sections(&self) -> impl Iterator<Item = &str> {
+		self.sections.iter().map(|sec| sec.name.as_str())
+	}
+
+	/// Returns the keys and values of the section, in the order they appear.
+	/// If the section doesn't exist, the iterator is empty.
+	#[must_use]
+	pub fn entries<'a>(&'a self,
+		section: &str,
+	) -> impl Iterator<Item = (&'a str, &'a str)> + 'a
+	{
+		self.section(section)
+			.into_iter()
+			.flat_map(|sec| sec.lines.iter())
+			.filter_map(|line| match line {
+				IniLine::Entry { key, value, .. } => Some((key.as_str(), unquote(value))),
+				IniLine::Text(_) => None,
+			})
+	}
+
+	/// Tells whether the section exists.
+	#[must_use]
+	pub fn has_section(&self, section: &str) -> bool {
+		self.section(section).is_some()
+	}
+
+	/// Returns the value of the key, if it exists. If the value is enclosed in
+	/// double quotes, they're removed.
+	#[must_use]
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		self.entries(section)
+			.find(|(k, _)| eq_ci(k, key))
+			.map(|(_, v)| v)
+	}
+
+	/// Returns the value of the key parsed with
+	/// [`FromStr`](std::str::FromStr), or `None` if the key doesn't exist.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the value cannot be parsed.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let ini: w::IniFile; // initialized somewhere
+	/// # let ini = w::IniFile::new();
+	///
+	/// let port = ini.get_as::<u16>("Server", "Port")?.unwrap_or(8080);
+	/// # w::SysResult::Ok(())
+	/// ```
+	pub fn get_as<T>(&self, section: &str, key: &str) -> SysResult<Option<T>>
+		where T: std::str::FromStr,
+	{
+		self.get(section, key)
+			.map(|v| v.parse::<T>().map_err(|_| co::ERROR::INVALID_DATA))
+			.transpose()
+	}
+
+	/// Returns the value of the key as a `bool`, or `None` if the key doesn't
+	/// exist. Accepts `1`, `true`, `yes` and `on`, or `0`, `false`, `no` and
+	/// `off`, case-insensitive.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the value is something else.
+	pub fn get_bool(&self, section: &str, key: &str) -> SysResult<Option<bool>> {
+		self.get(section, key)
+			.map(|v| match v.to_ascii_lowercase().as_str() {
+				"1" | "true" | "yes" | "on" => Ok(true),
+				"0" | "false" | "no" | "off" => Ok(false),
+				_ => Err(co::ERROR::INVALID_DATA),
+			})
+			.transpose()
+	}
+
+	/// Sets the value of the key, formatted with
+	/// [`Display`](std::fmt::Display). Values with leading or trailing spaces
+	/// are enclosed in double quotes.
+	///
+	/// If the key exists, its line is rewritten; otherwise the key is added
+	/// after the last entry of the section. If the section doesn't exist, it's
+	/// added at the end of the document.
+	///
+	/// Fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if the section or the value contain line breaks, or if the key is empty,
+	/// has leading or trailing spaces, contains `=` or line breaks, or starts
+	/// with `[`, `;` or `#` – these would be read back as something else.
+	pub fn set<V>(&mut self, section: &str, key: &str, value: V) -> SysResult<()>
+		where V: std::fmt::Display,
+	{
+		let value = value.to_string();
+		let has_line_break = |s: &str| s.contains(['\r', '\n']);
+		if has_line_break(section)
+			|| has_line_break(&value)
+			|| key.is_empty()
+			|| key.trim() != key
+			|| key.contains(['=', '\r', '\n'])
+			|| key.starts_with(['[', ';', '#'])
+		{
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		let value = if value.trim() != value || unquote(&value) != value {
+			format!("\"{}\"", value)
+		} else {
+			value
+		};
+		let new_line = IniLine::Entry {
+			key: key.to_owned(),
+			line: format!("{}={}", key, value),
+			value,
+		};
+
+		let sec_idx = match self.sections.iter().position(|sec| eq_ci(&sec.name, section)) {
+			Some(idx) => idx,
+			None => {
+				let last_lines = self.sections.last()
+					.map_or(&self.preamble, |sec| &sec.lines);
+				if last_lines.last().is_some_and(|line| !line.text().trim().is_empty()) {
+					self.last_lines_mut().push(IniLine::Text(String::default()));
+				}
+				self.sections.push(IniSection {
+					name: section.to_owned(),
+					header: format!("[{}]", section),
+					lines: Vec::default(),
+				});
+				self.sections.len() - 1
+			},
+		};
+
+		let lines = &mut self.sections[sec_idx].lines;
+		match lines.iter_mut().find(|line| matches!(line, IniLine::Entry { key: k, .. } if eq_ci(k, key))) {
+			Some(existing) => *existing = new_line,
+			None => {
+				let pos = lines.iter()
+					.rposition(|line| matches!(line, IniLine::Entry { .. }))
+					.map_or(0, |idx| idx + 1);
+				lines.insert(pos, new_line);
+			},
+		}
+		Ok(())
+	}
+
+	/// Removes the key, returning its value, if it existed.
+	pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+		let lines = &mut self.sections.iter_mut()
+			.find(|sec| eq_ci(&sec.name, section))?
+			.lines;
+		let idx = lines.iter()
+			.position(|line| matches!(line, IniLine::Entry { key: k, .. } if eq_ci(k, key)))?;
+		match lines.remove(idx) {
+			IniLine::Entry { value, .. } => Some(unquote(&value).to_owned()),
+			IniLine::Text(_) => unreachable!(),
+		}
+	}
+
+	/// Removes the section with all its lines, including comments. Returns
+	/// `true` if the section existed.
+	pub fn remove_section(&mut self, section: &str) -> bool {
+		match self.sections.iter().position(|sec| eq_ci(&sec.name, section)) {
+			Some(idx) => {
+				self.sections.remove(idx);
+				true
+			},
+			None => false,
+		}
+	}
+
+	#[must_use]
+	fn section(&self, section: &str) -> Option<&IniSection> {
+		self.sections.iter().find(|sec| eq_ci(&sec.name, section))
+	}
+
+	#[must_use]
+	fn last_lines_mut(&mut self) -> &mut Vec<IniLine> {
+		match self.sections.last_mut() {
+			Some(sec) => &mut sec.lines,
+			None => &mut self.preamble,
+		}
+	}
+}
+
+/// Removes a pair of enclosing double quotes, if any.
+#[must_use]
+fn unquote(value: &str) -> &str {
+	value.strip_prefix('"')
+		.and_then(|v| v.strip_suffix('"'))
+		.unwrap_or(value)
+}
+
+/// Case-insensitive comparison.
+#[must_use]
+fn eq_ci(a: &str, b: &str) -> bool {
+	a.chars().flat_map(char::to_lowercase)
+		.eq(b.chars().flat_map(char::to_lowercase))
+}
@@ -1,11 +1,37 @@
+mod command;
 mod encoding;
+mod env_block;
 mod file_mapped;
 mod file;
+mod ini_file;
+mod named_pipe;
+mod pe_resources;
+mod reg_file;
+mod res_file;
+mod service_config;
+mod service_host;
+mod sid;
+mod text_decoder;
+mod text_encoder;
+mod text_reader;
 mod w_string;
 
+pub mod cmd_line;
 pub mod path;
 
+pub use command::{Child, ChildOutput, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
 pub use encoding::Encoding;
+pub use env_block::EnvBlock;
+pub(in crate::kernel) use env_block::split_entry;
 pub use file_mapped::FileMapped;
 pub use file::{File, FileAccess};
+pub use ini_file::IniFile;
+pub use named_pipe::{NamedPipeClient, NamedPipeConnection, NamedPipeServer};
+pub use pe_resources::{PeResources, PeResourceType, PeResourceName, PeResourceLang};
+pub use reg_file::{RegFile, RegFileKey, RegFileValue};
+pub use res_file::{ResEntry, ResFile};
+pub use service_config::ServiceConfig;
+pub use service_host::{ServiceContext, ServiceEvent, ServiceHost};
+pub use sid::Sid;
+pub use text_reader::TextReader;
 pub use w_string::WString;
@@ -0,0 +1,438 @@
+use std::time::Instant;
+
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::kernel::{ffi, ffi_types::*, privs::*};
+use crate::prelude::*;
+
+/// Size of the input and output buffers of each pipe instance, and of the
+/// chunks read by `read_message`.
+const BUF_SZ: u32 = 4096;
+
+/// Server end of a
+/// [named pipe](https://learn.microsoft.com/en-us/windows/win32/ipc/named-pipes),
+/// which accepts any number of clients, each one with its own
+/// [`NamedPipeConnection`](crate::NamedPipeConnection).
+///
+/// There is always one pipe instance waiting for the next client, so clients
+/// which connect between two calls to
+/// [`accept`](crate::NamedPipeServer::accept) don't fail. The instances are
+/// created with overlapped I/O, so the waiting can time out.
+///
+/// Unless other security attributes are given, the pipe is created with the
+/// default security, which allows only administrators, the `LocalSystem`
+/// account and the creator to write to it. Remote clients are always
+/// rejected.
+///
+/// A service which serves a pipe to non-administrator users must pass a
+/// security descriptor whose DACL grants them only read and write access. Do
+/// not use a null DACL: it gives every user full control of the pipe,
+/// including changing its DACL and its owner.
+///
+/// # Examples
+///
+/// A server which echoes each message back, with one thread per client:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let mut server = w::NamedPipeServer::new("\\\\.\\pipe\\my-pipe", true, None)?;
+///
+/// loop {
+///     let mut conn = server.accept()?;
+///     std::thread::spawn(move || -> w::SysResult<()> {
+///         println!("Client PID: {}", conn.client_process_id()?);
+///         loop {
+///             let msg = match conn.read_message() {
+///                 Err(co::ERROR::BROKEN_PIPE) => break, // client is gone
+///                 res => res?,
+///             };
+///             conn.write_message(&msg)?;
+///         }
+///         Ok(())
+///     });
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+pub struct NamedPipeServer<'a> {
+	name: String,
+	message_mode: bool,
+	security_attributes: Option<&'a mut SECURITY_ATTRIBUTES<'a>>,
+	hpipe: Option<CloseHandleGuard<HPIPE>>, // instance waiting for the next client
+	hevent: CloseHandleGuard<HEVENT>,
+}
+
+impl<'a> NamedPipeServer<'a> {
+	/// Creates the first instance of the pipe with
+	/// [`HPIPE::CreateNamedPipe`](crate::prelude::kernel_Hpipe::CreateNamedPipe).
+	/// The `name` must have the form `\\.\pipe\name`.
+	///
+	/// If `message_mode` is true, data is written and read as messages,
+	/// otherwise as a stream of bytes.
+	///
+	/// The `security_attributes` are kept and used for every instance of the
+	/// pipe, so they must outlive the server.
+	///
+	/// Fails with [`co::ERROR::ACCESS_DENIED`](crate::co::ERROR::ACCESS_DENIED)
+	/// if the pipe already exists.
+	#[must_use]
+	pub fn new(
+		name: &str,
+		message_mode: bool,
+		security_attributes: Option<&'a mut SECURITY_ATTRIBUTES<'a>>,
+	) -> SysResult<Self>
+	{
+		let mut new_self = Self {
+			name: name.to_owned(),
+			message_mode,
+			security_attributes,
+			hpipe: None,
+			hevent: HEVENT::CreateEvent(None, true, false, None)?,
+		};
+		new_self.hpipe = Some(new_self.create_instance(true)?);
+		Ok(new_self)
+	}
+
+	/// Returns the name of the pipe.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Blocks until a client connects, returning the connection.
+	pub fn accept(&mut self) -> SysResult<NamedPipeConnection> {
+		self.accept_with(None)
+			.map(|conn| conn.unwrap()) // cannot time out
+	}
+
+	/// Waits until a client connects, or until the timeout elapses, in which
+	/// case returns `None`.
+	pub fn accept_timeout(&mut self,
+		milliseconds: u32,
+	) -> SysResult<Option<NamedPipeConnection>>
+	{
+		self.accept_with(Some(milliseconds))
+	}
+
+	fn accept_with(&mut self,
+		milliseconds: Option<u32>,
+	) -> SysResult<Option<NamedPipeConnection>>
+	{
+		if self.hpipe.is_none() { // creation failed in the previous accept
+			self.hpipe = Some(self.create_instance(false)?);
+		}
+		let hpipe = self.hpipe.as_ref().unwrap();
+
+		self.hevent.ResetEvent()?;
+		let mut ov = OVERLAPPED {
+			hEvent: unsafe { self.hevent.raw_copy() },
+			..Default::default()
+		};
+
+		match bool_to_sysresult(
+			unsafe { ffi::ConnectNamedPipe(hpipe.ptr(), &mut ov as *mut _ as _) },
+		) {
+			Ok(_) | Err(co::ERROR::PIPE_CONNECTED) => {}, // client already connected
+			Err(co::ERROR::IO_PENDING) => {
+				if self.hevent.WaitForSingleObject(milliseconds)? == co::WAIT::TIMEOUT {
+					unsafe { ffi::CancelIoEx(hpipe.ptr(), &mut ov as *mut _ as _); }
+				}
+				// The OVERLAPPED must outlive the operation, so we always wait
+				// for it; a cancelled operation may have connected anyway.
+				match overlapped_result(hpipe, &mut ov) {
+					Ok(_) => {},
+					Err(co::ERROR::OPERATION_ABORTED) => return Ok(None),
+					Err(err) => return Err(err),
+				}
+			},
+			Err(err) => return Err(err),
+		}
+
+		let hpipe = self.hpipe.take().unwrap();
+		self.hpipe = self.create_instance(false).ok(); // if it fails, next accept will retry
+		Ok(Some(NamedPipeConnection { end: PipeEnd::new(hpipe)? }))
+	}
+
+	fn create_instance(&mut self, first: bool) -> SysResult<CloseHandleGuard<HPIPE>> {
+		let pipe_mode = if self.message_mode {
+			co::PIPE::TYPE_MESSAGE | co::PIPE::READMODE_MESSAGE
+		} else {
+			co::PIPE::TYPE_BYTE | co::PIPE::READMODE_BYTE
+		};
+		let flags = if first {
+			co::FILE_FLAG::OVERLAPPED | co::FILE_FLAG::FIRST_PIPE_INSTANCE
+		} else {
+			co::FILE_FLAG::OVERLAPPED
+		};
+
+		HPIPE::CreateNamedPipe(
+			&self.name,
+			co::PIPE_ACCESS::DUPLEX,
+			pipe_mode | co::PIPE::WAIT | co::PIPE::REJECT_REMOTE_CLIENTS,
+			None,
+			BUF_SZ,
+			BUF_SZ,
+			0,
+			Some(flags),
+			self.security_attributes.as_deref_mut(),
+		)
+	}
+}
+
+/// A client connected to a [`NamedPipeServer`](crate::NamedPipeServer),
+/// which implements [`Read`](std::io::Read) and [`Write`](std::io::Write).
+///
+/// When the client closes its end, reading returns zero bytes.
+///
+/// Dropping the connection closes the pipe instance, discarding any data not
+/// yet read by the client; to wait until the client reads it, call
+/// [`disconnect`](crate::NamedPipeConnection::disconnect).
+pub struct NamedPipeConnection {
+	end: PipeEnd,
+}
+
+impl NamedPipeConnection {
+	/// Returns the ID of the client process, with
+	/// [`HPIPE::GetNamedPipeClientProcessId`](crate::prelude::kernel_Hpipe::GetNamedPipeClientProcessId).
+	#[must_use]
+	pub fn client_process_id(&self) -> SysResult<u32> {
+		self.end.hpipe.GetNamedPipeClientProcessId()
+	}
+
+	/// Waits until the client reads all the data written to the pipe, with
+	/// [`HPIPE::FlushFileBuffers`](crate::prelude::kernel_Hpipe::FlushFileBuffers),
+	/// then disconnects it, with
+	/// [`HPIPE::DisconnectNamedPipe`](crate::prelude::kernel_Hpipe::DisconnectNamedPipe).
+	pub fn disconnect(self) -> SysResult<()> {
+		self.end.hpipe.FlushFileBuffers()?;
+		self.end.hpipe.DisconnectNamedPipe()
+	}
+}
+
+/// Client end of a
+/// [named pipe](https://learn.microsoft.com/en-us/windows/win32/ipc/named-pipes),
+/// which implements [`Read`](std::io::Read) and [`Write`](std::io::Write).
+///
+/// When the server closes its end, reading returns zero bytes.
+///
+/// # Examples
+///
+/// Sending a message and waiting for the answer:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut client = w::NamedPipeClient::connect(
+///     "\\\\.\\pipe\\my-pipe", Some(5000))?;
+///
+/// client.write_message("status".as_bytes())?;
+/// let answer = client.read_message()?;
+/// # w::SysResult::Ok(())
+/// ```
+pub struct NamedPipeClient {
+	end: PipeEnd,
+}
+
+impl NamedPipeClient {
+	/// Connects to an existing pipe. The `name` must have the form
+	/// `\\server\pipe\name`, where `server` is `.` for the local computer.
+	///
+	/// If all instances of the pipe are busy, waits for one to be available
+	/// with [`HPIPE::WaitNamedPipe`](crate::prelude::kernel_Hpipe::WaitNamedPipe),
+	/// up to `milliseconds`, or indefinitely if `None`. If the pipe is in
+	/// message mode, the client also reads in message mode.
+	///
+	/// Fails with [`co::ERROR::FILE_NOT_FOUND`](crate::co::ERROR::FILE_NOT_FOUND)
+	/// if the pipe doesn't exist, and with
+	/// [`co::ERROR::SEM_TIMEOUT`](crate::co::ERROR::SEM_TIMEOUT) if the timeout
+	/// elapses.
+	#[must_use]
+	pub fn connect(name: &str, milliseconds: Option<u32>) -> SysResult<Self> {
+		let start = Instant::now();
+
+		let mut hfile = loop {
+			match HFILE::CreateFile(
+				name,
+				co::GENERIC::READ | co::GENERIC::WRITE,
+				None,
+				None,
+				co::DISPOSITION::OPEN_EXISTING,
+				co::FILE_ATTRIBUTE::NORMAL,
+				Some(co::FILE_FLAG::OVERLAPPED),
+				None,
+				None,
+			) {
+				Ok((hfile, _)) => break hfile,
+				Err(co::ERROR::PIPE_BUSY) => {
+					let remaining = match milliseconds {
+						Some(ms) => {
+							let elapsed = start.elapsed().as_millis().min(ms as _) as u32;
+							Some((ms - elapsed).max(1)) // zero would mean the default timeout
+						},
+						None => None,
+					};
+					HPIPE::WaitNamedPipe(name, remaining)?;
+				},
+				Err(err) => return Err(err),
+			}
+		};
+
+		let hpipe = unsafe { CloseHandleGuard::new(HPIPE::from_ptr(hfile.leak().ptr())) };
+		let (flags, _, _, _) = hpipe.GetNamedPipeInfo()?;
+		if flags.has(co::PIPE::TYPE_MESSAGE) {
+			hpipe.SetNamedPipeHandleState(Some(co::PIPE::READMODE_MESSAGE), None, None)?;
+		}
+		Ok(Self { end: PipeEnd::new(hpipe)? })
+	}
+
+	/// Returns the ID of the server process, with
+	/// [`HPIPE::GetNamedPipeServerProcessId`](crate::prelude::kernel_Hpipe::GetNamedPipeServerProcessId).
+	#[must_use]
+	pub fn server_process_id(&self) -> SysResult<u32> {
+		self.end.hpipe.GetNamedPipeServerProcessId()
+	}
+}
+
+macro_rules! impl_pipe_stream {
+	($name:ident) => {
+		impl std::io::Read for $name {
+			fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+				match self.end.read(buf) {
+					Ok((bytes_read, _)) => Ok(bytes_read as _),
+					Err(co::ERROR::BROKEN_PIPE) => Ok(0), // other end closed the pipe
+					Err(err) => Err(err.into()),
+				}
+			}
+		}
+
+		impl std::io::Write for $name {
+			fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+				let len = buf.len().min(u32::MAX as _); // API limits the writing up to 4 GB
+				self.end.write(&buf[..len])
+					.map(|bytes_written| bytes_written as _)
+					.map_err(|err| err.into())
+			}
+
+			fn flush(&mut self) -> std::io::Result<()> {
+				Ok(()) // writes are not buffered
+			}
+		}
+
+		impl $name {
+			/// Returns the handle to the pipe.
+			#[must_use]
+			pub fn hpipe(&self) -> &HPIPE {
+				&self.end.hpipe
+			}
+
+			/// Returns the number of bytes which can be read without blocking,
+			/// with [`HPIPE::PeekNamedPipe`](crate::prelude::kernel_Hpipe::PeekNamedPipe).
+			#[must_use]
+			pub fn bytes_available(&self) -> SysResult<u32> {
+				self.end.hpipe.PeekNamedPipe(None)
+					.map(|(_, total_avail, _)| total_avail)
+			}
+
+			/// Blocks until an entire message is read.
+			///
+			/// In a byte-mode pipe, returns the bytes read by a single read
+			/// operation.
+			///
+			/// Fails with [`co::ERROR::BROKEN_PIPE`](crate::co::ERROR::BROKEN_PIPE)
+			/// if the other end closed the pipe.
+			pub fn read_message(&mut self) -> SysResult<Vec<u8>> {
+				let mut msg = Vec::<u8>::default();
+				loop {
+					let prev_len = msg.len();
+					msg.resize(prev_len + BUF_SZ as usize, 0);
+					let (bytes_read, more_data) = self.end.read(&mut msg[prev_len..])?;
+					msg.truncate(prev_len + bytes_read as usize);
+					if !more_data {
+						return Ok(msg);
+					}
+				}
+			}
+
+			/// Writes the whole data at once, which is a single message in a
+			/// message-mode pipe.
+			pub fn write_message(&mut self, data: &[u8]) -> SysResult<()> {
+				if data.len() > u32::MAX as _ { // API limits the writing up to 4 GB
+					return Err(co::ERROR::INVALID_PARAMETER);
+				}
+				self.end.write(data).map(|_| ())
+			}
+		}
+	};
+}
+
+impl_pipe_stream!(NamedPipeConnection);
+impl_pipe_stream!(NamedPipeClient);
+
+/// A connected pipe handle, opened for overlapped I/O, which is performed
+/// synchronously by waiting for each operation to complete.
+struct PipeEnd {
+	hpipe: CloseHandleGuard<HPIPE>,
+	hevent: CloseHandleGuard<HEVENT>,
+}
+
+impl PipeEnd {
+	#[must_use]
+	fn new(hpipe: CloseHandleGuard<HPIPE>) -> SysResult<Self> {
+		Ok(Self {
+			hpipe,
+			hevent: HEVENT::CreateEvent(None, true, false, None)?,
+		})
+	}
+
+	/// Returns the number of bytes read, and whether the message has more data
+	/// to be read.
+	fn read(&mut self, buf: &mut [u8]) -> SysResult<(u32, bool)> {
+		let len = buf.len().min(u32::MAX as _); // API limits the reading up to 4 GB
+		let hpipe = &self.hpipe;
+		self.overlapped_io(|ov| unsafe {
+			ffi::ReadFile(hpipe.ptr(), buf.as_mut_ptr() as _, len as _, std::ptr::null_mut(), ov)
+		})
+	}
+
+	/// Returns the number of bytes written.
+	fn write(&mut self, data: &[u8]) -> SysResult<u32> {
+		let hpipe = &self.hpipe;
+		self.overlapped_io(|ov| unsafe {
+			ffi::WriteFile(hpipe.ptr(), vec_ptr(data) as _, data.len() as _, std::ptr::null_mut(), ov)
+		}).map(|(bytes_written, _)| bytes_written)
+	}
+
+	/// Starts the operation with the given `OVERLAPPED`, then blocks until it's
+	/// complete, so the buffer is not released while in use.
+	fn overlapped_io<F>(&self, start_op: F) -> SysResult<(u32, bool)>
+		where F: FnOnce(PVOID) -> BOOL,
+	{
+		let mut ov = OVERLAPPED {
+			hEvent: unsafe { self.hevent.raw_copy() },
+			..Default::default()
+		};
+
+		match bool_to_sysresult(start_op(&mut ov as *mut _ as _)) {
+			Ok(_) | Err(co::ERROR::IO_PENDING) | Err(co::ERROR::MORE_DATA) => {},
+			Err(err) => return Err(err), // operation didn't start
+		}
+		match overlapped_result(&self.hpipe, &mut ov) {
+			Ok(num_bytes) => Ok((num_bytes, false)),
+			Err(co::ERROR::MORE_DATA) => Ok((ov.InternalHigh as _, true)),
+			Err(err) => Err(err),
+		}
+	}
+}
+
+/// Blocks until the operation is complete, with
+/// [`GetOverlappedResult`](https://learn.microsoft.com/en-us/windows/win32/api/ioapiset/nf-ioapiset-getoverlappedresult),
+/// returning the number of bytes transferred.
+fn overlapped_result(hpipe: &HPIPE, ov: &mut OVERLAPPED) -> SysResult<u32> {
+	let mut num_bytes = u32::default();
+	bool_to_sysresult(
+		unsafe {
+			ffi::GetOverlappedResult(hpipe.ptr(), ov as *mut _ as _, &mut num_bytes, 1)
+		},
+	).map(|_| num_bytes)
+}
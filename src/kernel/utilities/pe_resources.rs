@@ -0,0 +1,405 @@
+use std::collections::HashSet;
+
+use crate::co;
+use crate::decl::*;
+use crate::kernel::privs::bytes::*;
+
+/// Index of the resource table in the data directories of the optional header.
+const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+
+/// Maximum number of directory entries, in all levels, read from a file.
+const MAX_ENTRIES: usize = 0x4_0000;
+
+/// Maximum number of chars, in all resource names, read from a file.
+const MAX_NAME_CHARS: usize = 0x10_0000;
+
+/// High bit of a resource directory entry field, which flags either a named
+/// entry or a subdirectory.
+const RESOURCE_HIGH_BIT: u32 = 0x8000_0000;
+
+/// Resources of a [PE](https://learn.microsoft.com/en-us/windows/win32/debug/pe-format)
+/// file – an EXE or a DLL –, read straight from the `.rsrc` section of its
+/// bytes, without loading the module.
+///
+/// Unlike [`HINSTANCE::LoadLibrary`](crate::prelude::kernel_Hinstance::LoadLibrary)
+/// followed by
+/// [`HINSTANCE::EnumResourceTypes`](crate::prelude::kernel_Hinstance::EnumResourceTypes),
+/// no native function is called, so files of any bitness can be read, and
+/// nothing from the file is ever executed. The usual source of the bytes is
+/// [`FileMapped::as_slice`](crate::FileMapped::as_slice).
+///
+/// The resources are kept as a type/name/language tree, in the same order of
+/// the directory entries in the file; the data is not copied, but borrowed
+/// from the file bytes.
+///
+/// # Examples
+///
+/// Listing all resources of a DLL:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let f = w::FileMapped::open(
+///     "C:\\Temp\\foo.dll",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+/// let res = w::PeResources::parse(f.as_slice())?;
+///
+/// for (res_type, res_name, res_lang) in res.iter() {
+///     println!("{} {} {} - {} bytes",
+///         res_type, res_name, res_lang.lang_id, res_lang.data.len());
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Reading the version information:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let f = w::FileMapped::open(
+///     "C:\\Temp\\foo.exe",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+/// let res = w::PeResources::parse(f.as_slice())?;
+///
+/// if let Some(ver) = res.find(&w::RtStr::Rt(co::RT::VERSION), &w::IdStr::Id(1)) {
+///     let info = w::VersionInfo::parse(ver.data)?;
+///     println!("{:?}", info.fixed.dwFileVersion());
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct PeResources<'a> {
+	/// Whether the file is a 64-bit PE32+ image, rather than a 32-bit PE32.
+	pub pe32_plus: bool,
+	/// The resource types, first level of the tree.
+	pub types: Vec<PeResourceType<'a>>,
+}
+
+/// A resource type within [`PeResources`](crate::PeResources).
+#[derive(Clone)]
+pub struct PeResourceType<'a> {
+	/// The resource type.
+	pub resource_type: RtStr,
+	/// The resources of this type, second level of the tree.
+	pub names: Vec<PeResourceName<'a>>,
+}
+
+/// A resource name within [`PeResourceType`](crate::PeResourceType).
+#[derive(Clone)]
+pub struct PeResourceName<'a> {
+	/// The resource identifier.
+	pub resource_id: IdStr,
+	/// The language variants of this resource, third level of the tree.
+	pub langs: Vec<PeResourceLang<'a>>,
+}
+
+/// A language variant of a resource within
+/// [`PeResourceName`](crate::PeResourceName), which holds the actual data.
+#[derive(Clone)]
+pub struct PeResourceLang<'a> {
+	/// The resource language.
+	pub lang_id: LANGID,
+	/// Code page declared for the data, usually zero.
+	pub code_page: u32,
+	/// The raw resource data, borrowed from the file bytes.
+	pub data: &'a [u8],
+}
+
+impl<'a> PeResources<'a> {
+	/// Parses the resource directory of the PE file contained in the given
+	/// bytes. A file with no resources results in an empty tree.
+	///
+	/// Since the file may come from an untrusted source, the resource directory
+	/// is read defensively: a subdirectory referenced twice, more than 262,144
+	/// directory entries or more than 1,048,576 chars in the resource names are
+	/// rejected.
+	///
+	/// Fails with [`co::ERROR::BAD_EXE_FORMAT`](crate::co::ERROR::BAD_EXE_FORMAT)
+	/// if the headers, the resource directory or any of the data entries are
+	/// malformed or out of bounds.
+	pub fn parse(data: &'a [u8]) -> SysResult<Self> {
+		let parse = || -> SysResult<Self> {
+			let image = PeImage::parse(data)?;
+			let types = match image.resource_dir()? {
+				None => Vec::default(),
+				Some(rsrc) => RsrcReader::new(&image, rsrc).read_types()?,
+			};
+			Ok(Self { pe32_plus: image.pe32_plus, types })
+		};
+		parse().map_err(|_| co::ERROR::BAD_EXE_FORMAT) // out-of-bounds reads fail with INVALID_DATA
+	}
+
+	/// Returns an iterator over all resources, flattening the tree into its
+	/// type, name and language variant.
+	#[must_use]
+	pub fn iter(&self,
+	) -> impl Iterator<Item = (&RtStr, &IdStr, &PeResourceLang<'a>)> + '_
+	{
+		self.types.iter().flat_map(|ty| {
+			ty.names.iter().flat_map(move |name| {
+				name.langs.iter()
+					.map(move |lang| (&ty.resource_type, &name.resource_id, lang))
+			})
+		})
+	}
+
+	/// Returns the first language variant of the given resource, if any.
+	///
+	/// To pick a specific language, use
+	/// [`PeResources::find_lang`](crate::PeResources::find_lang).
+	#[must_use]
+	pub fn find(&self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+	) -> Option<&PeResourceLang<'a>>
+	{
+		self.find_name(resource_type, resource_id)
+			.and_then(|name| name.langs.first())
+	}
+
+	/// Returns the given language variant of the given resource, if any.
+	#[must_use]
+	pub fn find_lang(&self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+		lang_id: LANGID,
+	) -> Option<&PeResourceLang<'a>>
+	{
+		self.find_name(resource_type, resource_id)
+			.and_then(|name| name.langs.iter().find(|lang| lang.lang_id == lang_id))
+	}
+
+	#[must_use]
+	fn find_name(&self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+	) -> Option<&PeResourceName<'a>>
+	{
+		self.types.iter()
+			.find(|ty| match (&ty.resource_type, resource_type) {
+				(RtStr::Rt(a), RtStr::Rt(b)) => a == b,
+				(RtStr::Str(a), RtStr::Str(b)) => a.to_string().eq_ignore_ascii_case(&b.to_string()),
+				_ => false,
+			})
+			.and_then(|ty| ty.names.iter().find(|name| match (&name.resource_id, resource_id) {
+				(IdStr::Id(a), IdStr::Id(b)) => a == b,
+				(IdStr::Str(a), IdStr::Str(b)) => a.to_string().eq_ignore_ascii_case(&b.to_string()),
+				_ => false,
+			}))
+	}
+}
+
+/// The PE headers needed to locate the resources, with bounds-checked readers
+/// over the file bytes.
+struct PeImage<'a> {
+	data: &'a [u8],
+	pe32_plus: bool,
+	data_dirs: &'a [u8],
+	sections: &'a [u8],
+}
+
+impl<'a> PeImage<'a> {
+	fn parse(data: &'a [u8]) -> SysResult<Self> {
+		const IMAGE_DOS_SIGNATURE: u16 = 0x5a4d; // MZ
+		const IMAGE_NT_SIGNATURE: u32 = 0x0000_4550; // PE\0\0
+		const IMAGE_NT_OPTIONAL_HDR32_MAGIC: u16 = 0x10b;
+		const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20b;
+		const SZ_FILE_HEADER: usize = 20;
+		const SZ_SECTION_HEADER: usize = 40;
+
+		if read_u16(data, 0)? != IMAGE_DOS_SIGNATURE {
+			return Err(co::ERROR::BAD_EXE_FORMAT);
+		}
+		let nt_off = read_u32(data, 0x3c)? as usize; // e_lfanew
+		if read_u32(data, nt_off)? != IMAGE_NT_SIGNATURE {
+			return Err(co::ERROR::BAD_EXE_FORMAT);
+		}
+
+		let file_hdr = nt_off + 4;
+		let num_sections = read_u16(data, file_hdr + 2)? as usize;
+		let sz_opt_hdr = read_u16(data, file_hdr + 16)? as usize;
+		let opt_hdr = file_hdr + SZ_FILE_HEADER;
+
+		let (pe32_plus, dirs_off) = match read_u16(data, opt_hdr)? {
+			IMAGE_NT_OPTIONAL_HDR32_MAGIC => (false, 96),
+			IMAGE_NT_OPTIONAL_HDR64_MAGIC => (true, 112),
+			_ => return Err(co::ERROR::BAD_EXE_FORMAT),
+		};
+		let num_dirs = read_u32(data, opt_hdr + dirs_off - 4)? as usize; // NumberOfRvaAndSizes
+		let num_dirs = num_dirs.min((sz_opt_hdr.saturating_sub(dirs_off)) / 8);
+		let data_dirs = slice_at(data, opt_hdr + dirs_off, num_dirs * 8)?;
+
+		let sections = slice_at(data,
+			opt_hdr + sz_opt_hdr, num_sections * SZ_SECTION_HEADER)?;
+
+		Ok(Self { data, pe32_plus, data_dirs, sections })
+	}
+
+	/// Returns the bytes of the resource directory, from the root directory to
+	/// the end of the resource data directory, bounded by the raw data of its
+	/// section; `None` if the file has no resources.
+	fn resource_dir(&self) -> SysResult<Option<&'a [u8]>> {
+		let idx = IMAGE_DIRECTORY_ENTRY_RESOURCE * 8;
+		if self.data_dirs.len() < idx + 8 {
+			return Ok(None);
+		}
+		let rva = read_u32(self.data_dirs, idx)?;
+		let sz = read_u32(self.data_dirs, idx + 4)?;
+		if rva == 0 || sz == 0 {
+			return Ok(None);
+		}
+		let (off, avail) = self.locate_rva(rva)?;
+		slice_at(self.data, off, (sz as usize).min(avail)).map(Some)
+	}
+
+	/// Converts a relative virtual address into a file offset, making sure the
+	/// given number of bytes is within the raw data of its section.
+	fn rva_to_offset(&self, rva: u32, len: usize) -> SysResult<usize> {
+		let (off, avail) = self.locate_rva(rva)?;
+		if len > avail { // not backed by file data
+			return Err(co::ERROR::BAD_EXE_FORMAT);
+		}
+		Ok(off)
+	}
+
+	/// Converts a relative virtual address into a file offset, returning also
+	/// the number of bytes from there to the end of the raw data of its section,
+	/// or to the end of the file.
+	fn locate_rva(&self, rva: u32) -> SysResult<(usize, usize)> {
+		for sec in self.sections.chunks_exact(40) {
+			let virt_sz = read_u32(sec, 8)?;
+			let virt_addr = read_u32(sec, 12)?;
+			let raw_sz = read_u32(sec, 16)?;
+			let raw_ptr = read_u32(sec, 20)?;
+
+			let sec_sz = virt_sz.max(raw_sz);
+			if rva >= virt_addr && (rva - virt_addr) < sec_sz {
+				let delta = (rva - virt_addr) as usize;
+				let off = raw_ptr as usize + delta;
+				let avail = (raw_sz as usize).saturating_sub(delta)
+					.min(self.data.len().saturating_sub(off));
+				return Ok((off, avail));
+			}
+		}
+		Err(co::ERROR::BAD_EXE_FORMAT)
+	}
+}
+
+/// Reader of the resource directory tree. All the offsets are relative to the
+/// root directory, and bounds-checked against the resource section.
+///
+/// To guard against crafted files, each subdirectory can be read only once –
+/// otherwise a few entries pointing to the same subdirectory would expand into
+/// a huge tree –, and the number of entries and name chars is limited.
+struct RsrcReader<'i, 'a> {
+	image: &'i PeImage<'a>,
+	rsrc: &'a [u8],
+	visited: HashSet<usize>,
+	num_entries: usize,
+	num_name_chars: usize,
+}
+
+impl<'i, 'a> RsrcReader<'i, 'a> {
+	#[must_use]
+	fn new(image: &'i PeImage<'a>, rsrc: &'a [u8]) -> Self {
+		Self { image, rsrc, visited: HashSet::default(), num_entries: 0, num_name_chars: 0 }
+	}
+
+	fn read_types(&mut self) -> SysResult<Vec<PeResourceType<'a>>> {
+		self.read_dir_entries(0)?
+			.into_iter()
+			.map(|(key, sub)| Ok(PeResourceType {
+				resource_type: match self.read_key(key)? {
+					IdStr::Id(id) => RtStr::Rt(unsafe { co::RT::from_raw(id) }),
+					IdStr::Str(s) => RtStr::Str(s),
+				},
+				names: self.read_names(Self::subdir(sub)?)?,
+			}))
+			.collect()
+	}
+
+	fn read_names(&mut self, dir: usize) -> SysResult<Vec<PeResourceName<'a>>> {
+		self.read_dir_entries(dir)?
+			.into_iter()
+			.map(|(key, sub)| Ok(PeResourceName {
+				resource_id: self.read_key(key)?,
+				langs: self.read_langs(Self::subdir(sub)?)?,
+			}))
+			.collect()
+	}
+
+	fn read_langs(&mut self, dir: usize) -> SysResult<Vec<PeResourceLang<'a>>> {
+		self.read_dir_entries(dir)?
+			.into_iter()
+			.map(|(key, entry)| {
+				if key & RESOURCE_HIGH_BIT != 0 || entry & RESOURCE_HIGH_BIT != 0 {
+					return Err(co::ERROR::BAD_EXE_FORMAT); // languages are numeric leaves
+				}
+				let entry_off = entry as usize; // IMAGE_RESOURCE_DATA_ENTRY
+				let data_rva = read_u32(self.rsrc, entry_off)?;
+				let data_sz = read_u32(self.rsrc, entry_off + 4)? as usize;
+				let code_page = read_u32(self.rsrc, entry_off + 8)?;
+
+				let data = if data_sz == 0 {
+					&[][..]
+				} else {
+					let off = self.image.rva_to_offset(data_rva, data_sz)?;
+					slice_at(self.image.data, off, data_sz)?
+				};
+				Ok(PeResourceLang { lang_id: unsafe { LANGID::from_raw(key as _) }, code_page, data })
+			})
+			.collect()
+	}
+
+	/// Reads the entries of an `IMAGE_RESOURCE_DIRECTORY`, returning the raw
+	/// name and offset fields of each one.
+	fn read_dir_entries(&mut self, dir: usize) -> SysResult<Vec<(u32, u32)>> {
+		if !self.visited.insert(dir) {
+			return Err(co::ERROR::BAD_EXE_FORMAT); // subdirectory already read: a loop or a shared one
+		}
+
+		let num_named = read_u16(self.rsrc, dir + 12)? as usize;
+		let num_ids = read_u16(self.rsrc, dir + 14)? as usize;
+		self.num_entries += num_named + num_ids;
+		if self.num_entries > MAX_ENTRIES {
+			return Err(co::ERROR::BAD_EXE_FORMAT);
+		}
+
+		let entries = slice_at(self.rsrc, dir + 16, (num_named + num_ids) * 8)?;
+		entries.chunks_exact(8)
+			.map(|e| Ok((read_u32(e, 0)?, read_u32(e, 4)?)))
+			.collect()
+	}
+
+	/// Reads the name field of a directory entry, which is either a numeric ID
+	/// or an offset to an `IMAGE_RESOURCE_DIR_STRING_U`.
+	fn read_key(&mut self, key: u32) -> SysResult<IdStr> {
+		if key & RESOURCE_HIGH_BIT == 0 {
+			return Ok(IdStr::Id(key as _));
+		}
+		let str_off = (key & !RESOURCE_HIGH_BIT) as usize;
+		let len = read_u16(self.rsrc, str_off)? as usize;
+		self.num_name_chars += len;
+		if self.num_name_chars > MAX_NAME_CHARS {
+			return Err(co::ERROR::BAD_EXE_FORMAT);
+		}
+
+		let chars = slice_at(self.rsrc, str_off + 2, len * 2)?
+			.chunks_exact(2)
+			.map(|b| u16::from_le_bytes([b[0], b[1]]))
+			.collect::<Vec<_>>();
+		Ok(IdStr::Str(WString::from_wchars_slice(&chars)))
+	}
+
+	/// Validates a directory entry pointing to a subdirectory, returning its
+	/// offset.
+	fn subdir(offset: u32) -> SysResult<usize> {
+		if offset & RESOURCE_HIGH_BIT == 0 {
+			Err(co::ERROR::BAD_EXE_FORMAT) // a data entry where a directory was expected
+		} else {
+			Ok((offset & !RESOURCE_HIGH_BIT) as usize)
+		}
+	}
+}
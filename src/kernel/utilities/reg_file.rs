@@ -0,0 +1,326 @@
+use crate::co;
+use crate::decl::*;
+
+/// Header line of the format written by the Windows Registry Editor.
+const HEADER: &str = "Windows Registry Editor Version 5.00";
+
+/// Maximum length of a line with hex data, before it's wrapped.
+const MAX_LINE_LEN: usize = 80;
+
+/// Contents of a `.reg` file, in the textual
+/// [Windows Registry Editor 5.00](https://support.microsoft.com/en-us/topic/how-to-add-modify-or-delete-registry-subkeys-and-values-by-using-a-reg-file-9c7f37cf-a5e9-e1cd-c4fa-2a26218a1a23)
+/// format, with the values mapped onto [`RegistryValue`](crate::RegistryValue).
+///
+/// The text is parsed with [`RegFile::parse`](crate::RegFile::parse), and
+/// written with [`Display`](std::fmt::Display), which uses CRLF line breaks.
+/// Note that the Registry Editor reads and writes `.reg` files encoded as
+/// UTF-16 with a BOM, so the encoding is handled with
+/// [`WString::parse`](crate::WString::parse) and
+/// [`WString::serialize`](crate::WString::serialize).
+///
+/// To read a registry subtree, use
+/// [`HKEY::export_reg_file`](crate::prelude::kernel_Hkey::export_reg_file);
+/// to write the contents into the registry, use
+/// [`HKEY::import_reg_file`](crate::prelude::kernel_Hkey::import_reg_file).
+///
+/// # Examples
+///
+/// Reading a `.reg` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let f = w::FileMapped::open("C:\\Temp\\settings.reg", w::FileAccess::ExistingReadOnly)?;
+/// let text = w::WString::parse(f.as_slice())?.to_string();
+/// let reg_file = w::RegFile::parse(&text)?;
+///
+/// for key in reg_file.keys.iter() {
+///     println!("{}", key.path);
+///     for val in key.values.iter() {
+///         println!("  {:?} = {:?}", val.name, val.data);
+///     }
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Writing a `.reg` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let reg_file = w::RegFile {
+///     keys: vec![
+///         w::RegFileKey {
+///             path: "HKEY_CURRENT_USER\\Software\\My Company".to_owned(),
+///             delete: false,
+///             values: vec![
+///                 w::RegFileValue {
+///                     name: Some("Color".to_owned()),
+///                     data: Some(w::RegistryValue::Sz("blue".to_owned())),
+///                 },
+///             ],
+///         },
+///     ],
+/// };
+///
+/// let bytes = w::WString::from_str(reg_file.to_string())
+///     .serialize(w::Encoding::Utf16le, true)?;
+/// let f = w::File::open("C:\\Temp\\settings.reg", w::FileAccess::CreateRW)?;
+/// f.write(&bytes)?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegFile {
+	/// The key sections, in the order they appear in the file.
+	pub keys: Vec<RegFileKey>,
+}
+
+/// A key section of a [`RegFile`](crate::RegFile), like
+/// `[HKEY_CURRENT_USER\Software\My Company]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegFileKey {
+	/// Full path of the key, starting with the name of the predefined key, like
+	/// `HKEY_CURRENT_USER\Software\My Company`.
+	pub path: String,
+	/// If `true`, the key and all its subkeys are deleted, which is written as
+	/// `[-HKEY_CURRENT_USER\Software\My Company]`. In this case, the values are
+	/// ignored.
+	pub delete: bool,
+	/// The values of the key.
+	pub values: Vec<RegFileValue>,
+}
+
+/// A value of a [`RegFileKey`](crate::RegFileKey), like `"Color"="blue"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegFileValue {
+	/// Name of the value; `None` is the default value of the key, which is
+	/// written as `@`.
+	pub name: Option<String>,
+	/// Data of the value; `None` means the value is deleted, which is written
+	/// as `"Color"=-`.
+	pub data: Option<RegistryValue>,
+}
+
+impl std::fmt::Display for RegFile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}\r\n", HEADER)?;
+		for key in self.keys.iter() {
+			if key.delete {
+				write!(f, "\r\n[-{}]\r\n", key.path)?;
+				continue;
+			}
+			write!(f, "\r\n[{}]\r\n", key.path)?;
+			for val in key.values.iter() {
+				write!(f, "{}\r\n", Self::format_value(val))?;
+			}
+		}
+		write!(f, "\r\n")
+	}
+}
+
+impl RegFile {
+	/// Parses the text of a `.reg` file.
+	///
+	/// The text must start with the `Windows Registry Editor Version 5.00`
+	/// header. Comments, starting with `;`, and blank lines are ignored, and
+	/// lines ending with a backslash are joined with the next one.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the text is malformed, or if a key to be deleted has no subkey, like
+	/// `[-HKEY_CURRENT_USER]`, since it would delete the whole predefined key.
+	pub fn parse(text: &str) -> SysResult<Self> {
+		let text = text.strip_prefix('\u{feff}').unwrap_or(text); // BOM, if any
+		let mut lines = Self::logical_lines(text);
+
+		match lines.next() {
+			Some(header) if header == HEADER => {},
+			_ => return Err(co::ERROR::INVALID_DATA),
+		}
+
+		let mut keys = Vec::<RegFileKey>::default();
+		for line in lines {
+			if line.is_empty() || line.starts_with(';') {
+				continue;
+			}
+
+			if let Some(inner) = line.strip_prefix('[') {
+				let inner = inner.strip_suffix(']')
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				let (path, delete) = match inner.strip_prefix('-') {
+					Some(path) => (path, true),
+					None => (inner, false),
+				};
+				if path.is_empty()
+					|| delete && path.split_once('\\').is_none_or(|(_, sub_key)|
+						sub_key.trim_matches(|ch: char| ch == '\\' || ch.is_whitespace()).is_empty())
+				{
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				keys.push(RegFileKey { path: path.to_owned(), delete, values: Vec::default() });
+			} else {
+				let key = keys.last_mut() // a value must be inside a key section
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				key.values.push(Self::parse_value(&line)?);
+			}
+		}
+
+		Ok(Self { keys })
+	}
+
+	/// Splits the text into trimmed lines, joining the ones ending with a
+	/// backslash.
+	fn logical_lines(text: &str) -> impl Iterator<Item = String> + '_ {
+		let mut physical = text.lines();
+		std::iter::from_fn(move || {
+			let mut line = physical.next()?.trim().to_owned();
+			while line.ends_with('\\') && !line.starts_with(';') {
+				line.pop();
+				match physical.next() {
+					Some(next) => line.push_str(next.trim()),
+					None => break,
+				}
+			}
+			Some(line)
+		})
+	}
+
+	fn parse_value(line: &str) -> SysResult<RegFileValue> {
+		let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+			(None, rest)
+		} else {
+			let (name, rest) = Self::parse_quoted(line)?;
+			(Some(name), rest)
+		};
+
+		let data = rest.trim_start()
+			.strip_prefix('=')
+			.ok_or(co::ERROR::INVALID_DATA)?
+			.trim();
+
+		let data = if data == "-" {
+			None
+		} else if data.starts_with('"') {
+			let (s, rest) = Self::parse_quoted(data)?;
+			if !rest.trim().is_empty() {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			Some(RegistryValue::Sz(s))
+		} else if let Some(hex) = data.strip_prefix("dword:") {
+			if hex.is_empty() || hex.len() > 8 {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			Some(RegistryValue::Dword(
+				u32::from_str_radix(hex, 16).map_err(|_| co::ERROR::INVALID_DATA)?,
+			))
+		} else if let Some(hex) = data.strip_prefix("hex:") {
+			Some(RegistryValue::Binary(Self::parse_hex_bytes(hex)?))
+		} else if let Some(typed) = data.strip_prefix("hex(") {
+			let (reg_type, hex) = typed.split_once("):")
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			let reg_type = u32::from_str_radix(reg_type, 16)
+				.map_err(|_| co::ERROR::INVALID_DATA)?;
+			Some(RegistryValue::try_from((
+				&Self::parse_hex_bytes(hex)?[..],
+				unsafe { co::REG::from_raw(reg_type) },
+			))?)
+		} else {
+			return Err(co::ERROR::INVALID_DATA);
+		};
+
+		Ok(RegFileValue { name, data })
+	}
+
+	/// Parses a string between double quotes, with `\\` and `\"` escapes,
+	/// returning the string and the remaining text.
+	fn parse_quoted(text: &str) -> SysResult<(String, &str)> {
+		let mut chars = text.strip_prefix('"')
+			.ok_or(co::ERROR::INVALID_DATA)?
+			.char_indices();
+		let mut s = String::default();
+
+		while let Some((_, ch)) = chars.next() {
+			match ch {
+				'"' => return Ok((s, chars.as_str())),
+				'\\' => match chars.clone().next() {
+					Some((_, next)) if next == '\\' || next == '"' => {
+						s.push(next);
+						chars.next();
+					},
+					_ => s.push(ch), // lone backslash is kept
+				},
+				_ => s.push(ch),
+			}
+		}
+		Err(co::ERROR::INVALID_DATA) // no closing quote
+	}
+
+	/// Parses comma-separated hex bytes, like `41,00,42,00`.
+	fn parse_hex_bytes(text: &str) -> SysResult<Vec<u8>> {
+		let text = text.trim();
+		if text.is_empty() {
+			return Ok(Vec::default());
+		}
+		text.trim_end_matches(',') // tolerate a trailing comma
+			.split(',')
+			.map(|b| {
+				let b = b.trim();
+				if b.is_empty() || b.len() > 2 {
+					Err(co::ERROR::INVALID_DATA)
+				} else {
+					u8::from_str_radix(b, 16).map_err(|_| co::ERROR::INVALID_DATA)
+				}
+			})
+			.collect()
+	}
+
+	#[must_use]
+	fn format_value(val: &RegFileValue) -> String {
+		let name = match &val.name {
+			Some(name) => Self::format_quoted(name),
+			None => "@".to_owned(),
+		};
+
+		match &val.data {
+			None => format!("{}=-", name),
+			Some(RegistryValue::Sz(s)) if !s.contains(['\r', '\n']) =>
+				format!("{}={}", name, Self::format_quoted(s)),
+			Some(RegistryValue::Dword(n)) => format!("{}=dword:{:08x}", name, n),
+			Some(RegistryValue::Binary(b)) => Self::format_hex(format!("{}=hex:", name), b),
+			Some(RegistryValue::Other(reg_type, b)) => Self::format_hex( // undecodable data, kept raw
+				format!("{}=hex({:x}):", name, reg_type.raw()),
+				b,
+			),
+			Some(other) => Self::format_hex(
+				format!("{}=hex({:x}):", name, other.reg_type().raw()),
+				&other.to_bytes(),
+			),
+		}
+	}
+
+	#[must_use]
+	fn format_quoted(s: &str) -> String {
+		format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+	}
+
+	/// Writes the bytes after the given prefix, wrapping the lines like the
+	/// Registry Editor does.
+	#[must_use]
+	fn format_hex(prefix: String, data: &[u8]) -> String {
+		let mut out = String::default();
+		let mut line = prefix;
+
+		for (idx, b) in data.iter().enumerate() {
+			let is_last = idx == data.len() - 1;
+			let piece = if is_last { format!("{:02x}", b) } else { format!("{:02x},", b) };
+			if line.len() + piece.len() + 1 > MAX_LINE_LEN { // room for the backslash
+				out.push_str(&line);
+				out.push_str("\\\r\n");
+				line = "  ".to_owned();
+			}
+			line.push_str(&piece);
+		}
+
+		out.push_str(&line);
+		out
+	}
+}
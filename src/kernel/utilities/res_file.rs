@@ -0,0 +1,295 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::privs::bytes::*;
+
+/// Default memory flags written by the resource compiler: `MOVEABLE`, `PURE`
+/// and `DISCARDABLE`.
+const DEFAULT_MEMORY_FLAGS: u16 = 0x1030;
+
+/// Contents of a compiled
+/// [`.res` file](https://learn.microsoft.com/en-us/windows/win32/menurc/resource-file-formats),
+/// in the 32-bit format, which is a sequence of
+/// [`RESOURCEHEADER`](https://learn.microsoft.com/en-us/windows/win32/menurc/resourceheader)
+/// entries, each one followed by its data.
+///
+/// The bytes are parsed with [`ResFile::parse`](crate::ResFile::parse), and
+/// written with [`ResFile::serialize`](crate::ResFile::serialize). The entries
+/// use the same identifiers taken by
+/// [`HUPDATERSRC::UpdateResource`](crate::prelude::kernel_Hupdatersrc::UpdateResource),
+/// so a `.res` file can be merged, patched or written into an executable
+/// without the resource compiler.
+///
+/// # Examples
+///
+/// Patching a `.res` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let f = w::FileMapped::open("C:\\Temp\\app.res", w::FileAccess::ExistingReadOnly)?;
+/// let mut res = w::ResFile::parse(f.as_slice())?;
+/// drop(f);
+///
+/// res.set(w::ResEntry::new(
+///     w::RtStr::Rt(co::RT::RCDATA),
+///     w::IdStr::Id(101),
+///     w::LANGID::USER_DEFAULT,
+///     b"hello".to_vec(),
+/// ));
+///
+/// let f = w::File::open("C:\\Temp\\app.res", w::FileAccess::ExistingRW)?;
+/// f.set_size(0)?;
+/// f.write(&res.serialize())?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResFile {
+	/// The resources, in the order they appear in the file. The empty entry
+	/// which starts every 32-bit `.res` file is not included.
+	pub entries: Vec<ResEntry>,
+}
+
+/// A resource of a [`ResFile`](crate::ResFile).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResEntry {
+	/// The resource type.
+	pub resource_type: RtStr,
+	/// The resource identifier.
+	pub resource_id: IdStr,
+	/// The resource language.
+	pub lang_id: LANGID,
+	/// Memory flags, like `MOVEABLE` (`0x0010`), `PURE` (`0x0020`) and
+	/// `DISCARDABLE` (`0x1000`). Ignored by 32-bit Windows.
+	pub memory_flags: u16,
+	/// Version of the data format, usually zero.
+	pub data_version: u32,
+	/// User-defined version, set with the `VERSION` statement.
+	pub version: u32,
+	/// User-defined characteristics, set with the `CHARACTERISTICS`
+	/// statement.
+	pub characteristics: u32,
+	/// The raw resource data.
+	pub data: Vec<u8>,
+}
+
+impl ResEntry {
+	/// Creates a new resource with the given data, and the same memory flags
+	/// used by the resource compiler.
+	#[must_use]
+	pub fn new(
+		resource_type: RtStr,
+		resource_id: IdStr,
+		lang_id: LANGID,
+		data: Vec<u8>,
+	) -> Self
+	{
+		Self {
+			resource_type,
+			resource_id,
+			lang_id,
+			memory_flags: DEFAULT_MEMORY_FLAGS,
+			data_version: 0,
+			version: 0,
+			characteristics: 0,
+			data,
+		}
+	}
+
+	/// Tells whether the entry has the given type, identifier and language.
+	#[must_use]
+	pub fn is(&self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+		lang_id: LANGID,
+	) -> bool
+	{
+		self.resource_type == *resource_type
+			&& self.resource_id == *resource_id
+			&& self.lang_id == lang_id
+	}
+}
+
+impl ResFile {
+	/// Parses the bytes of a 32-bit `.res` file.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is malformed, which includes 16-bit `.res` files.
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let mut entries = Vec::<ResEntry>::default();
+		let mut off = 0;
+		let mut is_first = true;
+
+		while off < data.len() {
+			let data_sz = read_u32(data, off)? as usize;
+			let header_sz = read_u32(data, off + 4)? as usize;
+			let header = slice_at(data, off, header_sz)?;
+
+			let (resource_type, pos) = read_id(header, 8)?;
+			let (resource_id, pos) = read_id(header, pos)?;
+			let pos = align4(pos);
+			let fixed = slice_at(header, pos, 16)?; // the rest of RESOURCEHEADER
+
+			let entry_data = slice_at(data, off + header_sz, data_sz)?;
+			off = align4(off + header_sz + data_sz);
+
+			let resource_type = match resource_type {
+				IdStr::Id(id) => RtStr::Rt(unsafe { co::RT::from_raw(id) }),
+				IdStr::Str(s) => RtStr::Str(s),
+			};
+
+			if is_first {
+				is_first = false;
+				if data_sz == 0 && resource_type == RtStr::Rt(unsafe { co::RT::from_raw(0) }) {
+					continue; // the empty entry which flags a 32-bit file
+				}
+			}
+
+			entries.push(ResEntry {
+				resource_type,
+				resource_id,
+				data_version: read_u32(fixed, 0)?,
+				memory_flags: read_u16(fixed, 4)?,
+				lang_id: unsafe { LANGID::from_raw(read_u16(fixed, 6)?) },
+				version: read_u32(fixed, 8)?,
+				characteristics: read_u32(fixed, 12)?,
+				data: entry_data.to_vec(),
+			});
+		}
+
+		Ok(Self { entries })
+	}
+
+	/// Serializes the entries into the bytes of a 32-bit `.res` file, which
+	/// can be parsed back with [`ResFile::parse`](crate::ResFile::parse).
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::default();
+		Self::write_entry(&mut buf, &ResEntry {
+			resource_type: RtStr::Rt(unsafe { co::RT::from_raw(0) }),
+			resource_id: IdStr::Id(0),
+			lang_id: unsafe { LANGID::from_raw(0) },
+			memory_flags: 0,
+			data_version: 0,
+			version: 0,
+			characteristics: 0,
+			data: Vec::default(),
+		});
+		self.entries.iter()
+			.for_each(|entry| Self::write_entry(&mut buf, entry));
+		buf
+	}
+
+	/// Returns the entry with the given type, identifier and language, if any.
+	#[must_use]
+	pub fn find(&self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+		lang_id: LANGID,
+	) -> Option<&ResEntry>
+	{
+		self.entries.iter()
+			.find(|entry| entry.is(resource_type, resource_id, lang_id))
+	}
+
+	/// Returns the entry with the given type, identifier and language, if any.
+	#[must_use]
+	pub fn find_mut(&mut self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+		lang_id: LANGID,
+	) -> Option<&mut ResEntry>
+	{
+		self.entries.iter_mut()
+			.find(|entry| entry.is(resource_type, resource_id, lang_id))
+	}
+
+	/// Replaces the entry with the same type, identifier and language, or
+	/// appends it if there is none.
+	pub fn set(&mut self, entry: ResEntry) {
+		match self.find_mut(&entry.resource_type, &entry.resource_id, entry.lang_id) {
+			Some(existing) => *existing = entry,
+			None => self.entries.push(entry),
+		}
+	}
+
+	/// Removes the entry with the given type, identifier and language,
+	/// returning it, if any.
+	pub fn remove(&mut self,
+		resource_type: &RtStr,
+		resource_id: &IdStr,
+		lang_id: LANGID,
+	) -> Option<ResEntry>
+	{
+		self.entries.iter()
+			.position(|entry| entry.is(resource_type, resource_id, lang_id))
+			.map(|idx| self.entries.remove(idx))
+	}
+
+	/// Adds all the entries of another `ResFile` with
+	/// [`ResFile::set`](crate::ResFile::set), so the entries of `other` take
+	/// precedence.
+	pub fn merge(&mut self, other: ResFile) {
+		other.entries.into_iter()
+			.for_each(|entry| self.set(entry));
+	}
+
+	fn write_entry(buf: &mut Vec<u8>, entry: &ResEntry) {
+		let start = buf.len();
+		buf.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+		buf.extend_from_slice(&0u32.to_le_bytes()); // header size, patched below
+
+		match &entry.resource_type {
+			RtStr::Rt(rt) => write_id(buf, &IdStr::Id(rt.raw())),
+			RtStr::Str(s) => write_id(buf, &IdStr::Str(s.clone())),
+		}
+		write_id(buf, &entry.resource_id);
+		pad4(buf);
+
+		buf.extend_from_slice(&entry.data_version.to_le_bytes());
+		buf.extend_from_slice(&entry.memory_flags.to_le_bytes());
+		buf.extend_from_slice(&entry.lang_id.raw().to_le_bytes());
+		buf.extend_from_slice(&entry.version.to_le_bytes());
+		buf.extend_from_slice(&entry.characteristics.to_le_bytes());
+
+		let header_sz = (buf.len() - start) as u32;
+		buf[start + 4..start + 8].copy_from_slice(&header_sz.to_le_bytes());
+
+		buf.extend_from_slice(&entry.data);
+		pad4(buf);
+	}
+}
+
+/// Reads a type or name field, which is either `0xffff` followed by an
+/// ordinal, or a null-terminated UTF-16 string. Returns the identifier and the
+/// position right after it.
+fn read_id(header: &[u8], pos: usize) -> SysResult<(IdStr, usize)> {
+	if read_u16(header, pos)? == 0xffff {
+		return Ok((IdStr::Id(read_u16(header, pos + 2)?), pos + 4));
+	}
+
+	let mut chars = Vec::<u16>::default();
+	let mut pos = pos;
+	loop {
+		let ch = read_u16(header, pos)?;
+		pos += 2;
+		if ch == 0x0000 {
+			break;
+		}
+		chars.push(ch);
+	}
+	Ok((IdStr::Str(WString::from_wchars_slice(&chars)), pos))
+}
+
+fn write_id(buf: &mut Vec<u8>, id: &IdStr) {
+	match id {
+		IdStr::Id(id) => {
+			buf.extend_from_slice(&0xffffu16.to_le_bytes());
+			buf.extend_from_slice(&id.to_le_bytes());
+		},
+		IdStr::Str(s) => {
+			s.to_string().encode_utf16()
+				.chain(std::iter::once(0x0000))
+				.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+		},
+	}
+}
@@ -0,0 +1,31 @@
+use crate::co;
+
+/// Configuration of a service, returned by
+/// [`HSERVICE::QueryServiceConfig`](crate::prelude::kernel_Hservice::QueryServiceConfig).
+///
+/// This is an owned version of the
+/// [`QUERY_SERVICE_CONFIG`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-query_service_configw)
+/// struct.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServiceConfig {
+	/// Type of the service.
+	pub service_type: co::SERVICE_TYPE,
+	/// When the service is started.
+	pub start_type: co::SERVICE_START,
+	/// Severity of the error if the service fails to start during boot.
+	pub error_control: co::SERVICE_ERROR,
+	/// Command line of the service executable, including its arguments.
+	pub binary_path_name: String,
+	/// Load ordering group of the service; empty if none.
+	pub load_order_group: String,
+	/// Tag of the service within its load ordering group; zero if none.
+	pub tag_id: u32,
+	/// Services or groups which must start before this service. Group names
+	/// are prefixed with `+`.
+	pub dependencies: Vec<String>,
+	/// Account under which the service runs, like `LocalSystem` or
+	/// `NT AUTHORITY\LocalService`.
+	pub service_start_name: String,
+	/// Name of the service shown by user interface programs.
+	pub display_name: String,
+}
@@ -0,0 +1,507 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::{Arc, Mutex, PoisonError, mpsc};
+
+use crate::co;
+use crate::decl::*;
+use crate::kernel::{ffi, ffi_types::*, privs::*};
+use crate::prelude::*;
+
+/// Wait hint reported when entering a pending state, in milliseconds.
+const DEFAULT_WAIT_HINT: u32 = 5_000;
+
+type ServiceMainFn = dyn Fn(ServiceContext) -> SysResult<()> + Send + Sync;
+
+/// A service added to a [`ServiceHost`](crate::ServiceHost).
+struct ServiceEntry {
+	name: String,
+	accept: co::SERVICE_ACCEPT,
+	main: Arc<ServiceMainFn>,
+}
+
+/// Services of the running dispatcher, which are looked up by `service_main`,
+/// since it receives no context.
+static SERVICES: Mutex<Vec<ServiceEntry>> = Mutex::new(Vec::new());
+
+/// [`SERVICE_TABLE_ENTRY`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_table_entryw)
+/// struct.
+#[repr(C)]
+struct SERVICE_TABLE_ENTRY {
+	lpServiceName: PCSTR,
+	lpServiceProc: PFUNC,
+}
+
+/// Runtime which hosts one or more Windows services in the current process,
+/// calling
+/// [`StartServiceCtrlDispatcher`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nf-winsvc-startservicectrldispatcherw).
+///
+/// Each service is a closure, which runs in its own thread and receives a
+/// [`ServiceContext`](crate::ServiceContext). The
+/// [`SERVICE_STATUS`](crate::SERVICE_STATUS) is reported automatically:
+///
+/// * [`START_PENDING`](crate::co::SERVICE_STATE::START_PENDING) before the
+///   closure is called;
+/// * [`RUNNING`](crate::co::SERVICE_STATE::RUNNING) when the closure calls
+///   [`set_running`](crate::ServiceContext::set_running), or when it first
+///   waits for an event;
+/// * [`STOP_PENDING`](crate::co::SERVICE_STATE::STOP_PENDING) as soon as a
+///   stop, shutdown or pre-shutdown control is received;
+/// * [`PAUSE_PENDING`](crate::co::SERVICE_STATE::PAUSE_PENDING) and
+///   [`CONTINUE_PENDING`](crate::co::SERVICE_STATE::CONTINUE_PENDING) as soon
+///   as the control is received, then
+///   [`PAUSED`](crate::co::SERVICE_STATE::PAUSED) and
+///   [`RUNNING`](crate::co::SERVICE_STATE::RUNNING) when the closure waits for
+///   the next event;
+/// * [`STOPPED`](crate::co::SERVICE_STATE::STOPPED) when the closure returns,
+///   with the error code, if any, as the exit code; if the closure panics,
+///   the exit code is
+///   [`co::ERROR::PROCESS_ABORTED`](crate::co::ERROR::PROCESS_ABORTED).
+///
+/// If the Service Control Manager starts a service which wasn't added, it's
+/// stopped with
+/// [`co::ERROR::SERVICE_NOT_IN_EXE`](crate::co::ERROR::SERVICE_NOT_IN_EXE).
+///
+/// Interrogate controls are answered automatically; all other controls are
+/// delivered to the closure as [`ServiceEvent`](crate::ServiceEvent).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// w::ServiceHost::new()
+///     .service(
+///         "MyService",
+///         co::SERVICE_ACCEPT::STOP | co::SERVICE_ACCEPT::SHUTDOWN,
+///         |svc| {
+///             // initialization...
+///             svc.set_running()?;
+///
+///             loop {
+///                 match svc.recv()? {
+///                     w::ServiceEvent::Stop | w::ServiceEvent::Shutdown => break,
+///                     _ => {},
+///                 }
+///             }
+///
+///             // cleanup...
+///             Ok(())
+///         },
+///     )
+///     .run()?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Default)]
+pub struct ServiceHost {
+	services: Vec<ServiceEntry>,
+}
+
+impl ServiceHost {
+	/// Creates a new host, without services.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a service, which accepts the given controls, and whose entry point
+	/// is `main`.
+	///
+	/// If the host has only one service, it runs as
+	/// [`co::SERVICE_TYPE::WIN32_OWN_PROCESS`](crate::co::SERVICE_TYPE::WIN32_OWN_PROCESS),
+	/// otherwise as
+	/// [`co::SERVICE_TYPE::WIN32_SHARE_PROCESS`](crate::co::SERVICE_TYPE::WIN32_SHARE_PROCESS).
+	pub fn service<F>(&mut self,
+		name: &str,
+		accept: co::SERVICE_ACCEPT,
+		main: F,
+	) -> &mut Self
+		where F: Fn(ServiceContext) -> SysResult<()> + Send + Sync + 'static,
+	{
+		self.services.push(ServiceEntry {
+			name: name.to_owned(),
+			accept,
+			main: Arc::new(main),
+		});
+		self
+	}
+
+	/// Connects the main thread to the Service Control Manager, and blocks
+	/// until all services stop. The services are moved out of the host.
+	///
+	/// Fails with
+	/// [`co::ERROR::FAILED_SERVICE_CONTROLLER_CONNECT`](crate::co::ERROR::FAILED_SERVICE_CONTROLLER_CONNECT)
+	/// if the process was not started by the Service Control Manager, like
+	/// when running from a console, and with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER) if
+	/// there are no services.
+	pub fn run(&mut self) -> SysResult<()> {
+		if self.services.is_empty() {
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		let names = self.services.iter()
+			.map(|entry| WString::from_str(&entry.name))
+			.collect::<Vec<_>>(); // must outlive the dispatcher
+		let mut table = names.iter()
+			.map(|name| SERVICE_TABLE_ENTRY {
+				lpServiceName: name.as_ptr(),
+				lpServiceProc: service_main as _,
+			})
+			.collect::<Vec<_>>();
+		table.push(SERVICE_TABLE_ENTRY { // terminator
+			lpServiceName: std::ptr::null(),
+			lpServiceProc: std::ptr::null(),
+		});
+
+		*SERVICES.lock().unwrap() = std::mem::take(&mut self.services);
+		let res = bool_to_sysresult(
+			unsafe { ffi::StartServiceCtrlDispatcherW(table.as_ptr() as _) },
+		);
+		SERVICES.lock().unwrap().clear();
+		res
+	}
+}
+
+/// Control received by a service hosted by
+/// [`ServiceHost`](crate::ServiceHost), delivered through
+/// [`ServiceContext::recv`](crate::ServiceContext::recv).
+///
+/// This is an owned version of [`SvcCtl`](crate::SvcCtl), whose data is valid
+/// only within the handler. Interrogate controls are answered by the host, and
+/// are not delivered.
+#[derive(Clone, Copy)]
+pub enum ServiceEvent {
+	Continue,
+	NetBindAdd,
+	NetBindDisable,
+	NetBindEnable,
+	NetBindRemove,
+	ParamChange,
+	Pause,
+	PreShutdown,
+	Shutdown,
+	Stop,
+
+	DeviceEvent(co::DBT),
+	HardwareProfileChange(co::DBT),
+	PowerEvent(co::PBT),
+	SessionChange(co::WTS, WTSSESSION_NOTIFICATION),
+	TimeChange(SERVICE_TIMECHANGE_INFO),
+	TriggerEvent,
+	UserModeReboot,
+
+	UserDefined(u8, u32),
+}
+
+impl ServiceEvent {
+	/// Copies the data of the control, returning `None` for interrogate.
+	#[must_use]
+	fn from_svc_ctl(ctl: &SvcCtl) -> Option<Self> {
+		Some(match ctl {
+			SvcCtl::Continue => Self::Continue,
+			SvcCtl::Interrogate => return None,
+			SvcCtl::NetBindAdd => Self::NetBindAdd,
+			SvcCtl::NetBindDisable => Self::NetBindDisable,
+			SvcCtl::NetBindEnable => Self::NetBindEnable,
+			SvcCtl::NetBindRemove => Self::NetBindRemove,
+			SvcCtl::ParamChange => Self::ParamChange,
+			SvcCtl::Pause => Self::Pause,
+			SvcCtl::PreShutdown => Self::PreShutdown,
+			SvcCtl::Shutdown => Self::Shutdown,
+			SvcCtl::Stop => Self::Stop,
+
+			SvcCtl::DeviceEvent(dbt, _) => Self::DeviceEvent(*dbt),
+			SvcCtl::HardwareProfileChange(dbt) => Self::HardwareProfileChange(*dbt),
+			SvcCtl::PowerEvent(pe) => Self::PowerEvent(match pe {
+				SvcCtlPowerEvent::StatusChange => co::PBT::APMPOWERSTATUSCHANGE,
+				SvcCtlPowerEvent::ResumeAutomatic => co::PBT::APMRESUMEAUTOMATIC,
+				SvcCtlPowerEvent::ResumeSuspend => co::PBT::APMRESUMESUSPEND,
+				SvcCtlPowerEvent::Suspend => co::PBT::APMSUSPEND,
+				SvcCtlPowerEvent::PowerSettingChange(_) => co::PBT::POWERSETTINGCHANGE,
+			}),
+			SvcCtl::SessionChange(wts, notif) => Self::SessionChange(*wts, **notif),
+			SvcCtl::TimeChange(info) => Self::TimeChange(**info),
+			SvcCtl::TriggerEvent => Self::TriggerEvent,
+			SvcCtl::UserModeReboot => Self::UserModeReboot,
+
+			SvcCtl::UserDefined(control, event_type, _) => Self::UserDefined(*control, *event_type),
+		})
+	}
+}
+
+/// Passed to each service hosted by [`ServiceHost`](crate::ServiceHost), to
+/// receive the controls and report the status.
+pub struct ServiceContext {
+	name: String,
+	args: Vec<String>,
+	events: mpsc::Receiver<ServiceEvent>,
+	status: Arc<Mutex<StatusReporter>>,
+}
+
+impl ServiceContext {
+	/// Returns the name of the service.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns the arguments passed to the service when it was started, not
+	/// including the service name.
+	#[must_use]
+	pub fn args(&self) -> &[String] {
+		&self.args
+	}
+
+	/// Returns the last reported state.
+	#[must_use]
+	pub fn state(&self) -> co::SERVICE_STATE {
+		self.status.lock().unwrap().status.dwCurrentState
+	}
+
+	/// Reports that the initialization is complete, or that the service
+	/// resumed after a pause. Does nothing in other states.
+	pub fn set_running(&self) -> SysResult<()> {
+		let mut status = self.status.lock().unwrap();
+		match status.status.dwCurrentState {
+			co::SERVICE_STATE::START_PENDING
+				| co::SERVICE_STATE::CONTINUE_PENDING => status.set(co::SERVICE_STATE::RUNNING),
+			_ => Ok(()),
+		}
+	}
+
+	/// While in a pending state, reports that a long operation is making
+	/// progress, by incrementing the checkpoint, and sets the time, in
+	/// milliseconds, until the next checkpoint or state change. Does nothing in
+	/// other states.
+	///
+	/// Without it, the Service Control Manager may consider the service hung
+	/// after the default wait hint of 5 seconds.
+	pub fn checkpoint(&self, wait_hint: u32) -> SysResult<()> {
+		self.status.lock().unwrap().checkpoint(wait_hint)
+	}
+
+	/// Blocks until a control is received.
+	///
+	/// Before waiting, completes the current pending state: start and continue
+	/// pending become running, and pause pending becomes paused. Stop pending
+	/// remains until the service returns.
+	pub fn recv(&self) -> SysResult<ServiceEvent> {
+		self.complete_pending()?;
+		self.events.recv()
+			.map_err(|_| co::ERROR::BROKEN_PIPE) // handler is never dropped
+	}
+
+	/// Waits until a control is received, or until the timeout elapses, in
+	/// which case returns `None`.
+	///
+	/// Before waiting, completes the current pending state, like
+	/// [`recv`](crate::ServiceContext::recv).
+	pub fn recv_timeout(&self, milliseconds: u32) -> SysResult<Option<ServiceEvent>> {
+		self.complete_pending()?;
+		match self.events.recv_timeout(std::time::Duration::from_millis(milliseconds as _)) {
+			Ok(event) => Ok(Some(event)),
+			Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+			Err(mpsc::RecvTimeoutError::Disconnected) => Err(co::ERROR::BROKEN_PIPE),
+		}
+	}
+
+	fn complete_pending(&self) -> SysResult<()> {
+		let mut status = self.status.lock().unwrap();
+		match status.status.dwCurrentState {
+			co::SERVICE_STATE::START_PENDING
+				| co::SERVICE_STATE::CONTINUE_PENDING => status.set(co::SERVICE_STATE::RUNNING),
+			co::SERVICE_STATE::PAUSE_PENDING => status.set(co::SERVICE_STATE::PAUSED),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Keeps the current status, shared between the service and its control
+/// handler.
+struct StatusReporter {
+	hss: HSERVICESTATUS,
+	status: SERVICE_STATUS,
+	accept: co::SERVICE_ACCEPT,
+}
+
+impl StatusReporter {
+	/// Reports a new state, with a first checkpoint and the default wait hint
+	/// if it's pending. No controls are accepted while starting or stopping.
+	fn set(&mut self, state: co::SERVICE_STATE) -> SysResult<()> {
+		let pending = matches!(state,
+			co::SERVICE_STATE::START_PENDING
+				| co::SERVICE_STATE::STOP_PENDING
+				| co::SERVICE_STATE::PAUSE_PENDING
+				| co::SERVICE_STATE::CONTINUE_PENDING);
+
+		self.status.dwCurrentState = state;
+		self.status.dwCheckPoint = if pending { 1 } else { 0 };
+		self.status.dwWaitPoint = if pending { DEFAULT_WAIT_HINT } else { 0 };
+		self.status.dwControlsAccepted = match state {
+			co::SERVICE_STATE::START_PENDING
+				| co::SERVICE_STATE::STOP_PENDING
+				| co::SERVICE_STATE::STOPPED => co::SERVICE_ACCEPT::default(),
+			_ => self.accept,
+		};
+		self.report()
+	}
+
+	fn checkpoint(&mut self, wait_hint: u32) -> SysResult<()> {
+		if self.status.dwCheckPoint == 0 { // not pending
+			return Ok(());
+		}
+		self.status.dwCheckPoint += 1;
+		self.status.dwWaitPoint = wait_hint;
+		self.report()
+	}
+
+	fn report(&mut self) -> SysResult<()> {
+		self.hss.SetServiceStatus(&mut self.status)
+	}
+}
+
+/// [`LPSERVICE_MAIN_FUNCTIONW`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nc-winsvc-lpservice_main_functionw)
+/// callback, shared by all services; the first argument is the service name.
+extern "system" fn service_main(argc: u32, argv: *mut PSTR) {
+	let mut args = (0..argc as usize)
+		.map(|i| unsafe { WString::from_wchars_nullt(*argv.add(i)) }.to_string())
+		.collect::<Vec<_>>();
+
+	let (entry, service_type) = {
+		let services = SERVICES.lock().unwrap();
+		let entry = args.first()
+			.and_then(|name| services.iter().find(|entry| entry.name.eq_ignore_ascii_case(name)))
+			.or_else(|| if services.len() == 1 { services.first() } else { None })
+			.map(|entry| (entry.name.clone(), entry.accept, entry.main.clone()));
+		let service_type = if services.len() == 1 {
+			co::SERVICE_TYPE::WIN32_OWN_PROCESS
+		} else {
+			co::SERVICE_TYPE::WIN32_SHARE_PROCESS
+		};
+		(entry, service_type)
+	};
+
+	let (name, accept, main) = match entry {
+		Some(entry) => entry,
+		None => { // not one of our services
+			if let Some(name) = args.first() {
+				let _ = stop_not_in_exe(name, service_type);
+			}
+			return;
+		},
+	};
+	if !args.is_empty() {
+		args.remove(0); // service name
+	}
+
+	// There's no one to report a failure to, since the status handle is the
+	// only channel to the Service Control Manager.
+	let _ = run_service(name, args, accept, main, service_type);
+}
+
+/// Registers the control handler, drives the service status and calls the
+/// service entry point.
+fn run_service(
+	name: String,
+	args: Vec<String>,
+	accept: co::SERVICE_ACCEPT,
+	main: Arc<ServiceMainFn>,
+	service_type: co::SERVICE_TYPE,
+) -> SysResult<()>
+{
+	let (tx, rx) = mpsc::channel::<ServiceEvent>();
+	let status = Arc::new(Mutex::new(StatusReporter {
+		hss: HSERVICESTATUS::NULL,
+		status: SERVICE_STATUS {
+			dwServiceType: service_type,
+			..Default::default()
+		},
+		accept,
+	}));
+
+	let handler_status = status.clone();
+	let hss = HSERVICESTATUS::RegisterServiceCtrlHandlerEx(&name, move |ctl| {
+		handle_control(&handler_status, &tx, ctl)
+	})?;
+
+	{
+		let mut status = status.lock().unwrap();
+		status.hss = hss;
+		status.set(co::SERVICE_STATE::START_PENDING)?;
+	}
+
+	// A panic must not unwind into the dispatcher, and the service must still
+	// be reported as stopped.
+	let res = catch_unwind(AssertUnwindSafe(|| main(ServiceContext {
+		name,
+		args,
+		events: rx,
+		status: status.clone(),
+	})));
+
+	let mut status = status.lock().unwrap_or_else(PoisonError::into_inner);
+	status.status.dwWin32ExitCode = match res {
+		Ok(Ok(_)) => co::ERROR::SUCCESS.raw(),
+		Ok(Err(err)) => err.raw(),
+		Err(_) => co::ERROR::PROCESS_ABORTED.raw(), // closure panicked
+	};
+	status.set(co::SERVICE_STATE::STOPPED)
+}
+
+/// Registers a control handler for a service which wasn't added to the
+/// [`ServiceHost`](crate::ServiceHost), and reports it as stopped.
+fn stop_not_in_exe(name: &str, service_type: co::SERVICE_TYPE) -> SysResult<()> {
+	let hss = HSERVICESTATUS::RegisterServiceCtrlHandlerEx(name,
+		|_| co::ERROR::CALL_NOT_IMPLEMENTED.raw())?;
+	let mut status = StatusReporter {
+		hss,
+		status: SERVICE_STATUS {
+			dwServiceType: service_type,
+			dwWin32ExitCode: co::ERROR::SERVICE_NOT_IN_EXE.raw(),
+			..Default::default()
+		},
+		accept: co::SERVICE_ACCEPT::default(),
+	};
+	status.set(co::SERVICE_STATE::STOPPED)
+}
+
+/// [`LPHANDLER_FUNCTION_EX`](https://learn.microsoft.com/en-us/windows/win32/api/winsvc/nc-winsvc-lphandler_function_ex)
+/// logic: updates the status, then forwards the control to the service.
+fn handle_control(
+	status: &Mutex<StatusReporter>,
+	tx: &mpsc::Sender<ServiceEvent>,
+	ctl: SvcCtl,
+) -> u32
+{
+	let mut status = status.lock().unwrap_or_else(PoisonError::into_inner); // the service may have panicked
+	let cur_state = status.status.dwCurrentState;
+
+	let new_state = match ctl {
+		SvcCtl::Interrogate => {
+			let _ = status.report();
+			return co::ERROR::SUCCESS.raw();
+		},
+		SvcCtl::Stop | SvcCtl::Shutdown | SvcCtl::PreShutdown => match cur_state {
+			co::SERVICE_STATE::STOP_PENDING | co::SERVICE_STATE::STOPPED => None,
+			_ => Some(co::SERVICE_STATE::STOP_PENDING),
+		},
+		SvcCtl::Pause => match cur_state {
+			co::SERVICE_STATE::RUNNING => Some(co::SERVICE_STATE::PAUSE_PENDING),
+			_ => None,
+		},
+		SvcCtl::Continue => match cur_state {
+			co::SERVICE_STATE::PAUSED => Some(co::SERVICE_STATE::CONTINUE_PENDING),
+			_ => None,
+		},
+		_ => None,
+	};
+	if let Some(new_state) = new_state {
+		let _ = status.set(new_state); // the control is delivered anyway
+	}
+
+	if let Some(event) = ServiceEvent::from_svc_ctl(&ctl) {
+		let _ = tx.send(event); // service may have returned already
+	}
+	co::ERROR::SUCCESS.raw()
+}
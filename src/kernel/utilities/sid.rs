@@ -0,0 +1,242 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::prelude::*;
+
+/// Maximum number of sub authorities in a [`SID`](crate::SID).
+const SID_MAX_SUB_AUTHORITIES: usize = 15;
+
+/// Owned representation of a [`SID`](crate::SID), made of its identifier
+/// authority and its sub authorities.
+///
+/// Unlike [`SID`](crate::SID), which is a variable-sized struct allocated by
+/// the system, `Sid` is an ordinary Rust value: it can be cloned, hashed,
+/// sorted and compared without calling any native function.
+///
+/// Implements [`Display`](std::fmt::Display), which formats the
+/// [SID string](https://learn.microsoft.com/en-us/windows/win32/secauthz/sid-components)
+/// like `S-1-5-32-544`, and [`FromStr`](std::str::FromStr) and
+/// [`TryFrom<&str>`](std::convert::TryFrom), which parse it, failing with
+/// [`co::ERROR::INVALID_SID`](crate::co::ERROR::INVALID_SID) if the string is
+/// malformed. SDDL aliases, like `BA`, are not accepted.
+///
+/// The ordering compares the identifier authority first, then the sub
+/// authorities, one by one.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let admins: w::Sid = "S-1-5-32-544".parse()?;
+/// assert_eq!(admins.authority(), &w::SID_IDENTIFIER_AUTHORITY::NT);
+/// assert_eq!(admins.sub_authorities(), &[
+///     co::RID::SECURITY_BUILTIN_DOMAIN,
+///     co::RID::DOMAIN_ALIAS_ADMINS,
+/// ]);
+///
+/// let bytes = admins.to_bytes();
+/// let same = w::Sid::try_from(&bytes[..])?;
+/// assert_eq!(same.to_string(), "S-1-5-32-544");
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Sid {
+	authority: SID_IDENTIFIER_AUTHORITY,
+	sub_authorities: Vec<co::RID>,
+}
+
+impl std::fmt::Display for Sid {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let au = &self.authority.Value;
+		if au[0] == 0 && au[1] == 0 { // fits 32 bits, written as decimal
+			write!(f, "S-1-{}", u32::from_be_bytes([au[2], au[3], au[4], au[5]]))?;
+		} else {
+			write!(f, "S-1-0x{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+				au[0], au[1], au[2], au[3], au[4], au[5])?;
+		}
+		for rid in self.sub_authorities.iter() {
+			write!(f, "-{}", rid.raw())?;
+		}
+		Ok(())
+	}
+}
+impl std::fmt::Debug for Sid {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Display::fmt(self, f)
+	}
+}
+
+impl std::str::FromStr for Sid {
+	type Err = co::ERROR;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::try_from(s)
+	}
+}
+
+impl TryFrom<&str> for Sid {
+	type Error = co::ERROR;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let mut parts = value.split('-');
+
+		if !parts.next().is_some_and(|s| s.eq_ignore_ascii_case("S"))
+			|| parts.next() != Some("1") // revision
+		{
+			return Err(co::ERROR::INVALID_SID);
+		}
+
+		let authority = parts.next()
+			.and_then(Self::parse_number)
+			.filter(|n| *n <= 0x0000_ffff_ffff_ffff) // 48 bits
+			.ok_or(co::ERROR::INVALID_SID)?;
+		let au = authority.to_be_bytes();
+
+		let sub_authorities = parts
+			.map(|s| Self::parse_number(s)
+				.and_then(|n| u32::try_from(n).ok())
+				.map(|n| unsafe { co::RID::from_raw(n) })
+				.ok_or(co::ERROR::INVALID_SID))
+			.collect::<SysResult<Vec<_>>>()?;
+
+		Self::new(
+			SID_IDENTIFIER_AUTHORITY { Value: [au[2], au[3], au[4], au[5], au[6], au[7]] },
+			sub_authorities,
+		)
+	}
+}
+
+impl TryFrom<&[u8]> for Sid {
+	type Error = co::ERROR;
+
+	fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+		if value.len() < 8
+			|| value[0] != 1 // revision
+			|| value.len() != 8 + 4 * value[1] as usize
+		{
+			return Err(co::ERROR::INVALID_SID);
+		}
+
+		let mut authority = SID_IDENTIFIER_AUTHORITY::NULL;
+		authority.Value.copy_from_slice(&value[2..8]);
+
+		Self::new(
+			authority,
+			value[8..].chunks_exact(4)
+				.map(|b| unsafe {
+					co::RID::from_raw(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+				})
+				.collect(),
+		)
+	}
+}
+
+impl From<&SID> for Sid {
+	fn from(value: &SID) -> Self {
+		Self {
+			authority: value.IdentifierAuthority,
+			sub_authorities: value.SubAuthority().to_vec(),
+		}
+	}
+}
+
+impl Sid {
+	/// Creates a new `Sid` from its identifier authority and sub authorities.
+	///
+	/// Fails with [`co::ERROR::INVALID_SID`](crate::co::ERROR::INVALID_SID) if
+	/// there are more than 15 sub authorities.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let everyone = w::Sid::new(
+	///     w::SID_IDENTIFIER_AUTHORITY::WORLD,
+	///     vec![co::RID::SECURITY_WORLD],
+	/// )?;
+	/// assert_eq!(everyone.to_string(), "S-1-1-0");
+	/// # w::SysResult::Ok(())
+	/// ```
+	pub fn new(
+		authority: SID_IDENTIFIER_AUTHORITY,
+		sub_authorities: Vec<co::RID>,
+	) -> SysResult<Self>
+	{
+		if sub_authorities.len() > SID_MAX_SUB_AUTHORITIES {
+			return Err(co::ERROR::INVALID_SID);
+		}
+		Ok(Self { authority, sub_authorities })
+	}
+
+	/// Returns the identifier authority.
+	#[must_use]
+	pub const fn authority(&self) -> &SID_IDENTIFIER_AUTHORITY {
+		&self.authority
+	}
+
+	/// Returns the sub authorities. The last one, if any, is usually the
+	/// [relative identifier](https://learn.microsoft.com/en-us/windows/win32/secgloss/r-gly).
+	#[must_use]
+	pub fn sub_authorities(&self) -> &[co::RID] {
+		&self.sub_authorities
+	}
+
+	/// Serializes the `Sid` into the raw [`SID`](crate::SID) memory layout.
+	/// These bytes can be parsed back with
+	/// [`TryFrom<&[u8]>`](std::convert::TryFrom), and their length is the same
+	/// value returned by [`GetLengthSid`](crate::GetLengthSid).
+	#[must_use]
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut buf = Vec::with_capacity(8 + 4 * self.sub_authorities.len());
+		buf.push(1); // revision
+		buf.push(self.sub_authorities.len() as _);
+		buf.extend_from_slice(&self.authority.Value);
+		self.sub_authorities.iter()
+			.for_each(|rid| buf.extend_from_slice(&rid.raw().to_le_bytes()));
+		buf
+	}
+
+	/// Allocates a raw [`SID`](crate::SID) with the same contents, to be passed
+	/// to native functions.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let sid: w::Sid = "S-1-5-18".parse()?;
+	/// let raw = sid.to_sid()?;
+	/// let (account, domain, _) = w::LookupAccountSid(None, &raw)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn to_sid(&self) -> SysResult<SidGuard> {
+		let bytes = self.to_bytes();
+		let sid_buf = HGLOBAL::GlobalAlloc(
+			Some(co::GMEM::FIXED | co::GMEM::ZEROINIT),
+			bytes.len(),
+		)?;
+
+		unsafe {
+			std::ptr::copy_nonoverlapping(
+				bytes.as_ptr(), sid_buf.ptr() as *mut u8, bytes.len());
+			Ok(SidGuard::new(sid_buf))
+		}
+	}
+
+	/// Parses a decimal number, or an hexadecimal one with `0x` prefix.
+	#[must_use]
+	fn parse_number(s: &str) -> Option<u64> {
+		let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+			Some(hex) => (hex, 16),
+			None => (s, 10),
+		};
+		if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+			None // from_str_radix would accept a sign
+		} else {
+			u64::from_str_radix(digits, radix).ok()
+		}
+	}
+}
@@ -0,0 +1,468 @@
+use crate::co;
+use crate::decl::*;
+
+/// Incremental decoder which converts raw bytes in a given
+/// [`Encoding`](crate::Encoding) into UTF-16 code units.
+///
+/// Bytes are fed one at a time, so the decoder state is kept across buffer
+/// boundaries, which allows streaming.
+pub(in crate::kernel) struct TextDecoder {
+	state: State,
+}
+
+enum State {
+	Ansi,
+	Win1252,
+	Utf8(Utf8State),
+	Utf16 { big_endian: bool, pending: Option<u8> },
+	Utf32 { big_endian: bool, pending: [u8; 4], count: usize },
+	Scsu(ScsuState),
+	Bocu1(Bocu1State),
+}
+
+impl TextDecoder {
+	/// Creates a new decoder for the given encoding.
+	///
+	/// Fails with [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if the encoding is [`Encoding::Unknown`](crate::Encoding::Unknown).
+	#[must_use]
+	pub(in crate::kernel) fn new(encoding: Encoding) -> SysResult<Self> {
+		let state = match encoding {
+			Encoding::Unknown => return Err(co::ERROR::INVALID_PARAMETER),
+			Encoding::Ansi => State::Ansi,
+			Encoding::Win1252 => State::Win1252,
+			Encoding::Utf8 => State::Utf8(Utf8State::default()),
+			Encoding::Utf16be => State::Utf16 { big_endian: true, pending: None },
+			Encoding::Utf16le => State::Utf16 { big_endian: false, pending: None },
+			Encoding::Utf32be => State::Utf32 { big_endian: true, pending: [0; 4], count: 0 },
+			Encoding::Utf32le => State::Utf32 { big_endian: false, pending: [0; 4], count: 0 },
+			Encoding::Scsu => State::Scsu(ScsuState::default()),
+			Encoding::Bocu1 => State::Bocu1(Bocu1State::default()),
+		};
+		Ok(Self { state })
+	}
+
+	/// Feeds a single byte, appending any decoded UTF-16 code units to `dest`.
+	///
+	/// Fails with
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION)
+	/// if the byte cannot be decoded.
+	pub(in crate::kernel) fn push(&mut self,
+		b: u8,
+		dest: &mut Vec<u16>,
+	) -> SysResult<()>
+	{
+		match &mut self.state {
+			State::Ansi => dest.push(b as _),
+			State::Win1252 => dest.push(win1252_to_utf16(b)),
+			State::Utf8(st) => st.push(b, dest)?,
+			State::Utf16 { big_endian, pending } => match pending.take() {
+				None => *pending = Some(b),
+				Some(b0) => dest.push(if *big_endian {
+					u16::from_be_bytes([b0, b])
+				} else {
+					u16::from_le_bytes([b0, b])
+				}),
+			},
+			State::Utf32 { big_endian, pending, count } => {
+				pending[*count] = b;
+				*count += 1;
+				if *count == 4 {
+					*count = 0;
+					let cp = if *big_endian {
+						u32::from_be_bytes(*pending)
+					} else {
+						u32::from_le_bytes(*pending)
+					};
+					push_code_point(cp, dest)?;
+				}
+			},
+			State::Scsu(st) => st.push(b, dest)?,
+			State::Bocu1(st) => st.push(b, dest)?,
+		}
+		Ok(())
+	}
+
+	/// Checks whether the input ended in the middle of a multi-byte sequence.
+	///
+	/// A dangling partial UTF-16 or UTF-32 code unit is simply discarded.
+	pub(in crate::kernel) fn finish(&self) -> SysResult<()> {
+		let incomplete = match &self.state {
+			State::Utf8(st) => st.needed > 0,
+			State::Scsu(st) => st.pending != ScsuPending::None,
+			State::Bocu1(st) => st.count > 0,
+			_ => false,
+		};
+		if incomplete {
+			Err(co::ERROR::NO_UNICODE_TRANSLATION)
+		} else {
+			Ok(())
+		}
+	}
+}
+
+/// Appends a Unicode code point to the buffer, as one or two UTF-16 code
+/// units.
+fn push_code_point(cp: u32, dest: &mut Vec<u16>) -> SysResult<()> {
+	let ch = char::from_u32(cp).ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?;
+	let mut buf = [0u16; 2];
+	dest.extend_from_slice(ch.encode_utf16(&mut buf));
+	Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Windows-1252 characters in the 0x80-0x9f range. Undefined positions are
+/// mapped to the C1 control with the same value, like Windows itself does.
+pub(in crate::kernel) const WIN1252_HIGH: [u16; 32] = [
+	0x20ac, 0x0081, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+	0x02c6, 0x2030, 0x0160, 0x2039, 0x0152, 0x008d, 0x017d, 0x008f,
+	0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+	0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0x009d, 0x017e, 0x0178,
+];
+
+#[must_use]
+fn win1252_to_utf16(b: u8) -> u16 {
+	match b {
+		0x80..=0x9f => WIN1252_HIGH[(b - 0x80) as usize],
+		_ => b as _,
+	}
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Utf8State {
+	cp: u32,
+	needed: u8,
+	lower: u8, // valid range of the next continuation byte
+	upper: u8,
+}
+
+impl Utf8State {
+	fn push(&mut self, b: u8, dest: &mut Vec<u16>) -> SysResult<()> {
+		if self.needed == 0 {
+			self.lower = 0x80;
+			self.upper = 0xbf;
+			match b {
+				0x00..=0x7f => dest.push(b as _),
+				0xc2..=0xdf => { self.needed = 1; self.cp = (b & 0x1f) as _; },
+				0xe0..=0xef => {
+					match b {
+						0xe0 => self.lower = 0xa0, // excluding overlongs
+						0xed => self.upper = 0x9f, // excluding surrogates
+						_ => {},
+					}
+					self.needed = 2;
+					self.cp = (b & 0x0f) as _;
+				},
+				0xf0..=0xf4 => {
+					match b {
+						0xf0 => self.lower = 0x90, // excluding overlongs
+						0xf4 => self.upper = 0x8f, // up to U+10FFFF
+						_ => {},
+					}
+					self.needed = 3;
+					self.cp = (b & 0x07) as _;
+				},
+				_ => return Err(co::ERROR::NO_UNICODE_TRANSLATION),
+			}
+		} else {
+			if b < self.lower || b > self.upper {
+				self.needed = 0;
+				return Err(co::ERROR::NO_UNICODE_TRANSLATION);
+			}
+			self.lower = 0x80;
+			self.upper = 0xbf;
+			self.cp = (self.cp << 6) | (b & 0x3f) as u32;
+			self.needed -= 1;
+			if self.needed == 0 {
+				push_code_point(self.cp, dest)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+//------------------------------------------------------------------------------
+
+/// Static windows, used by the SQn quoting tags.
+const SCSU_STATIC_WINDOWS: [u32; 8] = [
+	0x0000, 0x0080, 0x0100, 0x0300, 0x2000, 0x2080, 0x2100, 0x3000,
+];
+
+/// Initial offsets of the dynamic windows.
+pub(in crate::kernel) const SCSU_DYNAMIC_WINDOWS: [u32; 8] = [
+	0x0080, 0x00c0, 0x0400, 0x0600, 0x0900, 0x3040, 0x30a0, 0xff00,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ScsuPending {
+	None,
+	Quote(usize),             // SQn, waiting for 1 byte
+	Define(usize),            // SDn/UDn, waiting for 1 byte
+	DefineExt(Option<u8>),    // SDX/UDX, waiting for 2 bytes
+	QuoteUnicode(Option<u8>), // SQU/UQU, waiting for 2 bytes
+	Unicode(u8),              // second byte of a UTF-16 code unit in Unicode mode
+}
+
+/// [Standard Compression Scheme for Unicode](https://www.unicode.org/reports/tr6/)
+/// decoder state.
+struct ScsuState {
+	unicode_mode: bool,
+	active: usize,
+	windows: [u32; 8],
+	pending: ScsuPending,
+}
+
+impl Default for ScsuState {
+	fn default() -> Self {
+		Self {
+			unicode_mode: false,
+			active: 0,
+			windows: SCSU_DYNAMIC_WINDOWS,
+			pending: ScsuPending::None,
+		}
+	}
+}
+
+impl ScsuState {
+	fn push(&mut self, b: u8, dest: &mut Vec<u16>) -> SysResult<()> {
+		match self.pending {
+			ScsuPending::None => {},
+			ScsuPending::Quote(n) => {
+				self.pending = ScsuPending::None;
+				return if b < 0x80 {
+					push_code_point(SCSU_STATIC_WINDOWS[n] + b as u32, dest)
+				} else {
+					push_code_point(self.windows[n] + (b - 0x80) as u32, dest)
+				};
+			},
+			ScsuPending::Define(n) => {
+				self.pending = ScsuPending::None;
+				self.windows[n] = Self::window_offset(b)?;
+				self.active = n;
+				self.unicode_mode = false;
+				return Ok(());
+			},
+			ScsuPending::DefineExt(None) => {
+				self.pending = ScsuPending::DefineExt(Some(b));
+				return Ok(());
+			},
+			ScsuPending::DefineExt(Some(hi)) => {
+				self.pending = ScsuPending::None;
+				let val = u16::from_be_bytes([hi, b]);
+				let n = (val >> 13) as usize;
+				self.windows[n] = 0x10000 + ((val as u32 & 0x1fff) << 7);
+				self.active = n;
+				self.unicode_mode = false;
+				return Ok(());
+			},
+			ScsuPending::QuoteUnicode(None) => {
+				self.pending = ScsuPending::QuoteUnicode(Some(b));
+				return Ok(());
+			},
+			ScsuPending::QuoteUnicode(Some(hi)) => {
+				self.pending = ScsuPending::None;
+				dest.push(u16::from_be_bytes([hi, b]));
+				return Ok(());
+			},
+			ScsuPending::Unicode(hi) => {
+				self.pending = ScsuPending::None;
+				dest.push(u16::from_be_bytes([hi, b]));
+				return Ok(());
+			},
+		}
+
+		if self.unicode_mode {
+			match b {
+				0xe0..=0xe7 => { // UCn
+					self.active = (b - 0xe0) as _;
+					self.unicode_mode = false;
+				},
+				0xe8..=0xef => self.pending = ScsuPending::Define((b - 0xe8) as _), // UDn
+				0xf0 => self.pending = ScsuPending::QuoteUnicode(None), // UQU
+				0xf1 => self.pending = ScsuPending::DefineExt(None), // UDX
+				0xf2 => return Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved
+				_ => self.pending = ScsuPending::Unicode(b),
+			}
+		} else {
+			match b {
+				0x00 | 0x09 | 0x0a | 0x0d | 0x20..=0x7f => dest.push(b as _),
+				0x01..=0x08 => self.pending = ScsuPending::Quote((b - 0x01) as _), // SQn
+				0x0b => self.pending = ScsuPending::DefineExt(None), // SDX
+				0x0c => return Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved
+				0x0e => self.pending = ScsuPending::QuoteUnicode(None), // SQU
+				0x0f => self.unicode_mode = true, // SCU
+				0x10..=0x17 => self.active = (b - 0x10) as _, // SCn
+				0x18..=0x1f => self.pending = ScsuPending::Define((b - 0x18) as _), // SDn
+				0x80..=0xff => push_code_point(
+					self.windows[self.active] + (b - 0x80) as u32, dest)?,
+			}
+		}
+		Ok(())
+	}
+
+	fn window_offset(b: u8) -> SysResult<u32> {
+		Ok(match b {
+			0x01..=0x67 => b as u32 * 0x80,
+			0x68..=0xa7 => b as u32 * 0x80 + 0xac00,
+			0xf9 => 0x00c0,
+			0xfa => 0x0250,
+			0xfb => 0x0370,
+			0xfc => 0x0530,
+			0xfd => 0x3040,
+			0xfe => 0x30a0,
+			0xff => 0xff60,
+			_ => return Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved
+		})
+	}
+}
+
+//------------------------------------------------------------------------------
+
+pub(in crate::kernel) const BOCU1_ASCII_PREV: i32 = 0x40;
+const BOCU1_MIN: i32 = 0x21;
+pub(in crate::kernel) const BOCU1_MIDDLE: i32 = 0x90;
+pub(in crate::kernel) const BOCU1_RESET: u8 = 0xff;
+const BOCU1_TRAIL_CONTROLS_COUNT: i32 = 20;
+pub(in crate::kernel) const BOCU1_TRAIL_BYTE_OFFSET: i32 = BOCU1_MIN - BOCU1_TRAIL_CONTROLS_COUNT;
+pub(in crate::kernel) const BOCU1_TRAIL_COUNT: i32 = (0xff - BOCU1_MIN + 1) + BOCU1_TRAIL_CONTROLS_COUNT;
+const BOCU1_SINGLE: i32 = 64;
+const BOCU1_LEAD_2: i32 = 43;
+const BOCU1_LEAD_3: i32 = 3;
+pub(in crate::kernel) const BOCU1_REACH_POS_1: i32 = BOCU1_SINGLE - 1;
+pub(in crate::kernel) const BOCU1_REACH_NEG_1: i32 = -BOCU1_SINGLE;
+pub(in crate::kernel) const BOCU1_REACH_POS_2: i32 = BOCU1_REACH_POS_1 + BOCU1_LEAD_2 * BOCU1_TRAIL_COUNT;
+pub(in crate::kernel) const BOCU1_REACH_NEG_2: i32 = BOCU1_REACH_NEG_1 - BOCU1_LEAD_2 * BOCU1_TRAIL_COUNT;
+pub(in crate::kernel) const BOCU1_REACH_POS_3: i32 = BOCU1_REACH_POS_2 + BOCU1_LEAD_3 * BOCU1_TRAIL_COUNT * BOCU1_TRAIL_COUNT;
+pub(in crate::kernel) const BOCU1_REACH_NEG_3: i32 = BOCU1_REACH_NEG_2 - BOCU1_LEAD_3 * BOCU1_TRAIL_COUNT * BOCU1_TRAIL_COUNT;
+pub(in crate::kernel) const BOCU1_START_POS_2: i32 = BOCU1_MIDDLE + BOCU1_REACH_POS_1 + 1;
+pub(in crate::kernel) const BOCU1_START_POS_3: i32 = BOCU1_START_POS_2 + BOCU1_LEAD_2;
+pub(in crate::kernel) const BOCU1_START_POS_4: i32 = BOCU1_START_POS_3 + BOCU1_LEAD_3;
+pub(in crate::kernel) const BOCU1_START_NEG_2: i32 = BOCU1_MIDDLE + BOCU1_REACH_NEG_1;
+pub(in crate::kernel) const BOCU1_START_NEG_3: i32 = BOCU1_START_NEG_2 - BOCU1_LEAD_2;
+pub(in crate::kernel) const BOCU1_START_NEG_4: i32 = BOCU1_START_NEG_3 - BOCU1_LEAD_3;
+
+/// C0 control bytes which are used as BOCU-1 trail bytes, in trail value
+/// order.
+pub(in crate::kernel) const BOCU1_TRAIL_CONTROLS: [u8; BOCU1_TRAIL_CONTROLS_COUNT as usize] = [
+	0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+	0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+	0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+/// Computes the next BOCU-1 `prev` value from the last code point.
+#[must_use]
+pub(in crate::kernel) const fn bocu1_prev(cp: i32) -> i32 {
+	if 0x3040 <= cp && cp <= 0x309f { // Hiragana is not 128-aligned
+		0x3070
+	} else if 0x4e00 <= cp && cp <= 0x9fa5 { // CJK Unihan
+		0x4e00 - BOCU1_REACH_NEG_2
+	} else if 0xac00 <= cp && cp <= 0xd7a3 { // Korean Hangul
+		(0xd7a3 + 0xac00) / 2
+	} else { // mostly small scripts
+		(cp & !0x7f) + BOCU1_ASCII_PREV
+	}
+}
+
+/// [Binary Ordered Compression for Unicode](https://www.unicode.org/notes/tn6/)
+/// decoder state.
+struct Bocu1State {
+	prev: i32,
+	diff: i32,
+	count: u8,
+}
+
+impl Default for Bocu1State {
+	fn default() -> Self {
+		Self { prev: BOCU1_ASCII_PREV, diff: 0, count: 0 }
+	}
+}
+
+impl Bocu1State {
+	fn push(&mut self, b: u8, dest: &mut Vec<u16>) -> SysResult<()> {
+		if self.count == 0 {
+			self.push_lead(b, dest)
+		} else {
+			self.push_trail(b, dest)
+		}
+	}
+
+	fn push_lead(&mut self, b: u8, dest: &mut Vec<u16>) -> SysResult<()> {
+		let bi = b as i32;
+		if b <= 0x20 { // direct-encoded C0 control or space
+			if b != 0x20 {
+				self.prev = BOCU1_ASCII_PREV;
+			}
+			dest.push(b as _);
+		} else if (BOCU1_START_NEG_2..BOCU1_START_POS_2).contains(&bi) { // single-byte difference
+			self.deliver(self.prev + (bi - BOCU1_MIDDLE), dest)?;
+		} else if b == BOCU1_RESET {
+			self.prev = BOCU1_ASCII_PREV;
+		} else {
+			let tc = BOCU1_TRAIL_COUNT;
+			let (diff, count) = if bi >= BOCU1_START_NEG_2 { // positive difference
+				if bi < BOCU1_START_POS_3 {
+					((bi - BOCU1_START_POS_2) * tc + BOCU1_REACH_POS_1 + 1, 1)
+				} else if bi < BOCU1_START_POS_4 {
+					((bi - BOCU1_START_POS_3) * tc * tc + BOCU1_REACH_POS_2 + 1, 2)
+				} else {
+					(BOCU1_REACH_POS_3 + 1, 3)
+				}
+			} else { // negative difference
+				if bi >= BOCU1_START_NEG_3 {
+					((bi - BOCU1_START_NEG_2) * tc + BOCU1_REACH_NEG_1, 1)
+				} else if bi > BOCU1_MIN {
+					((bi - BOCU1_START_NEG_3) * tc * tc + BOCU1_REACH_NEG_2, 2)
+				} else {
+					(-tc * tc * tc + BOCU1_REACH_NEG_3, 3)
+				}
+			};
+			self.diff = diff;
+			self.count = count;
+		}
+		Ok(())
+	}
+
+	fn push_trail(&mut self, b: u8, dest: &mut Vec<u16>) -> SysResult<()> {
+		let t = if (b as i32) < BOCU1_MIN {
+			match BOCU1_TRAIL_CONTROLS.iter().position(|c| *c == b) {
+				Some(t) => t as i32,
+				None => {
+					self.prev = BOCU1_ASCII_PREV;
+					self.count = 0;
+					return Err(co::ERROR::NO_UNICODE_TRANSLATION);
+				},
+			}
+		} else {
+			b as i32 - BOCU1_TRAIL_BYTE_OFFSET
+		};
+
+		match self.count {
+			1 => {
+				self.count = 0;
+				let cp = self.prev + self.diff + t;
+				self.deliver(cp, dest)?;
+			},
+			2 => {
+				self.diff += t * BOCU1_TRAIL_COUNT;
+				self.count = 1;
+			},
+			_ => {
+				self.diff += t * BOCU1_TRAIL_COUNT * BOCU1_TRAIL_COUNT;
+				self.count = 2;
+			},
+		}
+		Ok(())
+	}
+
+	fn deliver(&mut self, cp: i32, dest: &mut Vec<u16>) -> SysResult<()> {
+		if !(0..=0x10ffff).contains(&cp) {
+			self.prev = BOCU1_ASCII_PREV;
+			return Err(co::ERROR::NO_UNICODE_TRANSLATION);
+		}
+		self.prev = bocu1_prev(cp);
+		push_code_point(cp as _, dest)
+	}
+}
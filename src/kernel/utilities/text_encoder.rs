@@ -0,0 +1,172 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::utilities::text_decoder::*;
+
+/// Converts UTF-16 code units into raw bytes in the given
+/// [`Encoding`](crate::Encoding), appending them to `dest`, optionally preceded
+/// by the BOM.
+///
+/// Fails with
+/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION)
+/// if a character cannot be represented in the target encoding, and with
+/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER) if
+/// the encoding is [`Encoding::Unknown`](crate::Encoding::Unknown).
+pub(in crate::kernel) fn encode(
+	encoding: Encoding,
+	src: &[u16],
+	bom: bool,
+	dest: &mut Vec<u8>,
+) -> SysResult<()>
+{
+	if encoding == Encoding::Unknown {
+		return Err(co::ERROR::INVALID_PARAMETER);
+	}
+
+	if bom {
+		dest.extend_from_slice(encoding.bom());
+		if encoding == Encoding::Bocu1 {
+			dest.push(BOCU1_RESET); // signature changes the state, so reset it
+		}
+	}
+
+	match encoding {
+		Encoding::Unknown => {},
+		Encoding::Ansi => for ch in src.iter() {
+			if *ch > 0x7f {
+				return Err(co::ERROR::NO_UNICODE_TRANSLATION);
+			}
+			dest.push(*ch as _);
+		},
+		Encoding::Win1252 => for ch in src.iter() {
+			dest.push(utf16_to_win1252(*ch)?);
+		},
+		Encoding::Utf8 => for ch in chars(src) {
+			let mut buf = [0u8; 4];
+			dest.extend_from_slice(ch?.encode_utf8(&mut buf).as_bytes());
+		},
+		Encoding::Utf16be => src.iter()
+			.for_each(|ch| dest.extend_from_slice(&ch.to_be_bytes())),
+		Encoding::Utf16le => src.iter()
+			.for_each(|ch| dest.extend_from_slice(&ch.to_le_bytes())),
+		Encoding::Utf32be => for ch in chars(src) {
+			dest.extend_from_slice(&(ch? as u32).to_be_bytes());
+		},
+		Encoding::Utf32le => for ch in chars(src) {
+			dest.extend_from_slice(&(ch? as u32).to_le_bytes());
+		},
+		Encoding::Scsu => encode_scsu(src, dest),
+		Encoding::Bocu1 => {
+			let mut prev = BOCU1_ASCII_PREV;
+			for ch in chars(src) {
+				encode_bocu1(ch? as _, &mut prev, dest);
+			}
+		},
+	}
+	Ok(())
+}
+
+/// Iterates over the characters of UTF-16 code units, yielding an error for
+/// unpaired surrogates.
+fn chars(src: &[u16]) -> impl Iterator<Item = SysResult<char>> + '_ {
+	char::decode_utf16(src.iter().copied())
+		.map(|ch| ch.map_err(|_| co::ERROR::NO_UNICODE_TRANSLATION))
+}
+
+fn utf16_to_win1252(ch: u16) -> SysResult<u8> {
+	match ch {
+		0x00..=0x7f | 0xa0..=0xff => Ok(ch as _),
+		_ => WIN1252_HIGH.iter()
+			.position(|hi| *hi == ch)
+			.map(|idx| 0x80 + idx as u8)
+			.ok_or(co::ERROR::NO_UNICODE_TRANSLATION),
+	}
+}
+
+/// Encodes with [SCSU](https://www.unicode.org/reports/tr6/), using only the
+/// default dynamic windows, and quoting everything else as UTF-16.
+fn encode_scsu(src: &[u16], dest: &mut Vec<u8>) {
+	const SQ0: u8 = 0x01;
+	const SQU: u8 = 0x0e;
+	const SC0: u8 = 0x10;
+
+	let mut active = 0;
+	let in_window = |ch: u16, n: usize| -> bool {
+		let off = SCSU_DYNAMIC_WINDOWS[n];
+		(ch as u32) >= off && (ch as u32) < off + 0x80
+	};
+
+	for ch in src.iter().copied() {
+		match ch {
+			0x00 | 0x09 | 0x0a | 0x0d | 0x20..=0x7f => dest.push(ch as _),
+			0x01..=0x1f => dest.extend_from_slice(&[SQ0, ch as _]), // tag values must be quoted
+			_ => if in_window(ch, active) {
+				dest.push(0x80 + (ch as u32 - SCSU_DYNAMIC_WINDOWS[active]) as u8);
+			} else if let Some(n) = (0..8).find(|n| in_window(ch, *n)) {
+				active = n;
+				dest.extend_from_slice(&[
+					SC0 + n as u8,
+					0x80 + (ch as u32 - SCSU_DYNAMIC_WINDOWS[n]) as u8,
+				]);
+			} else { // also handles surrogates, one code unit at a time
+				dest.push(SQU);
+				dest.extend_from_slice(&ch.to_be_bytes());
+			},
+		}
+	}
+}
+
+/// Encodes a single code point with
+/// [BOCU-1](https://www.unicode.org/notes/tn6/), updating the `prev` state.
+fn encode_bocu1(cp: i32, prev: &mut i32, dest: &mut Vec<u8>) {
+	if cp <= 0x20 { // C0 control or space, encoded directly
+		if cp != 0x20 {
+			*prev = BOCU1_ASCII_PREV;
+		}
+		dest.push(cp as _);
+		return;
+	}
+
+	let mut diff = cp - *prev;
+	*prev = bocu1_prev(cp);
+
+	if (BOCU1_REACH_NEG_1..=BOCU1_REACH_POS_1).contains(&diff) { // single byte
+		dest.push((BOCU1_MIDDLE + diff) as _);
+		return;
+	}
+
+	let (lead, count) = if diff >= BOCU1_REACH_NEG_1 {
+		if diff <= BOCU1_REACH_POS_2 {
+			diff -= BOCU1_REACH_POS_1 + 1;
+			(BOCU1_START_POS_2, 1)
+		} else if diff <= BOCU1_REACH_POS_3 {
+			diff -= BOCU1_REACH_POS_2 + 1;
+			(BOCU1_START_POS_3, 2)
+		} else {
+			diff -= BOCU1_REACH_POS_3 + 1;
+			(BOCU1_START_POS_4, 3)
+		}
+	} else if diff >= BOCU1_REACH_NEG_2 {
+		diff -= BOCU1_REACH_NEG_1;
+		(BOCU1_START_NEG_2, 1)
+	} else if diff >= BOCU1_REACH_NEG_3 {
+		diff -= BOCU1_REACH_NEG_2;
+		(BOCU1_START_NEG_3, 2)
+	} else {
+		diff -= BOCU1_REACH_NEG_3;
+		(BOCU1_START_NEG_4, 3)
+	};
+
+	let mut trails = [0u8; 3];
+	for idx in (0..count).rev() { // trail bytes are like digits, least significant last
+		let t = diff.rem_euclid(BOCU1_TRAIL_COUNT);
+		diff = diff.div_euclid(BOCU1_TRAIL_COUNT);
+		trails[idx] = if (t as usize) < BOCU1_TRAIL_CONTROLS.len() {
+			BOCU1_TRAIL_CONTROLS[t as usize]
+		} else {
+			(t + BOCU1_TRAIL_BYTE_OFFSET) as _
+		};
+	}
+
+	dest.push((lead + diff) as _);
+	dest.extend_from_slice(&trails[..count]);
+}
@@ -0,0 +1,215 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::iterators::*;
+use crate::kernel::utilities::text_decoder::TextDecoder;
+
+/// Size of each chunk read from the underlying reader.
+const CHUNK_SZ: usize = 64 * 1024;
+
+/// Buffered text reader over any [`std::io::Read`](std::io::Read) source,
+/// which decodes the text on the fly and yields it line by line.
+///
+/// The encoding is guessed with [`Encoding::guess`](crate::Encoding::guess)
+/// upon the first chunk of data, and the
+/// [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), if any, is skipped.
+/// Since only the first chunk is analyzed, data guessed as
+/// [`Encoding::Ansi`](crate::Encoding::Ansi) is decoded as
+/// [`Encoding::Utf8`](crate::Encoding::Utf8), which is a superset of it.
+///
+/// Lines can be terminated by CRLF, LF or CR; the terminators are not included
+/// in the returned strings.
+///
+/// Unlike [`WString::parse`](crate::WString::parse), the whole data is never
+/// kept in memory, so this is the recommended way to read large text files.
+///
+/// # Examples
+///
+/// Reading a file line by line:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let f = w::File::open(
+///     "C:\\Temp\\foo.log",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+/// let mut reader = w::TextReader::new(f);
+///
+/// for line in reader.lines() {
+///     let line = line?;
+///     println!("{}", line);
+/// }
+/// # std::io::Result::Ok(())
+/// ```
+pub struct TextReader<R> {
+	reader: R,
+	decoder: Option<TextDecoder>, // created after the first chunk is read
+	encoding: Encoding,
+	raw: Vec<u8>,
+	buf: Vec<u16>, // decoded chars not yet returned
+	pos: usize, // start of the next line within buf
+	eof: bool,
+}
+
+impl<R> TextReader<R>
+	where R: std::io::Read,
+{
+	/// Creates a new `TextReader` over the given source. No data is read
+	/// until the first line is requested.
+	#[must_use]
+	pub fn new(reader: R) -> Self {
+		Self {
+			reader,
+			decoder: None,
+			encoding: Encoding::Unknown,
+			raw: vec![0x00; CHUNK_SZ],
+			buf: Vec::default(),
+			pos: 0,
+			eof: false,
+		}
+	}
+
+	/// Returns the encoding being used to decode the text.
+	///
+	/// Returns [`Encoding::Unknown`](crate::Encoding::Unknown) if no data was
+	/// read yet.
+	#[must_use]
+	pub const fn encoding(&self) -> Encoding {
+		self.encoding
+	}
+
+	/// Consumes the `TextReader`, returning the underlying source.
+	#[must_use]
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+
+	/// Returns an iterator over the remaining lines, which calls
+	/// [`TextReader::read_line`](crate::TextReader::read_line) until the end of
+	/// the data.
+	#[must_use]
+	pub fn lines(&mut self) -> impl Iterator<Item = std::io::Result<String>> + '_ {
+		TextReaderLinesIter::new(self)
+	}
+
+	/// Reads the next line, without the line terminator. Returns `None` when
+	/// there is no more data.
+	///
+	/// If the data cannot be decoded, fails with an error of
+	/// [`std::io::ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData)
+	/// kind, which wraps
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+	pub fn read_line(&mut self) -> std::io::Result<Option<String>> {
+		let mut search_from = self.pos;
+		loop {
+			let found = self.buf[search_from..].iter()
+				.position(|ch| *ch == 0x000a || *ch == 0x000d) // LF or CR
+				.map(|idx| search_from + idx);
+
+			match found {
+				Some(idx) if self.buf[idx] == 0x000a => return self.take_line(idx, idx + 1),
+				Some(idx) if idx + 1 < self.buf.len() => { // CR, we can see what comes next
+					let next_pos = if self.buf[idx + 1] == 0x000a { idx + 2 } else { idx + 1 };
+					return self.take_line(idx, next_pos);
+				},
+				Some(idx) if self.eof => return self.take_line(idx, idx + 1), // CR is the last char
+				Some(idx) => search_from = idx, // CR at the end of buffer, it may be a CRLF
+				None if self.eof => return if self.pos < self.buf.len() {
+					self.take_line(self.buf.len(), self.buf.len())
+				} else {
+					Ok(None)
+				},
+				None => search_from = self.buf.len(),
+			}
+
+			search_from -= self.pos;
+			self.fill_buf()?;
+		}
+	}
+
+	fn take_line(&mut self,
+		end: usize,
+		next_pos: usize,
+	) -> std::io::Result<Option<String>>
+	{
+		let line = String::from_utf16(&self.buf[self.pos..end])
+			.map_err(|_| Self::decode_error(co::ERROR::NO_UNICODE_TRANSLATION))?;
+		self.pos = next_pos;
+		Ok(Some(line))
+	}
+
+	/// Reads and decodes one more chunk, discarding the chars which were
+	/// already returned.
+	fn fill_buf(&mut self) -> std::io::Result<()> {
+		self.buf.drain(..self.pos);
+		self.pos = 0;
+
+		let num_read = self.read_chunk()?;
+		let mut start = 0;
+
+		if self.decoder.is_none() { // first chunk, guess the encoding
+			let (mut encoding, sz_bom) = Encoding::guess(
+				Self::sniff_slice(&self.raw[..num_read], self.eof));
+			if encoding == Encoding::Ansi {
+				encoding = Encoding::Utf8;
+			}
+			self.encoding = encoding;
+			self.decoder = Some(TextDecoder::new(encoding).map_err(Self::decode_error)?);
+			start = sz_bom; // skip BOM, if any
+		}
+
+		let decoder = self.decoder.as_mut().unwrap();
+		for b in self.raw[start..num_read].iter() {
+			decoder.push(*b, &mut self.buf).map_err(Self::decode_error)?;
+		}
+		if self.eof {
+			decoder.finish().map_err(Self::decode_error)?;
+		}
+		Ok(())
+	}
+
+	/// Fills the raw buffer, returning the number of bytes read. If the
+	/// encoding was not guessed yet, reads until at least the BOM size.
+	fn read_chunk(&mut self) -> std::io::Result<usize> {
+		const MIN_GUESS_SZ: usize = 4; // enough to contain any BOM
+
+		let mut num_read = 0;
+		loop {
+			match self.reader.read(&mut self.raw[num_read..]) {
+				Ok(0) => {
+					self.eof = true;
+					break;
+				},
+				Ok(n) => {
+					num_read += n;
+					if self.decoder.is_some() || num_read >= MIN_GUESS_SZ {
+						break;
+					}
+				},
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {},
+				Err(e) => return Err(e),
+			}
+		}
+		Ok(num_read)
+	}
+
+	/// If the data is not complete, returns the slice up to the last ASCII
+	/// char, so a multi-byte sequence cut at the end of the chunk won't spoil
+	/// the UTF-8 guessing.
+	#[must_use]
+	fn sniff_slice(data: &[u8], eof: bool) -> &[u8] {
+		if eof {
+			data
+		} else {
+			match data.iter().rposition(|b| *b < 0x80) {
+				Some(idx) => &data[..=idx],
+				None => data,
+			}
+		}
+	}
+
+	#[must_use]
+	fn decode_error(err: co::ERROR) -> std::io::Error {
+		std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+	}
+}
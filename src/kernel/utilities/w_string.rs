@@ -4,6 +4,7 @@ use crate::co;
 use crate::decl::*;
 use crate::guard::*;
 use crate::kernel::{ffi, privs::*};
+use crate::kernel::utilities::{text_decoder::TextDecoder, text_encoder};
 use crate::prelude::*;
 
 /// Stores a `[u16]` buffer for a null-terminated
@@ -262,11 +263,18 @@ impl WString {
 	/// Guesses the encoding with [`Encoding::guess`](crate::Encoding::guess)
 	/// and parses the data as a string.
 	///
+	/// All [`Encoding`](crate::Encoding) variants are decoded in pure Rust,
+	/// without calling any native function. Parsing stops at the first null
+	/// character, if any. If the data cannot be decoded – like an invalid UTF-8
+	/// sequence, or a code point out of the Unicode range – the method fails
+	/// with [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+	/// An incomplete code unit at the end of UTF-16 or UTF-32 data is discarded.
+	///
 	/// If you're sure the data has UTF-8 encoding, you can also use the
 	/// built-in [`String::from_utf8`](std::string::String::from_utf8).
 	///
-	/// To serialize the string back into UTF-8 bytes, use the built-in
-	/// [`String::into_bytes`](std::string::String::into_bytes).
+	/// To serialize the string back into raw bytes, use
+	/// [`WString::serialize`](crate::WString::serialize).
 	///
 	/// # Examples
 	///
@@ -286,54 +294,78 @@ impl WString {
 	/// ```
 	#[must_use]
 	pub fn parse(data: &[u8]) -> SysResult<Self> {
-		let mut data = data;
 		if data.is_empty() { // nothing to parse
 			return Ok(Self::new());
 		}
 
 		let (encoding, sz_bom) = Encoding::guess(data);
-		data = &data[sz_bom..]; // skip BOM, if any
-
-		Ok(Self::from_wchars_slice(
-			&match encoding {
-				Encoding::Ansi => Self::parse_ansi(data),
-				Encoding::Win1252 => MultiByteToWideChar(co::CP::WINDOWS_1252, co::MBC::NoValue, data)?,
-				Encoding::Utf8 => MultiByteToWideChar(co::CP::UTF8, co::MBC::NoValue, data)?,
-				Encoding::Utf16be => Self::parse_utf16(data, true),
-				Encoding::Utf16le => Self::parse_utf16(data, false),
-				Encoding::Utf32be
-				| Encoding::Utf32le
-				| Encoding::Scsu
-				| Encoding::Bocu1
-				| Encoding::Unknown => panic!("Encoding {} not implemented.", encoding),
+		let data = &data[sz_bom..]; // skip BOM, if any
+
+		let mut decoder = TextDecoder::new(encoding)?;
+		let mut buf = Vec::<u16>::with_capacity(data.len());
+		for b in data.iter() {
+			decoder.push(*b, &mut buf)?;
+			if buf.last() == Some(&0x0000) { // terminating null, ignore the rest
+				buf.pop();
+				return Ok(Self::from_wchars_slice(&buf));
 			}
-		))
-	}
+		}
+		decoder.finish()?;
 
-	fn parse_ansi(data: &[u8]) -> Vec<u16> {
-		data.iter()
-			.take_while(|ch| **ch != 0x0000) // ignore terminating null, if any
-			.map(|ch| *ch as u16) // raw u8 to u16 conversion
-			.collect()
+		Ok(Self::from_wchars_slice(&buf))
 	}
 
-	fn parse_utf16(data: &[u8], is_big_endian: bool) -> Vec<u16> {
-		let data = if data.len() % 2 == 1 {
-			&data[..data.len() - 1] // if odd number of bytes, discard last one
-		} else {
-			data
-		};
+	/// Serializes the string into raw bytes with the given encoding, optionally
+	/// prepending its [BOM](https://en.wikipedia.org/wiki/Byte_order_mark).
+	/// This is the inverse of [`WString::parse`](crate::WString::parse).
+	///
+	/// The terminating null is not serialized. Since
+	/// [`Encoding::Ansi`](crate::Encoding::Ansi) and
+	/// [`Encoding::Win1252`](crate::Encoding::Win1252) have no BOM, `bom` is
+	/// ignored for them.
+	///
+	/// Fails with
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION)
+	/// if the string has a character which cannot be represented in the target
+	/// encoding – like a non-ASCII character in
+	/// [`Encoding::Ansi`](crate::Encoding::Ansi), or an unpaired surrogate in
+	/// [`Encoding::Utf8`](crate::Encoding::Utf8). Fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if `encoding` is [`Encoding::Unknown`](crate::Encoding::Unknown).
+	///
+	/// # Examples
+	///
+	/// Reading a file, then writing it back keeping its original encoding:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let f = w::File::open(
+	///     "C:\\Temp\\foo.txt",
+	///     w::FileAccess::ExistingRW,
+	/// )?;
+	/// let raw_bytes = f.read_all()?;
+	/// let (encoding, sz_bom) = w::Encoding::guess(&raw_bytes);
+	///
+	/// let text = w::WString::parse(&raw_bytes)?.to_string();
+	/// let new_text = text.replace("foo", "bar");
+	///
+	/// let new_bytes = w::WString::from_str(&new_text)
+	///     .serialize(encoding, sz_bom > 0)?;
+	/// f.set_size(0)?;
+	/// f.write(&new_bytes)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn serialize(&self, encoding: Encoding, bom: bool) -> SysResult<Vec<u8>> {
+		let src = self.as_slice()
+			.iter()
+			.position(|ch| *ch == 0x0000)
+			.map_or(self.as_slice(), |len| &self.as_slice()[..len]); // without terminating null
 
-		data.chunks(2)
-			.take_while(|ch2| **ch2 != [0x00, 0x00]) // ignore terminating null, if any
-			.map(|ch2| {
-				if is_big_endian {
-					u16::from_be_bytes(ch2.try_into().unwrap())
-				} else {
-					u16::from_le_bytes(ch2.try_into().unwrap())
-				}
-			})
-			.collect()
+		let mut buf = Vec::<u8>::with_capacity(src.len() * 2);
+		text_encoder::encode(encoding, src, bom, &mut buf)?;
+		Ok(buf)
 	}
 }
 
@@ -0,0 +1,250 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::prelude::*;
+
+/// Names of the keys which are not a letter, a digit or a function key. The
+/// first name of each key is the one used when formatting.
+const KEY_NAMES: &[(u16, &[&str])] = &[
+	(0x08, &["Backspace", "Back"]),
+	(0x09, &["Tab"]),
+	(0x0d, &["Enter", "Return"]),
+	(0x13, &["Pause", "Break"]),
+	(0x1b, &["Esc", "Escape"]),
+	(0x20, &["Space", "Spacebar"]),
+	(0x21, &["PgUp", "PageUp", "Prior"]),
+	(0x22, &["PgDn", "PageDown", "Next"]),
+	(0x23, &["End"]),
+	(0x24, &["Home"]),
+	(0x25, &["Left"]),
+	(0x26, &["Up"]),
+	(0x27, &["Right"]),
+	(0x28, &["Down"]),
+	(0x2c, &["PrtSc", "PrintScreen"]),
+	(0x2d, &["Ins", "Insert"]),
+	(0x2e, &["Del", "Delete"]),
+	(0x5d, &["Apps", "Menu"]),
+	(0x6a, &["Num*", "Multiply"]),
+	(0x6b, &["Num+", "Add"]),
+	(0x6d, &["Num-", "Subtract"]),
+	(0x6e, &["Num.", "Decimal"]),
+	(0x6f, &["Num/", "Divide"]),
+	(0xba, &[";"]),
+	(0xbb, &["Plus", "+", "="]),
+	(0xbc, &[",", "Comma"]),
+	(0xbd, &["Minus", "-"]),
+	(0xbe, &[".", "Period"]),
+	(0xbf, &["/"]),
+	(0xc0, &["`"]),
+	(0xdb, &["["]),
+	(0xdc, &["\\"]),
+	(0xdd, &["]"]),
+	(0xde, &["'"]),
+];
+
+impl ACCEL {
+	/// Parses a shortcut description, like `Ctrl+Shift+S`, `Alt+F4` or
+	/// `Shift+Del`, into an accelerator for the given command ID.
+	///
+	/// The modifiers `Ctrl`, `Alt` and `Shift` can appear in any order, and the
+	/// key can be a letter, a digit, a function key from `F1` to `F24`, a
+	/// numeric keypad key like `Num5`, a named key like `Enter`, `PgUp` or
+	/// `Plus`, or a raw virtual key code like `0x5b`. The comparison is
+	/// case-insensitive, and the resulting `ACCEL` always has
+	/// [`ACCELF::VIRTKEY`](crate::co::ACCELF::VIRTKEY).
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the description is malformed.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let accel = w::ACCEL::from_shortcut("Ctrl+Alt+F5", 2001)?;
+	/// assert_eq!(accel.key, co::VK::F5);
+	/// assert_eq!(accel.fVirt, co::ACCELF::VIRTKEY | co::ACCELF::CONTROL | co::ACCELF::ALT);
+	/// assert_eq!(accel.to_shortcut(), "Ctrl+Alt+F5");
+	/// # w::SysResult::Ok(())
+	/// ```
+	pub fn from_shortcut(shortcut: &str, cmd: u16) -> SysResult<Self> {
+		let mut rest = shortcut.trim();
+		let mut flags = co::ACCELF::VIRTKEY;
+
+		while let Some(idx) = rest.find('+') {
+			if idx == 0 {
+				break; // the key itself is a plus sign
+			}
+			let flag = match rest[..idx].trim().to_ascii_lowercase().as_str() {
+				"ctrl" | "control" => co::ACCELF::CONTROL,
+				"alt" => co::ACCELF::ALT,
+				"shift" => co::ACCELF::SHIFT,
+				_ => break, // not a modifier, like in "Num+"
+			};
+			if flags.has(flag) {
+				return Err(co::ERROR::INVALID_DATA); // repeated modifier
+			}
+			flags |= flag;
+			rest = rest[idx + 1..].trim_start();
+		}
+
+		Ok(Self {
+			fVirt: flags,
+			key: Self::key_from_name(rest.trim())?,
+			cmd,
+		})
+	}
+
+	/// Formats the accelerator as a shortcut description, like
+	/// `Ctrl+Shift+S`, which can be parsed back with
+	/// [`ACCEL::from_shortcut`](crate::ACCEL::from_shortcut). The modifiers are
+	/// always written in the `Ctrl`, `Alt`, `Shift` order.
+	///
+	/// If the accelerator doesn't have
+	/// [`ACCELF::VIRTKEY`](crate::co::ACCELF::VIRTKEY), the key is written as
+	/// the character it represents.
+	#[must_use]
+	pub fn to_shortcut(&self) -> String {
+		let mut s = String::default();
+		if self.fVirt.has(co::ACCELF::CONTROL) {
+			s.push_str("Ctrl+");
+		}
+		if self.fVirt.has(co::ACCELF::ALT) {
+			s.push_str("Alt+");
+		}
+		if self.fVirt.has(co::ACCELF::SHIFT) {
+			s.push_str("Shift+");
+		}
+
+		if self.fVirt.has(co::ACCELF::VIRTKEY) {
+			s.push_str(&Self::key_name(self.key));
+		} else {
+			s.push(char::from_u32(self.key.raw() as _).unwrap_or(char::REPLACEMENT_CHARACTER));
+		}
+		s
+	}
+
+	#[must_use]
+	fn key_name(key: co::VK) -> String {
+		let raw = key.raw();
+		match raw {
+			0x30..=0x39 | 0x41..=0x5a => char::from(raw as u8).to_string(), // digit or letter
+			0x60..=0x69 => format!("Num{}", raw - 0x60),
+			0x70..=0x87 => format!("F{}", raw - 0x70 + 1),
+			_ => KEY_NAMES.iter()
+				.find(|(code, _)| *code == raw)
+				.map(|(_, names)| names[0].to_owned())
+				.unwrap_or_else(|| format!("0x{:02x}", raw)),
+		}
+	}
+
+	fn key_from_name(name: &str) -> SysResult<co::VK> {
+		let mut chars = name.chars();
+		let raw = match (chars.next(), chars.next()) {
+			(None, _) => return Err(co::ERROR::INVALID_DATA),
+			(Some(ch), None) if ch.is_ascii_alphanumeric() => ch.to_ascii_uppercase() as u16,
+			_ => {
+				let lower = name.to_ascii_lowercase();
+				let by_prefix = if let Some(hex) = lower.strip_prefix("0x") {
+					u8::from_str_radix(hex, 16).map(|n| n as u16).ok()
+				} else if let Some(n) = lower.strip_prefix("numpad").or_else(|| lower.strip_prefix("num")) {
+					n.parse::<u8>().ok()
+						.filter(|n| *n <= 9)
+						.map(|n| 0x60 + n as u16)
+				} else {
+					None
+				};
+
+				by_prefix
+					.or_else(|| lower.strip_prefix('f')
+						.filter(|n| n.chars().all(|ch| ch.is_ascii_digit()))
+						.and_then(|n| n.parse::<u16>().ok())
+						.filter(|n| (1..=24).contains(n))
+						.map(|n| 0x70 + n - 1))
+					.or_else(|| KEY_NAMES.iter()
+						.find(|(_, names)| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+						.map(|(code, _)| *code))
+					.filter(|raw| *raw != 0)
+					.ok_or(co::ERROR::INVALID_DATA)?
+			},
+		};
+		Ok(unsafe { co::VK::from_raw(raw) })
+	}
+}
+
+/// Builder of an accelerator table, whose entries can be described as
+/// shortcut strings, like `Ctrl+S`, and which is created with
+/// [`HACCEL::CreateAcceleratorTable`](crate::prelude::user_Haccel::CreateAcceleratorTable).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, gui, seq_ids};
+///
+/// seq_ids! {
+///     ID_FILE_SAVE = 2001;
+///     ID_FILE_SAVE_AS
+///     ID_VIEW_REFRESH
+/// }
+///
+/// let haccel = w::AccelTable::new()
+///     .add("Ctrl+S", ID_FILE_SAVE)?
+///     .add("Ctrl+Shift+S", ID_FILE_SAVE_AS)?
+///     .add("F5", ID_VIEW_REFRESH)?
+///     .build()?;
+///
+/// let wnd = gui::WindowMain::new(gui::WindowMainOpts {
+///     accel_table: Some(haccel),
+///     ..Default::default()
+/// });
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AccelTable {
+	entries: Vec<ACCEL>,
+}
+
+impl AccelTable {
+	/// Creates a new, empty `AccelTable`.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds an entry described by a shortcut string, parsed with
+	/// [`ACCEL::from_shortcut`](crate::ACCEL::from_shortcut).
+	pub fn add(mut self, shortcut: &str, cmd: u16) -> SysResult<Self> {
+		self.entries.push(ACCEL::from_shortcut(shortcut, cmd)?);
+		Ok(self)
+	}
+
+	/// Adds an entry.
+	#[must_use]
+	pub fn add_accel(mut self, accel: ACCEL) -> Self {
+		self.entries.push(accel);
+		self
+	}
+
+	/// Returns the entries added so far.
+	#[must_use]
+	pub fn entries(&self) -> &[ACCEL] {
+		&self.entries
+	}
+
+	/// Returns the command ID of the first entry with the same key and
+	/// modifiers of the given one, if any. Useful to detect conflicting
+	/// shortcuts.
+	#[must_use]
+	pub fn find_conflict(&self, accel: &ACCEL) -> Option<u16> {
+		self.entries.iter()
+			.find(|e| e.key == accel.key && e.fVirt == accel.fVirt)
+			.map(|e| e.cmd)
+	}
+
+	/// Creates the accelerator table with
+	/// [`HACCEL::CreateAcceleratorTable`](crate::prelude::user_Haccel::CreateAcceleratorTable).
+	#[must_use]
+	pub fn build(&self) -> SysResult<DestroyAcceleratorTableGuard> {
+		HACCEL::CreateAcceleratorTable(&self.entries)
+	}
+}
@@ -1115,6 +1115,31 @@ const_ordinary! { DPI_HOSTING_BEHAVIOR: u32;
 	MIXED 1
 }
 
+const_ws! { DS: u32;
+	/// Dialog box
+	/// [styles](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dialog-box-styles)
+	/// (`u32`).
+	=>
+	=>
+	/// None of the actual values (zero).
+	NoValue 0
+	ABSALIGN 0x01
+	SYSMODAL 0x02
+	FIXEDSYS 0x0008
+	NOFAILCREATE 0x0010
+	LOCALEDIT 0x20
+	SETFONT 0x40
+	MODALFRAME 0x80
+	NOIDLEMSG 0x100
+	SETFOREGROUND 0x200
+	CONTROL 0x0400
+	CENTER 0x0800
+	CENTERMOUSE 0x1000
+	CONTEXTHELP 0x2000
+	/// Combines `SETFONT` and `FIXEDSYS`.
+	SHELLFONT Self::SETFONT.0 | Self::FIXEDSYS.0
+}
+
 const_bitflag! { DT: u32;
 	/// [`HDC::DrawText`](crate::prelude::user_Hdc::DrawText) `format` (`u32`).
 	=>
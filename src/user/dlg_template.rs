@@ -0,0 +1,367 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::privs::bytes::*;
+
+/// In-memory dialog template, which is serialized into the
+/// [`DLGTEMPLATEEX`](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dlgtemplateex)
+/// layout, followed by one
+/// [`DLGITEMTEMPLATEEX`](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dlgitemtemplateex)
+/// for each item.
+///
+/// All positions and sizes are in
+/// [dialog units](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdialogbaseunits).
+///
+/// The template can be passed to
+/// [`HINSTANCE::DialogBoxIndirectParam`](crate::prelude::user_Hinstance::DialogBoxIndirectParam)
+/// and
+/// [`HINSTANCE::CreateDialogIndirectParam`](crate::prelude::user_Hinstance::CreateDialogIndirectParam)
+/// through [`DlgTemplate::serialize_aligned`](crate::DlgTemplate::serialize_aligned),
+/// or written into a resource with
+/// [`DlgTemplate::serialize`](crate::DlgTemplate::serialize). The `gui` module
+/// also accepts it directly, like in
+/// [`gui::WindowMain::new_dlg_template`](crate::gui::WindowMain::new_dlg_template).
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let template = w::DlgTemplate {
+///     title: "Login".to_owned(),
+///     cx: 180,
+///     cy: 60,
+///     items: vec![
+///         w::DlgItem {
+///             class: w::DlgItemClass::Edit,
+///             window_style: co::WS::CHILD | co::WS::VISIBLE | co::WS::TABSTOP
+///                 | co::WS::BORDER | co::ES::AUTOHSCROLL.into(),
+///             x: 10, y: 10, cx: 160, cy: 14,
+///             id: 1001,
+///             ..Default::default()
+///         },
+///         w::DlgItem {
+///             class: w::DlgItemClass::Button,
+///             title: w::IdStr::from_str("&OK"),
+///             window_style: co::WS::CHILD | co::WS::VISIBLE | co::WS::TABSTOP
+///                 | co::BS::DEFPUSHBUTTON.into(),
+///             x: 120, y: 36, cx: 50, cy: 14,
+///             id: co::DLGID::OK.raw() as _,
+///             ..Default::default()
+///         },
+///     ],
+///     ..Default::default()
+/// };
+///
+/// let bytes = template.serialize(); // can be saved as an RT_DIALOG resource
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DlgTemplate {
+	/// Context help identifier.
+	///
+	/// Defaults to zero.
+	pub help_id: u32,
+	/// Extended window styles.
+	///
+	/// Defaults to `WS_EX::NoValue`.
+	pub window_ex_style: co::WS_EX,
+	/// Window styles.
+	///
+	/// Defaults to `WS::POPUP | WS::CAPTION | WS::SYSMENU`.
+	pub window_style: co::WS,
+	/// Dialog styles. If [`font`](crate::DlgTemplate::font) is set,
+	/// `DS::SETFONT` is automatically added.
+	///
+	/// Defaults to `DS::SHELLFONT | DS::MODALFRAME`.
+	pub dialog_style: co::DS,
+	/// Horizontal position.
+	///
+	/// Defaults to zero.
+	pub x: i16,
+	/// Vertical position.
+	///
+	/// Defaults to zero.
+	pub y: i16,
+	/// Width.
+	///
+	/// Defaults to 200.
+	pub cx: i16,
+	/// Height.
+	///
+	/// Defaults to 100.
+	pub cy: i16,
+	/// Menu resource, if any.
+	///
+	/// Defaults to `None`.
+	pub menu: Option<IdStr>,
+	/// Window class, if not the predefined dialog box class.
+	///
+	/// Defaults to `None`.
+	pub class: Option<IdStr>,
+	/// Title of the dialog.
+	///
+	/// Defaults to empty string.
+	pub title: String,
+	/// Font of the dialog. If `None`, the system font is used.
+	///
+	/// Defaults to 9-point `MS Shell Dlg`.
+	pub font: Option<DlgFont>,
+	/// The controls of the dialog, in tab order.
+	///
+	/// Defaults to none.
+	pub items: Vec<DlgItem>,
+}
+
+impl Default for DlgTemplate {
+	fn default() -> Self {
+		Self {
+			help_id: 0,
+			window_ex_style: co::WS_EX::NoValue,
+			window_style: co::WS::POPUP | co::WS::CAPTION | co::WS::SYSMENU,
+			dialog_style: co::DS::SHELLFONT | co::DS::MODALFRAME,
+			x: 0,
+			y: 0,
+			cx: 200,
+			cy: 100,
+			menu: None,
+			class: None,
+			title: String::default(),
+			font: Some(DlgFont::default()),
+			items: Vec::default(),
+		}
+	}
+}
+
+/// Font of a [`DlgTemplate`](crate::DlgTemplate).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DlgFont {
+	/// Size of the font, in points.
+	///
+	/// Defaults to 9.
+	pub point_size: u16,
+	/// Font weight, like `400` for normal and `700` for bold.
+	///
+	/// Defaults to 400.
+	pub weight: u16,
+	/// Whether the font is italic.
+	///
+	/// Defaults to `false`.
+	pub italic: bool,
+	/// Character set, like `1` for `DEFAULT_CHARSET`.
+	///
+	/// Defaults to 1.
+	pub char_set: u8,
+	/// Name of the typeface.
+	///
+	/// Defaults to `MS Shell Dlg`.
+	pub face_name: String,
+}
+
+impl Default for DlgFont {
+	fn default() -> Self {
+		Self {
+			point_size: 9,
+			weight: 400,
+			italic: false,
+			char_set: 1, // DEFAULT_CHARSET
+			face_name: "MS Shell Dlg".to_owned(),
+		}
+	}
+}
+
+/// A control of a [`DlgTemplate`](crate::DlgTemplate).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DlgItem {
+	/// Context help identifier.
+	///
+	/// Defaults to zero.
+	pub help_id: u32,
+	/// Extended window styles.
+	///
+	/// Defaults to `WS_EX::NoValue`.
+	pub window_ex_style: co::WS_EX,
+	/// Window styles, combined with the control styles, like
+	/// [`co::BS`](crate::co::BS), which are convertible to
+	/// [`co::WS`](crate::co::WS).
+	///
+	/// Defaults to `WS::CHILD | WS::VISIBLE`.
+	pub window_style: co::WS,
+	/// Horizontal position.
+	///
+	/// Defaults to zero.
+	pub x: i16,
+	/// Vertical position.
+	///
+	/// Defaults to zero.
+	pub y: i16,
+	/// Width.
+	///
+	/// Defaults to zero.
+	pub cx: i16,
+	/// Height.
+	///
+	/// Defaults to zero.
+	pub cy: i16,
+	/// Control ID.
+	///
+	/// Defaults to zero.
+	pub id: u32,
+	/// Window class of the control.
+	///
+	/// Defaults to `DlgItemClass::Static`.
+	pub class: DlgItemClass,
+	/// Text of the control, or the ID of a resource, like an icon for a
+	/// static control.
+	///
+	/// Defaults to empty string.
+	pub title: IdStr,
+	/// Creation data, passed to the control in
+	/// [`wm::Create`](crate::msg::wm::Create).
+	///
+	/// Defaults to none.
+	pub creation_data: Vec<u8>,
+}
+
+impl Default for DlgItem {
+	fn default() -> Self {
+		Self {
+			help_id: 0,
+			window_ex_style: co::WS_EX::NoValue,
+			window_style: co::WS::CHILD | co::WS::VISIBLE,
+			x: 0,
+			y: 0,
+			cx: 0,
+			cy: 0,
+			id: 0,
+			class: DlgItemClass::Static,
+			title: IdStr::Str(WString::new()),
+			creation_data: Vec::default(),
+		}
+	}
+}
+
+impl DlgTemplate {
+	/// Serializes the template into the `DLGTEMPLATEEX` layout, which is the
+	/// data of an [`RT::DIALOG`](crate::co::RT::DIALOG) resource.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut dialog_style = self.dialog_style;
+		if self.font.is_some() {
+			dialog_style |= co::DS::SETFONT;
+		} else {
+			dialog_style &= !co::DS::SETFONT; // no font data would follow
+		}
+
+		let mut buf = Vec::<u8>::default();
+		push_u16(&mut buf, 1); // dlgVer
+		push_u16(&mut buf, 0xffff); // signature
+		push_u32(&mut buf, self.help_id);
+		push_u32(&mut buf, self.window_ex_style.raw());
+		push_u32(&mut buf, self.window_style.raw() | dialog_style.raw());
+		push_u16(&mut buf, self.items.len() as _);
+		[self.x, self.y, self.cx, self.cy].iter()
+			.for_each(|n| push_u16(&mut buf, *n as _));
+		push_sz_or_ord(&mut buf, self.menu.as_ref());
+		push_sz_or_ord(&mut buf, self.class.as_ref());
+		push_sz(&mut buf, &self.title);
+
+		if let Some(font) = &self.font {
+			push_u16(&mut buf, font.point_size);
+			push_u16(&mut buf, font.weight);
+			buf.push(font.italic as _);
+			buf.push(font.char_set);
+			push_sz(&mut buf, &font.face_name);
+		}
+
+		for item in self.items.iter() {
+			pad4(&mut buf); // each item starts at a DWORD boundary
+			push_u32(&mut buf, item.help_id);
+			push_u32(&mut buf, item.window_ex_style.raw());
+			push_u32(&mut buf, item.window_style.raw());
+			[item.x, item.y, item.cx, item.cy].iter()
+				.for_each(|n| push_u16(&mut buf, *n as _));
+			push_u32(&mut buf, item.id);
+
+			match item.class.atom() {
+				Some(atom) => push_sz_or_ord(&mut buf, Some(&IdStr::Id(atom))),
+				None => if let DlgItemClass::Name(name) = &item.class {
+					push_sz(&mut buf, name);
+				},
+			}
+			push_sz_or_ord(&mut buf, Some(&item.title));
+
+			push_u16(&mut buf, item.creation_data.len() as _); // extraCount
+			buf.extend_from_slice(&item.creation_data); // already WORD-aligned
+		}
+
+		buf
+	}
+
+	/// Serializes the template with
+	/// [`DlgTemplate::serialize`](crate::DlgTemplate::serialize) into a
+	/// DWORD-aligned buffer, as required by the native functions which take a
+	/// [`DLGTEMPLATE`](crate::DLGTEMPLATE) pointer.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// extern "system" fn dlg_proc(
+	///     hwnd: w::HWND, msg: co::WM, wparam: usize, lparam: isize) -> isize
+	/// {
+	///     0
+	/// }
+	///
+	/// let template = w::DlgTemplate::default();
+	/// let buf = template.serialize_aligned();
+	///
+	/// let ret = unsafe {
+	///     w::HINSTANCE::GetModuleHandle(None)?.DialogBoxIndirectParam(
+	///         &*(buf.as_ptr() as *const w::DLGTEMPLATE),
+	///         None,
+	///         dlg_proc,
+	///         None,
+	///     )?
+	/// };
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn serialize_aligned(&self) -> Vec<u32> {
+		self.serialize()
+			.chunks(4)
+			.map(|b| {
+				let mut dw = [0u8; 4];
+				dw[..b.len()].copy_from_slice(b);
+				u32::from_ne_bytes(dw)
+			})
+			.collect()
+	}
+}
+
+fn push_u16(buf: &mut Vec<u8>, n: u16) {
+	buf.extend_from_slice(&n.to_le_bytes());
+}
+
+fn push_u32(buf: &mut Vec<u8>, n: u32) {
+	buf.extend_from_slice(&n.to_le_bytes());
+}
+
+fn push_sz(buf: &mut Vec<u8>, s: &str) {
+	s.encode_utf16()
+		.chain(std::iter::once(0x0000))
+		.for_each(|ch| push_u16(buf, ch));
+}
+
+/// Writes a `sz_Or_Ord` field: `0x0000` if none, `0xffff` followed by the
+/// ordinal, or a null-terminated string.
+fn push_sz_or_ord(buf: &mut Vec<u8>, val: Option<&IdStr>) {
+	match val {
+		None => push_u16(buf, 0x0000),
+		Some(IdStr::Id(id)) => {
+			push_u16(buf, 0xffff);
+			push_u16(buf, *id);
+		},
+		Some(IdStr::Str(s)) => push_sz(buf, &s.to_string()),
+	}
+}
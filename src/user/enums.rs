@@ -139,6 +139,41 @@ pub enum DispfNup {
 	Nup(co::DMNUP),
 }
 
+/// Window class of a [`DlgItem`](crate::DlgItem).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DlgItemClass {
+	/// Predefined button class, atom `0x0080`.
+	Button,
+	/// Predefined edit class, atom `0x0081`.
+	Edit,
+	/// Predefined static class, atom `0x0082`.
+	Static,
+	/// Predefined list box class, atom `0x0083`.
+	ListBox,
+	/// Predefined scroll bar class, atom `0x0084`.
+	ScrollBar,
+	/// Predefined combo box class, atom `0x0085`.
+	ComboBox,
+	/// Any other registered class, like `SysListView32`.
+	Name(String),
+}
+
+impl DlgItemClass {
+	/// Returns the predefined class atom, if any.
+	#[must_use]
+	pub const fn atom(&self) -> Option<u16> {
+		match self {
+			Self::Button => Some(0x0080),
+			Self::Edit => Some(0x0081),
+			Self::Static => Some(0x0082),
+			Self::ListBox => Some(0x0083),
+			Self::ScrollBar => Some(0x0084),
+			Self::ComboBox => Some(0x0085),
+			Self::Name(_) => None,
+		}
+	}
+}
+
 /// Variant parameter for:
 ///
 /// * [`EnumDisplaySettings`](crate::EnumDisplaySettings).
@@ -46,6 +46,7 @@ extern_sys! { "user32";
 	CreateAcceleratorTableW(PCVOID, i32) -> HANDLE
 	CreateDesktopExW(PCSTR, PCSTR, PCVOID, u32, u32, PVOID, u32, PVOID) -> HANDLE
 	CreateDesktopW(PCSTR, PCSTR, PCVOID, u32, u32, PVOID) -> HANDLE
+	CreateDialogIndirectParamW(HANDLE, PCVOID, HANDLE, PFUNC, isize) -> HANDLE
 	CreateDialogParamW(HANDLE, PCSTR, HANDLE, PFUNC, isize) -> HANDLE
 	CreateMenu() -> HANDLE
 	CreatePopupMenu() -> HANDLE
@@ -186,6 +187,7 @@ extern_sys! { "user32";
 	LoadAcceleratorsW(HANDLE, PCSTR) -> HANDLE
 	LoadCursorW(HANDLE, PCSTR) -> HANDLE
 	LoadIconW(HANDLE, PCSTR) -> HANDLE
+	LoadMenuIndirectW(PCVOID) -> HANDLE
 	LoadMenuW(HANDLE, PCSTR) -> HANDLE
 	LoadStringW(HANDLE, u32, PSTR, i32) -> i32
 	LockSetForegroundWindow(u32) -> BOOL
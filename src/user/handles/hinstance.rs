@@ -17,6 +17,34 @@ impl user_Hinstance for HINSTANCE {}
 /// use winsafe::prelude::*;
 /// ```
 pub trait user_Hinstance: kernel_Hinstance {
+	/// [`CreateDialogIndirectParam`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createdialogindirectparamw)
+	/// function.
+	///
+	/// The template can be built with [`DlgTemplate`](crate::DlgTemplate).
+	///
+	/// # Safety
+	///
+	/// To create a dialog, you must provide a dialog procedure.
+	unsafe fn CreateDialogIndirectParam(&self,
+		dialog_template: &DLGTEMPLATE,
+		hwnd_parent: Option<&HWND>,
+		dialog_proc: DLGPROC,
+		init_param: Option<isize>,
+	) -> SysResult<HWND>
+	{
+		ptr_to_sysresult_handle(
+			unsafe {
+				ffi::CreateDialogIndirectParamW(
+					self.ptr(),
+					dialog_template as *const _ as _,
+					hwnd_parent.map_or(std::ptr::null_mut(), |h| h.ptr()),
+					dialog_proc as _,
+					init_param.unwrap_or_default(),
+				)
+			},
+		)
+	}
+
 	/// [`CreateDialogParam`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createdialogparamw)
 	/// function.
 	///
@@ -46,6 +74,8 @@ pub trait user_Hinstance: kernel_Hinstance {
 	/// [`DialogBoxIndirectParam`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-dialogboxindirectparamw)
 	/// function.
 	///
+	/// The template can be built with [`DlgTemplate`](crate::DlgTemplate).
+	///
 	/// # Safety
 	///
 	/// To create a dialog, you must provide a dialog procedure.
@@ -495,6 +495,24 @@ pub trait user_Hmenu: Handle {
 		unsafe { ffi::IsMenu(self.ptr()) != 0 }
 	}
 
+	/// [`LoadMenuIndirect`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-loadmenuindirectw)
+	/// function.
+	///
+	/// The template can be built with [`MenuTemplate`](crate::MenuTemplate).
+	/// The data is copied into a DWORD-aligned buffer before the call.
+	///
+	/// **Note:** If not attached to a window, must be paired with an
+	/// [`HMENU::DestroyMenu`](crate::prelude::user_Hmenu::DestroyMenu) call.
+	#[must_use]
+	fn LoadMenuIndirect(menu_template: &[u8]) -> SysResult<HMENU> {
+		let mut aligned = vec![0u32; menu_template.len().div_ceil(4)];
+		unsafe {
+			std::ptr::copy_nonoverlapping(
+				menu_template.as_ptr(), aligned.as_mut_ptr() as *mut u8, menu_template.len());
+		}
+		ptr_to_sysresult_handle(unsafe { ffi::LoadMenuIndirectW(aligned.as_ptr() as _) })
+	}
+
 	/// [`RemoveMenu`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-removemenu)
 	/// function.
 	fn RemoveMenu(&self, id_or_pos: IdPos) -> SysResult<()> {
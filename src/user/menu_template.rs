@@ -0,0 +1,371 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::privs::bytes::*;
+use crate::prelude::*;
+
+/// Flag of `bResInfo` which marks the last item of a menu.
+const RESINFO_END: u16 = 0x0080;
+/// Flag of `bResInfo` which marks an item that opens a submenu.
+const RESINFO_POPUP: u16 = 0x0001;
+/// Maximum nesting of submenus accepted by the parser.
+const MAX_DEPTH: usize = 64;
+
+/// Declarative description of a menu, which is serialized into the
+/// [`MENUEX_TEMPLATE`](https://learn.microsoft.com/en-us/windows/win32/menurc/menuex-template-header)
+/// format, the data of an [`RT::MENU`](crate::co::RT::MENU) resource created
+/// with the `MENUEX` statement.
+///
+/// A menu bar is created with
+/// [`MenuTemplate::to_hmenu`](crate::MenuTemplate::to_hmenu), and a popup
+/// menu, like a context menu, with
+/// [`MenuTemplate::to_popup_hmenu`](crate::MenuTemplate::to_popup_hmenu).
+///
+/// # Examples
+///
+/// Creating the main menu of a window, and handling its commands:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, gui, seq_ids};
+///
+/// seq_ids! {
+///     ID_FILE_OPEN = 2001;
+///     ID_FILE_EXIT
+///     ID_VIEW_STATUS
+/// }
+///
+/// let template = w::MenuTemplate {
+///     items: vec![
+///         w::MenuTemplateItem::submenu("&File", vec![
+///             w::MenuTemplateItem::entry(ID_FILE_OPEN, "&Open...\tCtrl+O"),
+///             w::MenuTemplateItem::separator(),
+///             w::MenuTemplateItem::entry(ID_FILE_EXIT, "E&xit"),
+///         ]),
+///         w::MenuTemplateItem::submenu("&View", vec![
+///             w::MenuTemplateItem::entry(ID_VIEW_STATUS, "&Status bar").checked(true),
+///         ]),
+///     ],
+///     ..Default::default()
+/// };
+///
+/// let wnd = gui::WindowMain::new(gui::WindowMainOpts {
+///     menu: template.to_hmenu()?,
+///     ..Default::default()
+/// });
+///
+/// let wnd2 = wnd.clone();
+/// wnd.on().wm_command_accel_menu(ID_FILE_EXIT, move || {
+///     wnd2.hwnd().DestroyWindow()?;
+///     Ok(())
+/// });
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MenuTemplate {
+	/// Context help identifier of the menu.
+	pub help_id: u32,
+	/// The top-level items. For a menu bar, these are usually submenus.
+	pub items: Vec<MenuTemplateItem>,
+}
+
+/// An item of a [`MenuTemplate`](crate::MenuTemplate): a command entry, a
+/// separator or a submenu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuTemplateItem {
+	/// The item type, like [`MFT::SEPARATOR`](crate::co::MFT::SEPARATOR) or
+	/// [`MFT::RADIOCHECK`](crate::co::MFT::RADIOCHECK).
+	pub item_type: co::MFT,
+	/// The item state, like [`MFS::CHECKED`](crate::co::MFS::CHECKED) or
+	/// [`MFS::DISABLED`](crate::co::MFS::DISABLED).
+	pub state: co::MFS,
+	/// The command ID, sent in [`wm::Command`](crate::msg::wm::Command) when
+	/// the item is clicked.
+	pub id: u16,
+	/// The text of the item, which can contain a `\t` followed by the
+	/// accelerator description.
+	pub text: String,
+	/// Context help identifier, only stored for submenus.
+	pub help_id: u32,
+	/// The items of the submenu, if this item opens one. A submenu must have
+	/// at least one item, otherwise
+	/// [`MenuTemplate::serialize`](crate::MenuTemplate::serialize) fails.
+	pub submenu: Option<Vec<MenuTemplateItem>>,
+}
+
+impl MenuTemplateItem {
+	/// Creates a command entry.
+	#[must_use]
+	pub fn entry(id: u16, text: &str) -> Self {
+		Self {
+			item_type: co::MFT::STRING,
+			state: co::MFS::ENABLED,
+			id,
+			text: text.to_owned(),
+			help_id: 0,
+			submenu: None,
+		}
+	}
+
+	/// Creates a separator.
+	#[must_use]
+	pub fn separator() -> Self {
+		Self {
+			item_type: co::MFT::SEPARATOR,
+			..Self::entry(0, "")
+		}
+	}
+
+	/// Creates an item which opens a submenu.
+	#[must_use]
+	pub fn submenu(text: &str, items: Vec<MenuTemplateItem>) -> Self {
+		Self {
+			submenu: Some(items),
+			..Self::entry(0, text)
+		}
+	}
+
+	/// Returns the item with the [`MFS::CHECKED`](crate::co::MFS::CHECKED)
+	/// state set or cleared.
+	#[must_use]
+	pub fn checked(mut self, checked: bool) -> Self {
+		if checked {
+			self.state |= co::MFS::CHECKED;
+		} else {
+			self.state &= !co::MFS::CHECKED;
+		}
+		self
+	}
+
+	/// Returns the item with the [`MFS::DISABLED`](crate::co::MFS::DISABLED)
+	/// state set or cleared.
+	#[must_use]
+	pub fn disabled(mut self, disabled: bool) -> Self {
+		if disabled {
+			self.state |= co::MFS::DISABLED;
+		} else {
+			self.state &= !co::MFS::DISABLED;
+		}
+		self
+	}
+
+	/// Returns the item with the
+	/// [`MFT::RADIOCHECK`](crate::co::MFT::RADIOCHECK) type, so the check mark
+	/// is displayed as a radio bullet.
+	#[must_use]
+	pub fn radio(mut self) -> Self {
+		self.item_type |= co::MFT::RADIOCHECK;
+		self
+	}
+
+	/// Returns `true` if the item is a separator.
+	#[must_use]
+	pub fn is_separator(&self) -> bool {
+		self.item_type.has(co::MFT::SEPARATOR)
+	}
+}
+
+impl MenuTemplate {
+	/// Parses a `MENUEX_TEMPLATE`, like the data of a menu resource created
+	/// with the `MENUEX` statement.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is malformed, or if it's in the older `MENU` format.
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		if read_u16(data, 0)? != 1 { // wVersion
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let offset = read_u16(data, 2)? as usize; // relative to the end of this field
+		let help_id = read_u32(data, 4)?;
+
+		let mut pos = 4 + offset;
+		let items = if pos >= data.len() {
+			Vec::default()
+		} else {
+			Self::parse_items(data, &mut pos, 0)?
+		};
+		Ok(Self { help_id, items })
+	}
+
+	fn parse_items(
+		data: &[u8],
+		pos: &mut usize,
+		depth: usize,
+	) -> SysResult<Vec<MenuTemplateItem>>
+	{
+		if depth > MAX_DEPTH {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let mut items = Vec::<MenuTemplateItem>::default();
+		loop {
+			let item_type = unsafe { co::MFT::from_raw(read_u32(data, *pos)?) };
+			let state = unsafe { co::MFS::from_raw(read_u32(data, *pos + 4)?) };
+			let id = u16::try_from(read_u32(data, *pos + 8)?)
+				.map_err(|_| co::ERROR::INVALID_DATA)?;
+			let res_info = read_u16(data, *pos + 12)?;
+			*pos += 14;
+
+			let mut chars = Vec::<u16>::default();
+			loop {
+				let ch = read_u16(data, *pos)?;
+				*pos += 2;
+				if ch == 0x0000 {
+					break;
+				}
+				chars.push(ch);
+			}
+			let text = String::from_utf16(&chars)
+				.map_err(|_| co::ERROR::INVALID_DATA)?;
+			*pos = align4(*pos);
+
+			let (help_id, submenu) = if res_info & RESINFO_POPUP != 0 {
+				let help_id = read_u32(data, *pos)?;
+				*pos += 4;
+				(help_id, Some(Self::parse_items(data, pos, depth + 1)?))
+			} else {
+				(0, None)
+			};
+
+			items.push(MenuTemplateItem { item_type, state, id, text, help_id, submenu });
+			if res_info & RESINFO_END != 0 {
+				break;
+			}
+		}
+		Ok(items)
+	}
+
+	/// Serializes the menu into the `MENUEX_TEMPLATE` format, which can be
+	/// parsed back with [`MenuTemplate::parse`](crate::MenuTemplate::parse).
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if any submenu has no items, since the format cannot represent it.
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let mut buf = Vec::<u8>::default();
+		buf.extend_from_slice(&1u16.to_le_bytes()); // wVersion
+		buf.extend_from_slice(&4u16.to_le_bytes()); // wOffset
+		buf.extend_from_slice(&self.help_id.to_le_bytes());
+		Self::serialize_items(&mut buf, &self.items)?;
+		Ok(buf)
+	}
+
+	fn serialize_items(buf: &mut Vec<u8>, items: &[MenuTemplateItem]) -> SysResult<()> {
+		for (idx, item) in items.iter().enumerate() {
+			if item.submenu.as_ref().is_some_and(|sub_items| sub_items.is_empty()) {
+				return Err(co::ERROR::INVALID_DATA); // the next sibling would be read as its first child
+			}
+
+			let mut res_info = 0;
+			if item.submenu.is_some() {
+				res_info |= RESINFO_POPUP;
+			}
+			if idx == items.len() - 1 {
+				res_info |= RESINFO_END;
+			}
+
+			buf.extend_from_slice(&item.item_type.raw().to_le_bytes());
+			buf.extend_from_slice(&item.state.raw().to_le_bytes());
+			buf.extend_from_slice(&(item.id as u32).to_le_bytes());
+			buf.extend_from_slice(&res_info.to_le_bytes());
+			item.text.encode_utf16()
+				.chain(std::iter::once(0x0000))
+				.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+			pad4(buf);
+
+			if let Some(sub_items) = &item.submenu {
+				buf.extend_from_slice(&item.help_id.to_le_bytes());
+				Self::serialize_items(buf, sub_items)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Creates a menu bar with
+	/// [`HMENU::LoadMenuIndirect`](crate::prelude::user_Hmenu::LoadMenuIndirect).
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if any submenu has no items.
+	///
+	/// **Note:** If not attached to a window, must be paired with an
+	/// [`HMENU::DestroyMenu`](crate::prelude::user_Hmenu::DestroyMenu) call.
+	#[must_use]
+	pub fn to_hmenu(&self) -> SysResult<HMENU> {
+		HMENU::LoadMenuIndirect(&self.serialize()?)
+	}
+
+	/// Creates a popup menu with
+	/// [`HMENU::CreatePopupMenu`](crate::prelude::user_Hmenu::CreatePopupMenu),
+	/// inserting the items with
+	/// [`HMENU::InsertMenuItem`](crate::prelude::user_Hmenu::InsertMenuItem).
+	/// The menu can be displayed with
+	/// [`HMENU::track_popup_menu_at_point`](crate::prelude::user_Hmenu::track_popup_menu_at_point).
+	///
+	/// **Note:** Must be paired with an
+	/// [`HMENU::DestroyMenu`](crate::prelude::user_Hmenu::DestroyMenu) call.
+	#[must_use]
+	pub fn to_popup_hmenu(&self) -> SysResult<HMENU> {
+		Self::create_popup(&self.items)
+	}
+
+	fn create_popup(items: &[MenuTemplateItem]) -> SysResult<HMENU> {
+		let mut hmenu = HMENU::CreatePopupMenu()?;
+		if let Err(e) = Self::insert_items(&hmenu, items) {
+			hmenu.DestroyMenu().ok(); // also destroys the submenus already inserted
+			return Err(e);
+		}
+		Ok(hmenu)
+	}
+
+	fn insert_items(hmenu: &HMENU, items: &[MenuTemplateItem]) -> SysResult<()> {
+		for (idx, item) in items.iter().enumerate() {
+			let mut text = WString::from_str(&item.text);
+			let mut mii = MENUITEMINFO::default();
+			mii.fMask = co::MIIM::FTYPE | co::MIIM::STATE | co::MIIM::ID;
+			mii.fType = item.item_type;
+			mii.fState = item.state;
+			mii.wID = item.id as _;
+
+			if !item.is_separator() {
+				mii.fMask |= co::MIIM::STRING;
+				mii.dwTypeData = unsafe { text.as_mut_ptr() };
+			}
+
+			let mut hsub = match &item.submenu {
+				Some(sub_items) => {
+					let hsub = Self::create_popup(sub_items)?;
+					mii.fMask |= co::MIIM::SUBMENU;
+					mii.hSubMenu = unsafe { hsub.raw_copy() };
+					Some(hsub)
+				},
+				None => None,
+			};
+
+			if let Err(e) = hmenu.InsertMenuItem(IdPos::Pos(idx as _), &mii) {
+				if let Some(hsub) = hsub.as_mut() {
+					hsub.DestroyMenu().ok(); // not attached yet
+				}
+				return Err(e);
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns the command IDs of all entries, including the ones inside
+	/// submenus, which can be handled with
+	/// [`gui::events::WindowEvents::wm_command_accel_menu`](crate::gui::events::WindowEvents::wm_command_accel_menu).
+	#[must_use]
+	pub fn command_ids(&self) -> Vec<u16> {
+		let mut ids = Vec::<u16>::default();
+		Self::collect_ids(&self.items, &mut ids);
+		ids
+	}
+
+	fn collect_ids(items: &[MenuTemplateItem], ids: &mut Vec<u16>) {
+		for item in items.iter() {
+			match &item.submenu {
+				Some(sub_items) => Self::collect_ids(sub_items, ids),
+				None => if !item.is_separator() {
+					ids.push(item.id);
+				},
+			}
+		}
+	}
+}
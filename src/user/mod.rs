@@ -1,9 +1,12 @@
 #![cfg(feature = "user")]
 
+mod accel_table;
 mod aliases;
+mod dlg_template;
 mod enums;
 mod funcs;
 mod handles;
+mod menu_template;
 mod msg_traits;
 mod proc;
 mod structs;
@@ -16,10 +19,13 @@ pub mod guard;
 pub mod messages;
 
 pub mod decl {
+	pub use super::accel_table::AccelTable;
 	pub use super::aliases::*;
+	pub use super::dlg_template::{DlgFont, DlgItem, DlgTemplate};
 	pub use super::enums::*;
 	pub use super::funcs::*;
 	pub use super::handles::decl::*;
+	pub use super::menu_template::{MenuTemplate, MenuTemplateItem};
 	pub use super::structs::*;
 }
 
@@ -2,6 +2,7 @@
 
 mod handles;
 mod structs;
+mod version_info;
 
 pub(in crate::version) mod ffi;
 pub mod co;
@@ -10,6 +11,7 @@ pub mod guard;
 pub mod decl {
 	pub use super::handles::decl::*;
 	pub use super::structs::*;
+	pub use super::version_info::{VersionInfo, VersionStringTable};
 }
 
 pub mod traits {
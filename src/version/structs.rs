@@ -6,6 +6,7 @@ use crate::decl::*;
 /// [`VS_FIXEDFILEINFO`](https://learn.microsoft.com/en-us/windows/win32/api/verrsrc/ns-verrsrc-vs_fixedfileinfo)
 /// struct.
 #[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct VS_FIXEDFILEINFO {
 	dwSignature: u32,
 	pub dwStrucVersion: u32,
@@ -26,6 +27,7 @@ impl Default for VS_FIXEDFILEINFO {
 	fn default() -> Self {
 		let mut obj = unsafe { std::mem::zeroed::<Self>() };
 		obj.dwSignature = 0xfeef_04bd;
+		obj.dwStrucVersion = 0x0001_0000; // value used in VS_VERSIONINFO resources
 		obj
 	}
 }
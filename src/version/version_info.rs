@@ -0,0 +1,354 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::privs::bytes::*;
+
+/// Signature of [`VS_FIXEDFILEINFO`](crate::VS_FIXEDFILEINFO).
+const FIXED_SIGNATURE: u32 = 0xfeef_04bd;
+
+/// Block types, as stored in the `wType` field.
+const TYPE_BINARY: u16 = 0;
+const TYPE_TEXT: u16 = 1;
+
+/// Contents of a
+/// [`VS_VERSIONINFO`](https://learn.microsoft.com/en-us/windows/win32/menurc/vs-versioninfo)
+/// resource, which is the version information of an executable file.
+///
+/// It can be parsed from the raw resource data – like the bytes returned by
+/// [`HINSTANCE::LockResource`](crate::prelude::kernel_Hinstance::LockResource)
+/// for [`co::RT::VERSION`](crate::co::RT::VERSION) – and serialized back, so
+/// it can be written into an executable with
+/// [`HUPDATERSRC::UpdateResource`](crate::prelude::kernel_Hupdatersrc::UpdateResource).
+///
+/// # Examples
+///
+/// Stamping the version information into an executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let lang_id = w::LANGID::new(co::LANG::ENGLISH, co::SUBLANG::ENGLISH_US);
+///
+/// let mut info = w::VersionInfo::default();
+/// info.fixed.set_dwFileVersion([1, 2, 0, 0]);
+/// info.fixed.set_dwProductVersion([1, 2, 0, 0]);
+/// info.fixed.dwFileOS = co::VOS::NT_WINDOWS32;
+/// info.fixed.dwFileType = co::VFT::APP;
+/// info.set_string(lang_id, co::CP::UTF16, "FileVersion", "1.2.0.0");
+/// info.set_string(lang_id, co::CP::UTF16, "ProductName", "My App");
+/// info.translations.push((lang_id, co::CP::UTF16));
+///
+/// let hres_update = w::HUPDATERSRC::BeginUpdateResource("my_app.exe", false)?;
+/// hres_update.UpdateResource(
+///     w::RtStr::Rt(co::RT::VERSION),
+///     w::IdStr::Id(1),
+///     lang_id,
+///     &info.serialize()?,
+/// )?;
+///
+/// // EndUpdateResource() called automatically
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct VersionInfo {
+	/// The fixed information, which holds the numeric version.
+	pub fixed: VS_FIXEDFILEINFO,
+	/// The `StringFileInfo` block, with one
+	/// [`VersionStringTable`](crate::VersionStringTable) for each language and
+	/// code page.
+	pub string_tables: Vec<VersionStringTable>,
+	/// The `Translation` value of the `VarFileInfo` block, with the languages
+	/// and code pages supported by the file.
+	pub translations: Vec<(LANGID, co::CP)>,
+}
+
+/// A
+/// [`StringTable`](https://learn.microsoft.com/en-us/windows/win32/menurc/stringtable)
+/// block of a [`VersionInfo`](crate::VersionInfo), which holds the strings of
+/// a given language and code page.
+#[derive(Clone, PartialEq, Eq)]
+pub struct VersionStringTable {
+	/// Language of the strings.
+	pub lang_id: LANGID,
+	/// Code page of the strings.
+	pub code_page: co::CP,
+	/// Names and values of the strings, like `("FileVersion", "1.2.0.0")`, in
+	/// the order they are stored.
+	pub strings: Vec<(String, String)>,
+}
+
+impl VersionInfo {
+	/// Parses the raw `VS_VERSIONINFO` data.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if the data is malformed.
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let root = Block::parse(data)?;
+		if root.key != "VS_VERSION_INFO" {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let mut info = Self::default();
+		if root.value_len > 0 {
+			info.fixed = Self::parse_fixed(root.value)?;
+		}
+
+		for child in root.children()? {
+			match child.key.as_str() {
+				"StringFileInfo" => for table in child.children()? {
+					info.string_tables.push(Self::parse_string_table(&table)?);
+				},
+				"VarFileInfo" => for var in child.children()? {
+					if var.key == "Translation" {
+						info.translations.extend(
+							var.binary_value()?
+								.chunks_exact(4)
+								.map(|b| unsafe {(
+									LANGID::from_raw(u16::from_le_bytes([b[0], b[1]])),
+									co::CP::from_raw(u16::from_le_bytes([b[2], b[3]])),
+								)}),
+						);
+					}
+				},
+				_ => {}, // unknown blocks are ignored
+			}
+		}
+
+		Ok(info)
+	}
+
+	/// Serializes the data into the raw `VS_VERSIONINFO` format.
+	///
+	/// The `StringFileInfo` and `VarFileInfo` blocks are omitted if there are
+	/// no string tables or no translations, respectively.
+	///
+	/// Fails with [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA)
+	/// if any block is longer than 64 KB, which is the limit of the format.
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let mut buf = Vec::<u8>::default();
+
+		let fixed = unsafe {
+			std::slice::from_raw_parts(
+				&self.fixed as *const _ as *const u8,
+				std::mem::size_of::<VS_FIXEDFILEINFO>(),
+			)
+		};
+
+		Block::write(&mut buf, "VS_VERSION_INFO", TYPE_BINARY, fixed, fixed.len() as _, |buf| {
+			if !self.string_tables.is_empty() {
+				Block::write(buf, "StringFileInfo", TYPE_TEXT, &[], 0, |buf| {
+					for table in self.string_tables.iter() {
+						let key = format!("{:04x}{:04x}", table.lang_id.raw(), table.code_page.raw());
+						Block::write(buf, &key, TYPE_TEXT, &[], 0, |buf| {
+							for (name, value) in table.strings.iter() {
+								let value = Block::encode_sz(value);
+								let num_chars = value.len() / 2;
+								Block::write(buf, name, TYPE_TEXT, &value, num_chars as _, |_| Ok(()))?;
+							}
+							Ok(())
+						})?;
+					}
+					Ok(())
+				})?;
+			}
+
+			if !self.translations.is_empty() {
+				Block::write(buf, "VarFileInfo", TYPE_TEXT, &[], 0, |buf| {
+					let value = self.translations.iter()
+						.flat_map(|(lang_id, cp)| {
+							let (lang_id, cp) = (lang_id.raw().to_le_bytes(), cp.raw().to_le_bytes());
+							[lang_id[0], lang_id[1], cp[0], cp[1]]
+						})
+						.collect::<Vec<_>>();
+					Block::write(buf, "Translation", TYPE_BINARY, &value, value.len() as _, |_| Ok(()))
+				})?;
+			}
+			Ok(())
+		})?;
+
+		Ok(buf)
+	}
+
+	/// Returns the value of the string with the given name, in the string table
+	/// of the given language and code page, if any.
+	#[must_use]
+	pub fn string(&self, lang_id: LANGID, code_page: co::CP, name: &str) -> Option<&str> {
+		self.string_tables.iter()
+			.find(|t| t.lang_id == lang_id && t.code_page == code_page)
+			.and_then(|t| t.strings.iter().find(|(n, _)| n == name))
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// Sets the value of the string with the given name, in the string table
+	/// of the given language and code page. The string and the table are
+	/// created, if needed.
+	pub fn set_string(&mut self,
+		lang_id: LANGID,
+		code_page: co::CP,
+		name: &str,
+		value: &str,
+	)
+	{
+		let table = match self.string_tables.iter()
+			.position(|t| t.lang_id == lang_id && t.code_page == code_page)
+		{
+			Some(idx) => &mut self.string_tables[idx],
+			None => {
+				self.string_tables.push(VersionStringTable {
+					lang_id,
+					code_page,
+					strings: Vec::default(),
+				});
+				self.string_tables.last_mut().unwrap()
+			},
+		};
+
+		match table.strings.iter_mut().find(|(n, _)| n == name) {
+			Some((_, v)) => *v = value.to_owned(),
+			None => table.strings.push((name.to_owned(), value.to_owned())),
+		}
+	}
+
+	fn parse_fixed(data: &[u8]) -> SysResult<VS_FIXEDFILEINFO> {
+		if data.len() < std::mem::size_of::<VS_FIXEDFILEINFO>()
+			|| u32::from_le_bytes([data[0], data[1], data[2], data[3]]) != FIXED_SIGNATURE
+		{
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		Ok(unsafe { std::ptr::read_unaligned(data.as_ptr() as *const VS_FIXEDFILEINFO) })
+	}
+
+	fn parse_string_table(table: &Block) -> SysResult<VersionStringTable> {
+		if table.key.len() != 8 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let lang_cp = u32::from_str_radix(&table.key, 16)
+			.map_err(|_| co::ERROR::INVALID_DATA)?;
+
+		Ok(VersionStringTable {
+			lang_id: unsafe { LANGID::from_raw((lang_cp >> 16) as _) },
+			code_page: unsafe { co::CP::from_raw(lang_cp as _) },
+			strings: table.children()?
+				.iter()
+				.map(|s| Ok((s.key.clone(), s.text_value()?)))
+				.collect::<SysResult<Vec<_>>>()?,
+		})
+	}
+}
+
+/// A generic block of the `VS_VERSIONINFO` tree, with its header, key, value
+/// and children.
+struct Block<'a> {
+	key: String,
+	value_len: u16,
+	value: &'a [u8], // everything after the key, up to the block end
+	next: usize, // offset of the value within the block, to find the children
+	data: &'a [u8], // the whole block
+}
+
+impl<'a> Block<'a> {
+	/// Parses the block at the start of the data.
+	fn parse(data: &'a [u8]) -> SysResult<Self> {
+		if data.len() < 6 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let len = u16::from_le_bytes([data[0], data[1]]) as usize;
+		let value_len = u16::from_le_bytes([data[2], data[3]]);
+		if len < 6 || len > data.len() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let data = &data[..len];
+
+		let mut key = Vec::<u16>::default();
+		let mut pos = 6;
+		loop {
+			let ch = data.get(pos..pos + 2)
+				.map(|b| u16::from_le_bytes([b[0], b[1]]))
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			pos += 2;
+			if ch == 0x0000 {
+				break;
+			}
+			key.push(ch);
+		}
+		pos = align4(pos).min(len);
+
+		Ok(Self {
+			key: String::from_utf16(&key).map_err(|_| co::ERROR::INVALID_DATA)?,
+			value_len,
+			value: &data[pos..],
+			next: pos,
+			data,
+		})
+	}
+
+	/// Returns the value as binary data, whose length is in bytes.
+	fn binary_value(&self) -> SysResult<&'a [u8]> {
+		self.value.get(..self.value_len as usize)
+			.ok_or(co::ERROR::INVALID_DATA)
+	}
+
+	/// Returns the value as a null-terminated string. Since some compilers
+	/// write the length in bytes instead of chars, the string is read up to the
+	/// first null.
+	fn text_value(&self) -> SysResult<String> {
+		if self.value_len == 0 {
+			return Ok(String::default());
+		}
+		let units = self.value.chunks_exact(2)
+			.map(|b| u16::from_le_bytes([b[0], b[1]]))
+			.take_while(|ch| *ch != 0x0000)
+			.collect::<Vec<_>>();
+		String::from_utf16(&units).map_err(|_| co::ERROR::INVALID_DATA)
+	}
+
+	/// Parses the children blocks, which come after the value.
+	fn children(&self) -> SysResult<Vec<Block<'a>>> {
+		// The value length of text blocks is not reliable, but blocks with
+		// children don't have text values, only binary ones or none.
+		let mut pos = align4(self.next + self.value_len as usize);
+		let mut children = Vec::default();
+		while pos < self.data.len() {
+			let child = Block::parse(&self.data[pos..])?;
+			pos = align4(pos + child.data.len());
+			children.push(child);
+		}
+		Ok(children)
+	}
+
+	/// Appends a block, with its children written by the closure.
+	///
+	/// Fails if the block doesn't fit the 16-bit `wLength` field; since the
+	/// block contains its value, this also catches a truncated `value_len`.
+	fn write(
+		buf: &mut Vec<u8>,
+		key: &str,
+		block_type: u16,
+		value: &[u8],
+		value_len: u16,
+		children: impl FnOnce(&mut Vec<u8>) -> SysResult<()>,
+	) -> SysResult<()>
+	{
+		pad4(buf);
+		let start = buf.len();
+		buf.extend_from_slice(&[0, 0]); // length, written at the end
+		buf.extend_from_slice(&value_len.to_le_bytes());
+		buf.extend_from_slice(&block_type.to_le_bytes());
+		buf.extend_from_slice(&Self::encode_sz(key));
+		pad4(buf);
+		buf.extend_from_slice(value);
+		children(buf)?;
+
+		let len = u16::try_from(buf.len() - start)
+			.map_err(|_| co::ERROR::INVALID_DATA)?;
+		buf[start..start + 2].copy_from_slice(&len.to_le_bytes());
+		Ok(())
+	}
+
+	#[must_use]
+	fn encode_sz(s: &str) -> Vec<u8> {
+		s.encode_utf16()
+			.chain(std::iter::once(0x0000))
+			.flat_map(|ch| ch.to_le_bytes())
+			.collect()
+	}
+}